@@ -0,0 +1,472 @@
+/**
+ * 远程音频模块
+ *
+ * 接收被控端的音频（与 RustDesk 一致为 Opus 编码）并解码为 PCM：
+ * - AudioFormat 给出采样率和声道数，之后的 AudioFrame 按该格式解码
+ * - 解码后的 PCM 先进入抖动缓冲区，积累到目标时长才开始输出，欠载后重新积累
+ * - 缓冲超过上限时丢弃最旧的数据，避免延迟越积越大
+ * - ArkTS 的 AudioRenderer 按需拉取固定长度的 PCM（16 位交错），不足部分补静音
+ * - 静音时丢弃收到的音频（由连接通知对端停止发送）
+ *
+ * 语音通话（任一端发起，对端接听或拒绝）：
+ * - 通话建立后先发送麦克风的音频格式，之后发送 Opus 编码的麦克风音频
+ * - ArkTS 采集的 PCM 长度不固定，凑够 20 毫秒再编码为一帧
+ * - 对端在通话中的声音仍走上面的远程音频播放
+ *
+ */
+
+use crate::protocol::{AudioFormat, VoiceCallRequest, VoiceCallResponse};
+use magnum_opus::{Application, Channels, Decoder, Encoder};
+use std::collections::VecDeque;
+
+/// 开始输出前积累的时长（毫秒）
+const JITTER_TARGET_MS: usize = 60;
+/// 最多缓冲的时长（毫秒）
+const JITTER_MAX_MS: usize = 200;
+/// Opus 单帧的最大时长（毫秒）
+const OPUS_MAX_FRAME_MS: usize = 120;
+/// 麦克风音频的编码帧长（毫秒）
+const VOICE_FRAME_MS: usize = 20;
+/// 单帧 Opus 数据的最大长度
+const OPUS_MAX_PACKET: usize = 4000;
+
+/// 麦克风 PCM 的格式（ArkTS 按此格式采集 16 位 PCM）
+pub const MICROPHONE_FORMAT: AudioFormat = AudioFormat { sample_rate: 16000, channels: 1 };
+
+/// 每毫秒的采样数（所有声道）
+fn samples_per_ms(format: AudioFormat) -> usize {
+    format.sample_rate as usize / 1000 * format.channels as usize
+}
+
+/// 抖动缓冲区（16 位交错 PCM）
+#[derive(Debug)]
+pub struct JitterBuffer {
+    samples: VecDeque<i16>,
+    /// 开始输出前需要积累的采样数
+    target: usize,
+    /// 最多缓冲的采样数
+    max: usize,
+    /// 正在积累（启动或欠载后）
+    buffering: bool,
+}
+
+impl JitterBuffer {
+    pub fn new(format: AudioFormat) -> Self {
+        let per_ms = samples_per_ms(format);
+        Self {
+            samples: VecDeque::new(),
+            target: per_ms * JITTER_TARGET_MS,
+            max: per_ms * JITTER_MAX_MS,
+            buffering: true,
+        }
+    }
+
+    /// 缓冲的采样数
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// 加入解码后的 PCM（超过上限时丢弃最旧的数据）
+    pub fn push(&mut self, pcm: &[i16]) {
+        self.samples.extend(pcm);
+        if self.samples.len() > self.max {
+            let excess = self.samples.len() - self.max;
+            self.samples.drain(..excess);
+        }
+        if self.buffering && self.samples.len() >= self.target {
+            self.buffering = false;
+        }
+    }
+
+    /// 填充输出，返回实际输出的采样数（其余补静音，欠载时重新积累）
+    pub fn read(&mut self, out: &mut [i16]) -> usize {
+        let count = if self.buffering { 0 } else { out.len().min(self.samples.len()) };
+        for (slot, sample) in out.iter_mut().zip(self.samples.drain(..count)) {
+            *slot = sample;
+        }
+        out[count..].fill(0);
+        if count < out.len() && !self.buffering {
+            log::debug!("音频欠载，重新缓冲");
+            self.buffering = true;
+        }
+        count
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.buffering = true;
+    }
+}
+
+/// 会话的远程音频播放状态
+#[derive(Default)]
+pub struct AudioPlayback {
+    format: Option<AudioFormat>,
+    decoder: Option<Decoder>,
+    buffer: Option<JitterBuffer>,
+    muted: bool,
+    /// 解码缓冲区（一帧的最大采样数）
+    pcm: Vec<i16>,
+}
+
+impl AudioPlayback {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前的音频格式（尚未收到时为 None）
+    pub fn format(&self) -> Option<AudioFormat> {
+        self.format
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// 静音 / 取消静音（静音时清空缓冲）
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        if muted {
+            if let Some(buffer) = self.buffer.as_mut() {
+                buffer.clear();
+            }
+        }
+    }
+
+    /// 对端的音频格式（格式变化时重建解码器）
+    pub fn set_format(&mut self, format: AudioFormat) -> Result<(), String> {
+        if self.format == Some(format) {
+            return Ok(());
+        }
+        let channels = if format.channels == 1 { Channels::Mono } else { Channels::Stereo };
+        let decoder = Decoder::new(format.sample_rate, channels).map_err(|e| format!("创建音频解码器失败: {}", e))?;
+        log::info!("音频格式: {} Hz, {} 声道", format.sample_rate, format.channels);
+        self.format = Some(format);
+        self.decoder = Some(decoder);
+        self.buffer = Some(JitterBuffer::new(format));
+        self.pcm = vec![0; samples_per_ms(format) * OPUS_MAX_FRAME_MS];
+        Ok(())
+    }
+
+    /// 解码一帧 Opus 数据放入缓冲（静音时丢弃）
+    pub fn push_frame(&mut self, data: &[u8]) -> Result<(), String> {
+        if self.muted {
+            return Ok(());
+        }
+        let (format, decoder, buffer) = match (self.format, self.decoder.as_mut(), self.buffer.as_mut()) {
+            (Some(format), Some(decoder), Some(buffer)) => (format, decoder, buffer),
+            _ => return Err("尚未收到音频格式".to_string()),
+        };
+        let frames = decoder
+            .decode(data, &mut self.pcm, false)
+            .map_err(|e| format!("音频解码失败: {}", e))?;
+        buffer.push(&self.pcm[..frames * format.channels as usize]);
+        Ok(())
+    }
+
+    /// 拉取 frames 帧 PCM（16 位交错，不足部分为静音），尚未收到音频格式时返回 None
+    pub fn read(&mut self, frames: usize) -> Option<Vec<i16>> {
+        let format = self.format?;
+        let mut out = vec![0; frames * format.channels as usize];
+        if !self.muted {
+            if let Some(buffer) = self.buffer.as_mut() {
+                buffer.read(&mut out);
+            }
+        }
+        Some(out)
+    }
+
+    /// 连接断开：丢弃格式和缓冲（保留静音设置）
+    pub fn reset(&mut self) {
+        *self = Self { muted: self.muted, ..Self::default() };
+    }
+}
+
+/// 麦克风音频编码器
+pub struct VoiceEncoder {
+    encoder: Encoder,
+    /// 不足一帧的采样
+    pending: Vec<i16>,
+    /// 每帧的采样数
+    frame: usize,
+    output: Vec<u8>,
+}
+
+impl VoiceEncoder {
+    pub fn new() -> Result<Self, String> {
+        let encoder = Encoder::new(MICROPHONE_FORMAT.sample_rate, Channels::Mono, Application::Voip)
+            .map_err(|e| format!("创建音频编码器失败: {}", e))?;
+        Ok(Self {
+            encoder,
+            pending: Vec::new(),
+            frame: samples_per_ms(MICROPHONE_FORMAT) * VOICE_FRAME_MS,
+            output: vec![0; OPUS_MAX_PACKET],
+        })
+    }
+
+    /// 编码麦克风 PCM，返回凑满的各帧 Opus 数据（剩余采样留到下次）
+    pub fn encode(&mut self, pcm: &[i16]) -> Result<Vec<Vec<u8>>, String> {
+        self.pending.extend_from_slice(pcm);
+        let mut frames = Vec::new();
+        let mut offset = 0;
+        while self.pending.len() - offset >= self.frame {
+            let len = self
+                .encoder
+                .encode(&self.pending[offset..offset + self.frame], &mut self.output)
+                .map_err(|e| format!("音频编码失败: {}", e))?;
+            frames.push(self.output[..len].to_vec());
+            offset += self.frame;
+        }
+        self.pending.drain(..offset);
+        Ok(frames)
+    }
+}
+
+/// 语音通话状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceCallState {
+    Idle,
+    /// 已发起，等待对端接听
+    Calling { req_timestamp: u64 },
+    /// 对端发起，等待本端接听
+    Incoming { req_timestamp: u64 },
+    Active { req_timestamp: u64 },
+}
+
+/// 需要发给对端的通话消息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceCallMessage {
+    Request(VoiceCallRequest),
+    Response(VoiceCallResponse),
+    /// 通话建立后告知对端麦克风音频的格式
+    Format(AudioFormat),
+}
+
+/// 会话的语音通话
+pub struct VoiceCall {
+    state: VoiceCallState,
+    /// 通话中的麦克风编码器
+    encoder: Option<VoiceEncoder>,
+}
+
+impl Default for VoiceCall {
+    fn default() -> Self {
+        Self { state: VoiceCallState::Idle, encoder: None }
+    }
+}
+
+impl VoiceCall {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> VoiceCallState {
+        self.state
+    }
+
+    /// 发起通话，或接听对端的来电
+    pub fn start(&mut self, req_timestamp: u64) -> Result<Vec<VoiceCallMessage>, String> {
+        match self.state {
+            VoiceCallState::Idle => {
+                self.state = VoiceCallState::Calling { req_timestamp };
+                Ok(vec![VoiceCallMessage::Request(VoiceCallRequest { req_timestamp, is_connect: true })])
+            }
+            VoiceCallState::Incoming { req_timestamp } => {
+                let mut messages = vec![VoiceCallMessage::Response(VoiceCallResponse { req_timestamp, accepted: true })];
+                messages.extend(self.activate(req_timestamp)?);
+                Ok(messages)
+            }
+            VoiceCallState::Calling { .. } | VoiceCallState::Active { .. } => Err("语音通话已在进行".to_string()),
+        }
+    }
+
+    /// 挂断、取消发起或拒绝来电
+    pub fn end(&mut self) -> Vec<VoiceCallMessage> {
+        let message = match self.state {
+            VoiceCallState::Idle => return Vec::new(),
+            VoiceCallState::Incoming { req_timestamp } => {
+                VoiceCallMessage::Response(VoiceCallResponse { req_timestamp, accepted: false })
+            }
+            VoiceCallState::Calling { req_timestamp } | VoiceCallState::Active { req_timestamp } => {
+                VoiceCallMessage::Request(VoiceCallRequest { req_timestamp, is_connect: false })
+            }
+        };
+        self.reset();
+        vec![message]
+    }
+
+    /// 处理对端的通话请求
+    pub fn handle_request(&mut self, request: VoiceCallRequest) -> Vec<VoiceCallMessage> {
+        if !request.is_connect {
+            if self.state != VoiceCallState::Idle {
+                log::info!("对端结束了语音通话");
+            }
+            self.reset();
+            return Vec::new();
+        }
+        match self.state {
+            VoiceCallState::Idle => {
+                log::info!("收到语音通话请求");
+                self.state = VoiceCallState::Incoming { req_timestamp: request.req_timestamp };
+                Vec::new()
+            }
+            // 双方同时发起，接听对端的请求
+            VoiceCallState::Calling { .. } => {
+                let req_timestamp = request.req_timestamp;
+                match self.activate(req_timestamp) {
+                    Ok(messages) => {
+                        let response = VoiceCallResponse { req_timestamp, accepted: true };
+                        let mut reply = vec![VoiceCallMessage::Response(response)];
+                        reply.extend(messages);
+                        reply
+                    }
+                    Err(e) => {
+                        log::warn!("{}", e);
+                        let mut reply = self.end();
+                        reply.push(VoiceCallMessage::Response(VoiceCallResponse { req_timestamp, accepted: false }));
+                        reply
+                    }
+                }
+            }
+            VoiceCallState::Incoming { .. } | VoiceCallState::Active { .. } => Vec::new(),
+        }
+    }
+
+    /// 处理对端的接听 / 拒绝
+    pub fn handle_response(&mut self, response: VoiceCallResponse) -> Vec<VoiceCallMessage> {
+        match self.state {
+            VoiceCallState::Calling { req_timestamp } if req_timestamp == response.req_timestamp => {
+                if !response.accepted {
+                    log::info!("对端拒绝了语音通话");
+                    self.reset();
+                    return Vec::new();
+                }
+                self.activate(req_timestamp).unwrap_or_else(|e| {
+                    log::warn!("{}", e);
+                    self.end()
+                })
+            }
+            _ => {
+                log::debug!("忽略过期的语音通话回应: {}", response.req_timestamp);
+                Vec::new()
+            }
+        }
+    }
+
+    /// 编码麦克风 PCM（只在通话中）
+    pub fn encode(&mut self, pcm: &[i16]) -> Result<Vec<Vec<u8>>, String> {
+        match self.encoder.as_mut() {
+            Some(encoder) => encoder.encode(pcm),
+            None => Err("未在语音通话中".to_string()),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 通话建立：创建编码器并告知对端麦克风音频的格式
+    fn activate(&mut self, req_timestamp: u64) -> Result<Vec<VoiceCallMessage>, String> {
+        self.encoder = Some(VoiceEncoder::new()?);
+        self.state = VoiceCallState::Active { req_timestamp };
+        log::info!("语音通话已接通");
+        Ok(vec![VoiceCallMessage::Format(MICROPHONE_FORMAT)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEREO_16K: AudioFormat = AudioFormat { sample_rate: 16000, channels: 2 };
+
+    #[test]
+    fn test_jitter_buffer() {
+        let mut buffer = JitterBuffer::new(STEREO_16K);
+        let per_ms = samples_per_ms(STEREO_16K);
+        let mut out = vec![1; per_ms * 10];
+
+        // 积累到目标时长前只输出静音
+        buffer.push(&vec![5; per_ms * 40]);
+        assert_eq!(buffer.read(&mut out), 0);
+        assert!(out.iter().all(|sample| *sample == 0));
+        buffer.push(&vec![5; per_ms * 20]);
+        assert_eq!(buffer.read(&mut out), out.len());
+        assert!(out.iter().all(|sample| *sample == 5));
+
+        // 欠载时输出剩余数据后重新积累
+        buffer.read(&mut vec![0; per_ms * 45]);
+        assert_eq!(buffer.read(&mut out), per_ms * 5);
+        assert_eq!(&out[per_ms * 5..], &vec![0; per_ms * 5][..]);
+        buffer.push(&vec![5; per_ms * 30]);
+        assert_eq!(buffer.read(&mut out), 0);
+
+        // 超过上限时丢弃最旧的数据
+        buffer.push(&vec![7; per_ms * JITTER_MAX_MS]);
+        assert_eq!(buffer.len(), per_ms * JITTER_MAX_MS);
+        assert_eq!(buffer.read(&mut out), out.len());
+        assert!(out.iter().all(|sample| *sample == 7));
+    }
+
+    #[test]
+    fn test_playback_format_and_mute() {
+        let mut playback = AudioPlayback::new();
+        assert!(playback.push_frame(&[0; 10]).is_err());
+        assert_eq!(playback.read(160), None);
+
+        playback.set_format(STEREO_16K).unwrap();
+        assert_eq!(playback.read(160), Some(vec![0; 320]));
+
+        // 静音时丢弃音频，断开后保留静音设置
+        playback.set_muted(true);
+        assert!(playback.push_frame(&[0xFF; 10]).is_ok());
+        playback.reset();
+        assert!(playback.is_muted());
+        assert_eq!(playback.format(), None);
+    }
+
+    #[test]
+    fn test_voice_call_flow() {
+        use VoiceCallMessage::{Format, Request, Response};
+
+        // 发起后被拒绝，过期的回应被忽略
+        let mut call = VoiceCall::new();
+        assert_eq!(call.start(100).unwrap(), vec![Request(VoiceCallRequest { req_timestamp: 100, is_connect: true })]);
+        assert!(call.start(101).is_err());
+        assert!(call.handle_response(VoiceCallResponse { req_timestamp: 99, accepted: true }).is_empty());
+        call.handle_response(VoiceCallResponse { req_timestamp: 100, accepted: false });
+        assert_eq!(call.state(), VoiceCallState::Idle);
+        assert!(call.encode(&[0; 320]).is_err());
+
+        // 发起后接通，麦克风音频凑满 20 毫秒才编码
+        call.start(200).unwrap();
+        let accepted = call.handle_response(VoiceCallResponse { req_timestamp: 200, accepted: true });
+        assert_eq!(accepted, vec![Format(MICROPHONE_FORMAT)]);
+        assert!(call.encode(&[1; 200]).unwrap().is_empty());
+        assert_eq!(call.encode(&[1; 500]).unwrap().len(), 2);
+        assert_eq!(call.end(), vec![Request(VoiceCallRequest { req_timestamp: 200, is_connect: false })]);
+        assert!(call.end().is_empty());
+
+        // 来电：接听后由对端挂断，再次来电时拒绝
+        call.handle_request(VoiceCallRequest { req_timestamp: 300, is_connect: true });
+        assert_eq!(call.state(), VoiceCallState::Incoming { req_timestamp: 300 });
+        assert_eq!(call.start(301).unwrap(), vec![
+            Response(VoiceCallResponse { req_timestamp: 300, accepted: true }),
+            Format(MICROPHONE_FORMAT),
+        ]);
+        call.handle_request(VoiceCallRequest { req_timestamp: 300, is_connect: false });
+        assert_eq!(call.state(), VoiceCallState::Idle);
+        call.handle_request(VoiceCallRequest { req_timestamp: 400, is_connect: true });
+        assert_eq!(call.end(), vec![Response(VoiceCallResponse { req_timestamp: 400, accepted: false })]);
+
+        // 双方同时发起时直接接通
+        call.start(500).unwrap();
+        let crossed = call.handle_request(VoiceCallRequest { req_timestamp: 501, is_connect: true });
+        assert_eq!(crossed[0], Response(VoiceCallResponse { req_timestamp: 501, accepted: true }));
+        assert_eq!(call.state(), VoiceCallState::Active { req_timestamp: 501 });
+    }
+}
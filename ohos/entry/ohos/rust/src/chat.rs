@@ -0,0 +1,122 @@
+/**
+ * 会话聊天模块
+ *
+ * 与被控端的用户互发文字消息（对应 RustDesk ChatMessage）：
+ * - 每个会话保存带时间戳的聊天记录（超过上限时丢弃最旧的消息）
+ * - 收到的消息同时作为事件由 ArkTS 轮询，用于提示和未读计数
+ *
+ */
+
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 单条消息的最大长度（字节）
+pub const MAX_MESSAGE_LEN: usize = 4096;
+/// 每个会话保留的消息数
+const MAX_HISTORY: usize = 500;
+
+/// 聊天消息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub text: String,
+    /// 发送或收到的时间（毫秒）
+    pub timestamp: u64,
+    /// 是否为对端发来的消息
+    pub incoming: bool,
+}
+
+/// 会话的聊天记录
+#[derive(Debug, Default)]
+pub struct ChatHistory {
+    messages: VecDeque<ChatMessage>,
+    /// 尚未被 ArkTS 取走的收到的消息
+    incoming: Vec<ChatMessage>,
+}
+
+/// 当前时间（毫秒）
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl ChatHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 检查要发送的消息（去掉首尾空白，不能为空或过长）
+    pub fn prepare(text: &str) -> Result<String, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("消息为空".to_string());
+        }
+        if text.len() > MAX_MESSAGE_LEN {
+            return Err(format!("消息过长: {} 字节", text.len()));
+        }
+        Ok(text.to_string())
+    }
+
+    /// 记录已发出的消息
+    pub fn add_sent(&mut self, text: String) -> ChatMessage {
+        let message = ChatMessage { text, timestamp: now_millis(), incoming: false };
+        self.push(message.clone());
+        message
+    }
+
+    /// 记录收到的消息
+    pub fn add_received(&mut self, text: String) {
+        let message = ChatMessage { text, timestamp: now_millis(), incoming: true };
+        self.incoming.push(message.clone());
+        self.push(message);
+    }
+
+    /// 全部聊天记录（按时间顺序）
+    pub fn messages(&self) -> Vec<ChatMessage> {
+        self.messages.iter().cloned().collect()
+    }
+
+    /// 取出上次之后收到的消息
+    pub fn take_incoming(&mut self) -> Vec<ChatMessage> {
+        std::mem::take(&mut self.incoming)
+    }
+
+    fn push(&mut self, message: ChatMessage) {
+        if self.messages.len() == MAX_HISTORY {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_and_incoming_events() {
+        let mut chat = ChatHistory::new();
+        assert!(ChatHistory::prepare("  \n").is_err());
+        assert!(ChatHistory::prepare(&"a".repeat(MAX_MESSAGE_LEN + 1)).is_err());
+        let text = ChatHistory::prepare(" 我要重启服务器了 ").unwrap();
+        assert_eq!(chat.add_sent(text).text, "我要重启服务器了");
+        chat.add_received("好的".to_string());
+
+        let incoming = chat.take_incoming();
+        assert_eq!(incoming.len(), 1);
+        assert!(incoming[0].incoming);
+        assert!(chat.take_incoming().is_empty());
+        let history = chat.messages();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].timestamp <= history[1].timestamp);
+
+        // 超过上限时丢弃最旧的消息
+        for i in 0..MAX_HISTORY {
+            chat.add_received(i.to_string());
+        }
+        let history = chat.messages();
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(history[0].text, "0");
+    }
+}
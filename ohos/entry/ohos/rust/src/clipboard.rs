@@ -0,0 +1,340 @@
+/**
+ * 剪贴板同步模块
+ *
+ * 本地与远程剪贴板的双向多格式同步（文本、RTF、HTML、PNG 图片，每个会话一个状态）：
+ * - 遵循剪贴板同步策略，关闭时不发送也不接收
+ * - 抑制回声：远程内容写入本地剪贴板后不会再发回对端
+ * - 被控端回传刚发送的内容时不会再通知 ArkTS
+ * - 较大的内容只通告格式和长度，ArkTS 读取时才向对端请求，按块接收后拼接
+ * - 远程剪贴板变化保存为待取事件，只保留最新的内容
+ *
+ */
+
+use crate::protocol::{ClipboardData, ClipboardFormat, ClipboardItem, ClipboardResponse, FILE_BLOCK_SIZE};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+/// 请求后超过该时间仍未收齐时，下次读取重新请求
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 读取远程剪贴板的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteRead {
+    /// 内容已到达
+    Ready(Vec<u8>),
+    /// 延迟传输的内容，需要向对端请求
+    Fetch,
+    /// 已请求，等待对端传输
+    Pending,
+    /// 远程剪贴板没有该格式
+    Unavailable,
+}
+
+/// 剪贴板同步状态
+#[derive(Debug)]
+pub struct ClipboardSync {
+    enabled: bool,
+    /// 最近一次与对端同步的剪贴板指纹（发送或接收）
+    last_synced: Option<u64>,
+    /// 本地剪贴板的完整内容（回应对端的延迟传输请求）
+    local: Option<ClipboardData>,
+    /// 远程剪贴板（延迟传输的内容到达后补全）
+    remote: Option<ClipboardData>,
+    /// 远程剪贴板有尚未被 ArkTS 取走的变化
+    remote_changed: bool,
+    /// 已请求、尚未到达的格式（请求时间）
+    requested: Vec<(ClipboardFormat, Instant)>,
+    /// 正在接收的内容块（按格式、块序号）
+    receiving: HashMap<ClipboardFormat, BTreeMap<u32, Vec<u8>>>,
+}
+
+impl Default for ClipboardSync {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            last_synced: None,
+            local: None,
+            remote: None,
+            remote_changed: false,
+            requested: Vec::new(),
+            receiving: HashMap::new(),
+        }
+    }
+}
+
+/// 剪贴板指纹（按通告计算，延迟传输的内容只计入长度，收发双方一致）
+fn fingerprint(announce: &ClipboardData) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for item in &announce.items {
+        (item.format as u8).hash(&mut hasher);
+        item.size.hash(&mut hasher);
+        item.content.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+impl ClipboardSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 开启 / 关闭同步（关闭时丢弃两端的剪贴板内容）
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.last_synced = None;
+            self.local = None;
+            self.remote = None;
+            self.remote_changed = false;
+            self.requested.clear();
+            self.receiving.clear();
+        }
+    }
+
+    /// 本地剪贴板变化，需要发送给对端时返回通告消息
+    pub fn local_changed(&mut self, data: ClipboardData) -> Option<ClipboardData> {
+        if !self.enabled || data.is_empty() {
+            return None;
+        }
+        let announce = data.announce();
+        let fingerprint = fingerprint(&announce);
+        if self.last_synced == Some(fingerprint) {
+            return None;
+        }
+        self.last_synced = Some(fingerprint);
+        self.local = Some(data);
+        Some(announce)
+    }
+
+    /// 对端请求延迟传输的本地内容
+    pub fn local_item(&self, format: ClipboardFormat) -> Option<ClipboardItem> {
+        if !self.enabled {
+            return None;
+        }
+        self.local.as_ref()?.item(format).cloned()
+    }
+
+    /// 收到远程剪贴板，返回是否产生新的远程剪贴板事件
+    pub fn remote_changed(&mut self, data: ClipboardData) -> bool {
+        if !self.enabled || data.is_empty() {
+            return false;
+        }
+        let fingerprint = fingerprint(&data.announce());
+        if self.last_synced == Some(fingerprint) {
+            return false;
+        }
+        self.last_synced = Some(fingerprint);
+        self.remote = Some(data);
+        self.remote_changed = true;
+        self.requested.clear();
+        self.receiving.clear();
+        true
+    }
+
+    /// 收到延迟传输内容的一块或完成标记，返回内容是否已完整到达
+    ///
+    /// 收到完成标记时仍有缺块则撤销请求，下次读取时重新请求（已收到的块保留，迟到的块照常接收）
+    pub fn remote_response_received(&mut self, response: ClipboardResponse) -> bool {
+        let format = response.format();
+        let requested = self.requested.iter().any(|(requested, _)| *requested == format);
+        if !self.enabled || !(requested || self.receiving.contains_key(&format)) {
+            return false;
+        }
+        let size = match self.remote.as_ref().and_then(|remote| remote.item(format)) {
+            Some(item) if item.content.is_none() => item.size,
+            _ => return false,
+        };
+        let count = size.div_ceil(FILE_BLOCK_SIZE);
+        let blocks = self.receiving.entry(format).or_default();
+        let done = match response {
+            ClipboardResponse::Block { blk_id, data, .. } => {
+                let offset = blk_id as usize * FILE_BLOCK_SIZE;
+                if offset < size && data.len() == FILE_BLOCK_SIZE.min(size - offset) {
+                    blocks.insert(blk_id, data);
+                }
+                false
+            }
+            ClipboardResponse::Done { size: done_size, .. } => {
+                if done_size != size {
+                    self.cancel_request(format);
+                    return false;
+                }
+                true
+            }
+        };
+
+        if blocks.len() < count {
+            if done {
+                self.cancel_request(format);
+            }
+            return false;
+        }
+        let content: Vec<u8> = self.receiving.remove(&format).unwrap_or_default().into_values().flatten().collect();
+        if format.is_text() && std::str::from_utf8(&content).is_err() {
+            self.cancel_request(format);
+            return false;
+        }
+        self.remote_item_received(ClipboardItem::new(format, content))
+    }
+
+    /// 延迟传输的内容拼接完成，返回是否属于当前的远程剪贴板
+    fn remote_item_received(&mut self, item: ClipboardItem) -> bool {
+        let remote = match self.remote.as_mut() {
+            Some(remote) if self.enabled => remote,
+            _ => return false,
+        };
+        let target = remote
+            .items
+            .iter_mut()
+            .find(|target| target.format == item.format && target.size == item.size);
+        match target {
+            Some(target) if target.content.is_none() => {
+                target.content = item.content;
+                self.requested.retain(|(format, _)| *format != item.format);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 取出远程剪贴板变化（变化后的格式列表）
+    pub fn take_remote_formats(&mut self) -> Option<Vec<ClipboardFormat>> {
+        if !self.remote_changed {
+            return None;
+        }
+        self.remote_changed = false;
+        let remote = self.remote.as_ref()?;
+        Some(remote.items.iter().map(|item| item.format).collect())
+    }
+
+    /// 读取远程剪贴板的指定格式（需要请求时记录为已请求）
+    pub fn read_remote(&mut self, format: ClipboardFormat) -> RemoteRead {
+        let item = match self.remote.as_ref().and_then(|remote| remote.item(format)) {
+            Some(item) => item,
+            None => return RemoteRead::Unavailable,
+        };
+        if let Some(content) = &item.content {
+            return RemoteRead::Ready(content.clone());
+        }
+        let pending = self
+            .requested
+            .iter()
+            .any(|(requested, at)| *requested == format && at.elapsed() < REQUEST_TIMEOUT);
+        if pending {
+            return RemoteRead::Pending;
+        }
+        self.cancel_request(format);
+        self.requested.push((format, Instant::now()));
+        RemoteRead::Fetch
+    }
+
+    /// 请求发送失败或内容不完整时撤销请求记录，下次读取时重新请求
+    pub fn cancel_request(&mut self, format: ClipboardFormat) {
+        self.requested.retain(|(requested, _)| *requested != format);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::CLIPBOARD_LAZY_THRESHOLD;
+
+    fn text(text: &str) -> ClipboardData {
+        ClipboardData::new(text)
+    }
+
+    #[test]
+    fn test_echo_suppressed() {
+        let mut sync = ClipboardSync::new();
+
+        // 远程内容写入本地剪贴板后，本地变化不再发回
+        assert!(sync.remote_changed(text("remote")));
+        assert_eq!(sync.take_remote_formats(), Some(vec![ClipboardFormat::Text]));
+        assert_eq!(sync.take_remote_formats(), None);
+        assert_eq!(sync.read_remote(ClipboardFormat::Text), RemoteRead::Ready(b"remote".to_vec()));
+        assert_eq!(sync.local_changed(text("remote")), None);
+
+        // 对端回传刚发送的内容时不产生事件
+        assert_eq!(sync.local_changed(text("local")), Some(text("local")));
+        assert!(!sync.remote_changed(text("local")));
+        assert_eq!(sync.take_remote_formats(), None);
+
+        // 新内容照常同步
+        assert!(sync.remote_changed(text("remote")));
+    }
+
+    #[test]
+    fn test_disabled_policy() {
+        let mut sync = ClipboardSync::new();
+        sync.remote_changed(text("pending"));
+        sync.local_changed(text("local"));
+        sync.set_enabled(false);
+
+        assert_eq!(sync.take_remote_formats(), None);
+        assert_eq!(sync.local_item(ClipboardFormat::Text), None);
+        assert_eq!(sync.local_changed(text("local")), None);
+        assert!(!sync.remote_changed(text("remote")));
+
+        sync.set_enabled(true);
+        assert!(sync.local_changed(text("local")).is_some());
+    }
+
+    #[test]
+    fn test_lazy_transfer() {
+        let png = ClipboardItem::new(ClipboardFormat::ImagePng, vec![1; CLIPBOARD_LAZY_THRESHOLD + 1]);
+        let data = ClipboardData { items: vec![png.clone()] };
+
+        // 发送端通告后保留完整内容，回应对端请求
+        let mut local = ClipboardSync::new();
+        let announce = local.local_changed(data.clone()).unwrap();
+        assert_eq!(announce.items[0].content, None);
+        assert_eq!(local.local_item(ClipboardFormat::ImagePng), Some(png.clone()));
+
+        // 接收端读取时请求一次，内容到达后可读
+        let mut remote = ClipboardSync::new();
+        assert!(remote.remote_changed(announce.clone()));
+        assert_eq!(remote.read_remote(ClipboardFormat::Html), RemoteRead::Unavailable);
+        assert_eq!(remote.read_remote(ClipboardFormat::ImagePng), RemoteRead::Fetch);
+        assert_eq!(remote.read_remote(ClipboardFormat::ImagePng), RemoteRead::Pending);
+        let responses = ClipboardResponse::split(&png);
+        assert!(responses.iter().cloned().any(|response| remote.remote_response_received(response)));
+        assert!(!remote.remote_response_received(responses[0].clone()));
+        assert_eq!(remote.read_remote(ClipboardFormat::ImagePng), RemoteRead::Ready(vec![1; png.size]));
+
+        // 补全内容后指纹不变，写入本地剪贴板不会发回
+        assert_eq!(remote.local_changed(data), None);
+        assert!(!local.remote_changed(announce));
+    }
+
+    #[test]
+    fn test_chunked_transfer_with_lost_block() {
+        // 超过 64 KiB 的文本：丢失一块时完成标记触发重新请求，补齐后拼接
+        let content = "剪贴板".repeat(30_000).into_bytes();
+        let item = ClipboardItem::new(ClipboardFormat::Text, content.clone());
+        let mut local = ClipboardSync::new();
+        let announce = local.local_changed(ClipboardData { items: vec![item] }).unwrap();
+        let item = local.local_item(ClipboardFormat::Text).unwrap();
+
+        let mut remote = ClipboardSync::new();
+        remote.remote_changed(announce);
+        assert_eq!(remote.read_remote(ClipboardFormat::Text), RemoteRead::Fetch);
+        let responses = ClipboardResponse::split(&item);
+        assert!(responses.len() > 3);
+        for response in responses.iter().filter(|response| !matches!(response, ClipboardResponse::Block { blk_id: 1, .. })) {
+            assert!(!remote.remote_response_received(response.clone()));
+        }
+        assert_eq!(remote.read_remote(ClipboardFormat::Text), RemoteRead::Fetch);
+
+        // 重新请求的回应中，乱序到达的块也能拼接
+        for response in responses.iter().rev() {
+            remote.remote_response_received(response.clone());
+        }
+        assert_eq!(remote.read_remote(ClipboardFormat::Text), RemoteRead::Ready(content));
+    }
+}
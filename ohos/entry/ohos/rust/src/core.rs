@@ -3,10 +3,26 @@
  * 提供与 ArkTS 层交互的核心 API
  */
 
+use crate::audio::VoiceCallState;
+use crate::chat::ChatMessage;
+use crate::file_manager::{DirListing, FileOperation, OperationResult};
+use crate::file_transfer::TransferEvent;
+use crate::gesture::{TouchAction, TouchMode, TouchPoint};
+use crate::keyboard::SpecialAction;
+use crate::macros::{Macro, MacroStep, MacroStore};
+use crate::protocol::{
+    AudioFormat, ClipboardData, ClipboardFormat, ClipboardItem, ConnType, CursorData, DisplayInfo, KeyboardMode,
+    OptionMessage, PeerInfo,
+};
 use crate::rustdesk::{RustDeskConfig, RustDeskConnection, RustDeskVideoStream};
+use crate::video::DecodedFrame;
+use crate::viewport::{FitMode, ViewportState};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
 
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
@@ -34,6 +50,42 @@ pub struct SessionInfo {
     pub connected: bool,
     pub screen_width: u32,
     pub screen_height: u32,
+    pub username: String,
+    pub hostname: String,
+    pub platform: String,
+    pub version: String,
+    pub displays: Vec<DisplayInfo>,
+    pub current_display: usize,
+    pub features: Vec<String>,
+    /// 被控端支持触摸，触摸事件直接转发
+    pub supports_touch: bool,
+}
+
+impl SessionInfo {
+    /// 由登录时收到的对端信息构建（未收到时各字段为空，屏幕尺寸为 0）
+    fn new(id: &str, peer_info: Option<PeerInfo>) -> Self {
+        let info = peer_info.unwrap_or_default();
+        let supports_touch = info.supports_touch();
+        let (screen_width, screen_height) = info
+            .current()
+            .map(|display| (display.width, display.height))
+            .unwrap_or((0, 0));
+
+        Self {
+            id: id.to_string(),
+            connected: true,
+            screen_width,
+            screen_height,
+            username: info.username,
+            hostname: info.hostname,
+            platform: info.platform,
+            version: info.version,
+            displays: info.displays,
+            current_display: info.current_display,
+            features: info.features,
+            supports_touch,
+        }
+    }
 }
 
 /// 核心管理器
@@ -41,6 +93,19 @@ pub struct CoreManager {
     connections: Arc<Mutex<HashMap<String, Arc<Mutex<RustDeskConnection>>>>>,
     video_streams: Arc<Mutex<HashMap<String, RustDeskVideoStream>>>,
     server_config: Arc<Mutex<ServerConfig>>,
+    macros: Arc<Mutex<MacroStore>>,
+    /// 剪贴板同步策略（对所有会话生效）
+    clipboard_sync: Arc<Mutex<bool>>,
+    /// 各会话正在执行的宏
+    macro_tasks: Arc<Mutex<HashMap<String, JoinHandle<()>>>>,
+    /// 文件传输连接（FILE_TRANSFER 类型，与远程桌面连接分开）
+    file_connections: Arc<Mutex<HashMap<String, Arc<Mutex<RustDeskConnection>>>>>,
+    /// 文件传输策略（对所有会话生效）
+    file_transfer: Arc<Mutex<bool>>,
+    /// 各会话本地剪贴板中的文件（对端粘贴时通过文件传输连接请求）
+    file_clipboard: Arc<Mutex<HashMap<String, Vec<PathBuf>>>>,
+    /// 各会话的选项（登录时发送，断开后保留）
+    session_options: Arc<Mutex<HashMap<String, OptionMessage>>>,
 }
 
 impl CoreManager {
@@ -50,6 +115,13 @@ impl CoreManager {
             connections: Arc::new(Mutex::new(HashMap::new())),
             video_streams: Arc::new(Mutex::new(HashMap::new())),
             server_config: Arc::new(Mutex::new(ServerConfig::default())),
+            macros: Arc::new(Mutex::new(MacroStore::new())),
+            clipboard_sync: Arc::new(Mutex::new(true)),
+            macro_tasks: Arc::new(Mutex::new(HashMap::new())),
+            file_connections: Arc::new(Mutex::new(HashMap::new())),
+            file_transfer: Arc::new(Mutex::new(true)),
+            file_clipboard: Arc::new(Mutex::new(HashMap::new())),
+            session_options: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -59,7 +131,7 @@ impl CoreManager {
         *stored = config;
     }
 
-    async fn build_config(&self, desk_id: &str, password: &str) -> RustDeskConfig {
+    async fn build_config(&self, desk_id: &str, password: &str, conn_type: ConnType) -> RustDeskConfig {
         let base = RustDeskConfig::default();
         let settings = self.server_config.lock().await.clone();
         RustDeskConfig {
@@ -72,6 +144,8 @@ impl CoreManager {
             id_server: settings.id_server.unwrap_or(base.id_server),
             relay_server: settings.relay_server.or(base.relay_server),
             force_relay: settings.force_relay,
+            conn_type,
+            options: OptionMessage::default(),
         }
     }
 
@@ -82,26 +156,25 @@ impl CoreManager {
         // 检查是否已存在连接
         {
             let conns = self.connections.lock().await;
-            if conns.contains_key(desk_id) {
-                return Ok(SessionInfo {
-                    id: desk_id.to_string(),
-                    connected: true,
-                    screen_width: 1920,
-                    screen_height: 1080,
-                });
+            if let Some(conn) = conns.get(desk_id) {
+                return Ok(Self::session_info(desk_id, conn).await);
             }
         }
 
-        let config = self.build_config(desk_id, password).await;
+        let mut config = self.build_config(desk_id, password, ConnType::DefaultConn).await;
+        config.options = self.session_options.lock().await.get(desk_id).cloned().unwrap_or_default();
+        let clipboard_sync = *self.clipboard_sync.lock().await && config.options.disable_clipboard != Some(true);
 
         // 创建连接
         let mut connection = RustDeskConnection::new(config);
         connection.connect().await?;
+        connection.set_clipboard_sync(clipboard_sync).await;
 
         // 存储连接
         let connection = Arc::new(Mutex::new(connection));
         let mut conns = self.connections.lock().await;
         conns.insert(desk_id.to_string(), connection.clone());
+        let session = Self::session_info(desk_id, &connection).await;
 
         // 启动视频流
         let mut video_stream = RustDeskVideoStream::new(connection);
@@ -109,18 +182,39 @@ impl CoreManager {
         let mut streams = self.video_streams.lock().await;
         streams.insert(desk_id.to_string(), video_stream);
 
-        Ok(SessionInfo {
-            id: desk_id.to_string(),
-            connected: true,
-            screen_width: 1920,
-            screen_height: 1080,
-        })
+        Ok(session)
+    }
+
+    /// 根据连接保存的对端信息构建会话信息
+    async fn session_info(desk_id: &str, conn: &Arc<Mutex<RustDeskConnection>>) -> SessionInfo {
+        let conn = conn.lock().await;
+        SessionInfo::new(desk_id, conn.peer_info().await)
+    }
+
+    /// 获取指定会话的信息
+    pub async fn get_session_info(&self, desk_id: &str) -> Option<SessionInfo> {
+        let conns = self.connections.lock().await;
+        match conns.get(desk_id) {
+            Some(conn) => Some(Self::session_info(desk_id, conn).await),
+            None => None,
+        }
     }
 
     /// 断开指定连接
     pub async fn disconnect(&self, desk_id: &str) -> Result<(), String> {
         log::info!("CoreManager: Disconnecting {}", desk_id);
 
+        // 停止正在执行的宏
+        if let Some(task) = self.macro_tasks.lock().await.remove(desk_id) {
+            task.abort();
+        }
+        self.file_clipboard.lock().await.remove(desk_id);
+
+        // 断开该会话的文件传输连接（取消进行中的传输）
+        if let Err(e) = self.disconnect_file_transfer(desk_id).await {
+            log::warn!("断开文件传输连接失败: {}: {}", desk_id, e);
+        }
+
         // 停止视频流
         {
             let mut streams = self.video_streams.lock().await;
@@ -151,27 +245,182 @@ impl CoreManager {
         for desk_id in desk_ids {
             self.disconnect(&desk_id).await?;
         }
+        self.disconnect_file_transfers().await;
 
         Ok(())
     }
 
     /// 发送键盘事件
     pub async fn send_key(&self, desk_id: &str, key: u32, pressed: bool) -> Result<(), String> {
-        let conns = self.connections.lock().await;
-        if let Some(conn) = conns.get(desk_id) {
-            let conn = conn.lock().await;
-            conn.send_key_event(key, pressed).await?;
-        }
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.send_key_event(key, pressed).await
+    }
+
+    /// 发送文本输入
+    pub async fn send_text(&self, desk_id: &str, text: &str) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.send_text(text).await
+    }
+
+    /// 设置会话的键盘模式
+    pub async fn set_keyboard_mode(&self, desk_id: &str, mode: KeyboardMode) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        conn.lock().await.set_keyboard_mode(mode).await;
         Ok(())
     }
 
     /// 发送鼠标事件
     pub async fn send_mouse_move(&self, desk_id: &str, x: i32, y: i32) -> Result<(), String> {
-        let conns = self.connections.lock().await;
-        if let Some(conn) = conns.get(desk_id) {
-            let conn = conn.lock().await;
-            conn.send_mouse_move(x, y).await?;
-        }
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.send_mouse_move(x, y).await
+    }
+
+    /// 发送鼠标滚轮
+    pub async fn send_mouse_wheel(&self, desk_id: &str, dx: i32, dy: i32) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.send_mouse_wheel(dx, dy).await
+    }
+
+    /// 向会话发送相对鼠标移动
+    pub async fn send_mouse_relative(&self, desk_id: &str, dx: i32, dy: i32) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.send_mouse_relative(dx, dy).await
+    }
+
+    /// 开启 / 关闭会话的相对鼠标模式
+    pub async fn set_relative_mouse(&self, desk_id: &str, enabled: bool) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        conn.lock().await.set_relative_mouse(enabled).await;
+        Ok(())
+    }
+
+    /// 发送触摸事件，返回当前视口状态
+    pub async fn send_touch(
+        &self,
+        desk_id: &str,
+        points: &[TouchPoint],
+        action: TouchAction,
+    ) -> Result<ViewportState, String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.send_touch(points, action).await
+    }
+
+    /// 向会话发送特殊操作
+    pub async fn send_special_action(&self, desk_id: &str, action: SpecialAction) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.send_special_action(action).await
+    }
+
+    /// 设置会话的触摸模式
+    pub async fn set_touch_mode(&self, desk_id: &str, mode: TouchMode) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.set_touch_mode(mode).await
+    }
+
+    /// 设置会话视口（视图尺寸和适配模式）
+    pub async fn set_viewport(
+        &self,
+        desk_id: &str,
+        width: f32,
+        height: f32,
+        fit: FitMode,
+    ) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        conn.lock().await.set_viewport(width, height, fit).await;
+        Ok(())
+    }
+
+    /// 设置会话画面缩放
+    pub async fn set_view_zoom(
+        &self,
+        desk_id: &str,
+        zoom: f32,
+        center_x: f32,
+        center_y: f32,
+    ) -> Result<ViewportState, String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let state = conn.lock().await.set_view_zoom(zoom, center_x, center_y).await;
+        Ok(state)
+    }
+
+    /// 平移会话画面
+    pub async fn pan_view(&self, desk_id: &str, dx: f32, dy: f32) -> Result<ViewportState, String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let state = conn.lock().await.pan_view(dx, dy).await;
+        Ok(state)
+    }
+
+    /// 获取会话视口状态
+    pub async fn get_viewport(&self, desk_id: &str) -> Option<ViewportState> {
+        let conn = self.connection(desk_id).await?;
+        let state = conn.lock().await.viewport_state().await;
+        Some(state)
+    }
+
+    /// 抬起会话中所有按住的按键和鼠标按键
+    pub async fn release_all_inputs(&self, desk_id: &str) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        conn.lock().await.release_all_inputs().await;
+        Ok(())
+    }
+
+    /// 设置会话的输入发送频率（Hz，0 表示立即发送）
+    pub async fn set_input_rate(&self, desk_id: &str, rate: u32) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        conn.lock().await.set_input_rate(rate).await;
         Ok(())
     }
 
@@ -182,10 +431,588 @@ impl CoreManager {
         button: u32,
         pressed: bool,
     ) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.send_mouse_click(button, pressed).await
+    }
+
+    /// 切换远程显示器
+    pub async fn switch_display(&self, desk_id: &str, display: usize) -> Result<(), String> {
+        {
+            let conns = self.connections.lock().await;
+            let conn = conns
+                .get(desk_id)
+                .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+            let conn = conn.lock().await;
+            conn.switch_display(display).await?;
+        }
+
+        // 单显示器模式下其他显示器的画面不再更新
+        let streams = self.video_streams.lock().await;
+        if let Some(stream) = streams.get(desk_id) {
+            stream.retain_displays(&[display]).await;
+        }
+        Ok(())
+    }
+
+    /// 同时采集多个远程显示器
+    pub async fn capture_displays(&self, desk_id: &str, displays: &[usize]) -> Result<(), String> {
+        {
+            let conns = self.connections.lock().await;
+            let conn = conns
+                .get(desk_id)
+                .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+            let conn = conn.lock().await;
+            conn.capture_displays(displays).await?;
+        }
+
+        let streams = self.video_streams.lock().await;
+        if let Some(stream) = streams.get(desk_id) {
+            stream.retain_displays(displays).await;
+        }
+        Ok(())
+    }
+
+    /// 获取会话的显示器列表和当前显示器索引
+    pub async fn get_displays(&self, desk_id: &str) -> Option<(Vec<DisplayInfo>, usize)> {
         let conns = self.connections.lock().await;
-        if let Some(conn) = conns.get(desk_id) {
+        match conns.get(desk_id) {
+            Some(conn) => {
+                let session = Self::session_info(desk_id, conn).await;
+                Some((session.displays, session.current_display))
+            }
+            None => None,
+        }
+    }
+
+    /// 获取会话最新的解码帧（未指定显示器时取当前显示器）
+    pub async fn get_latest_frame(
+        &self,
+        desk_id: &str,
+        display: Option<usize>,
+    ) -> Option<DecodedFrame> {
+        let display = match display {
+            Some(display) => display,
+            None => self.get_displays(desk_id).await.map(|(_, current)| current)?,
+        };
+
+        let streams = self.video_streams.lock().await;
+        match streams.get(desk_id) {
+            Some(stream) => stream.latest_frame(display).await,
+            None => None,
+        }
+    }
+
+    /// 获取会话连接
+    async fn connection(&self, desk_id: &str) -> Option<Arc<Mutex<RustDeskConnection>>> {
+        self.connections.lock().await.get(desk_id).cloned()
+    }
+
+    /// 获取远程光标（当前光标图像和远程桌面坐标）
+    pub async fn get_cursor(&self, desk_id: &str) -> Option<(Option<Arc<CursorData>>, (i32, i32))> {
+        let conn = self.connection(desk_id).await?;
+        let cursor = conn.lock().await.cursor();
+        let cursor = cursor.lock().await;
+        Some((cursor.current(), cursor.position()))
+    }
+
+    /// 设置是否把远程光标合成到输出帧
+    pub async fn set_cursor_composite(&self, desk_id: &str, enabled: bool) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let cursor = conn.lock().await.cursor();
+        cursor.lock().await.set_composite(enabled);
+        Ok(())
+    }
+
+    /// 按会话设置把远程光标合成到 RGBA 帧（未启用时不做处理）
+    pub async fn composite_cursor(
+        &self,
+        desk_id: &str,
+        display: Option<usize>,
+        rgba: &mut [u8],
+        width: u32,
+        height: u32,
+    ) {
+        let conn = match self.connection(desk_id).await {
+            Some(conn) => conn,
+            None => return,
+        };
+        let (cursor, peer_info) = {
             let conn = conn.lock().await;
-            conn.send_mouse_click(button, pressed).await?;
+            (conn.cursor(), conn.peer_info().await)
+        };
+
+        let cursor = cursor.lock().await;
+        if !cursor.composite_enabled() {
+            return;
+        }
+
+        let display_info = peer_info.and_then(|info| {
+            let index = display.unwrap_or(info.current_display);
+            info.displays.get(index).cloned()
+        });
+        // 没有显示器信息时按帧尺寸处理（原点为 0,0）
+        let display_info = display_info.unwrap_or(DisplayInfo {
+            x: 0,
+            y: 0,
+            width,
+            height,
+            scale: 1.0,
+            is_primary: true,
+            name: String::new(),
+        });
+        cursor.composite_into(rgba, width, height, &display_info);
+    }
+
+    /// 设置剪贴板同步策略（同时应用到所有会话）
+    pub async fn set_clipboard_sync(&self, enabled: bool) {
+        log::info!("剪贴板同步: {}", enabled);
+        *self.clipboard_sync.lock().await = enabled;
+        let conns: Vec<_> = self.connections.lock().await.values().cloned().collect();
+        for conn in conns {
+            // 关闭了剪贴板的会话保持关闭
+            let conn = conn.lock().await;
+            let disabled = conn.session_options().await.disable_clipboard == Some(true);
+            conn.set_clipboard_sync(enabled && !disabled).await;
+        }
+    }
+
+    /// 把本地剪贴板同步到会话，返回是否已发送（文件传输被禁用时不发送文件列表）
+    pub async fn set_local_clipboard(&self, desk_id: &str, mut data: ClipboardData) -> Result<bool, String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        if !*self.file_transfer.lock().await {
+            data.items.retain(|item| item.format != ClipboardFormat::FileList);
+        }
+        let files: Vec<PathBuf> = data
+            .item(ClipboardFormat::FileList)
+            .and_then(|item| item.file_paths())
+            .unwrap_or_default()
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        self.set_shared_files(desk_id, files).await;
+
+        let conn = conn.lock().await;
+        conn.set_local_clipboard(data).await
+    }
+
+    /// 记录本地剪贴板中的文件，并应用到已建立的文件传输连接
+    async fn set_shared_files(&self, desk_id: &str, files: Vec<PathBuf>) {
+        self.file_clipboard.lock().await.insert(desk_id.to_string(), files.clone());
+        let conn = self.file_connections.lock().await.get(desk_id).cloned();
+        if let Some(conn) = conn {
+            conn.lock().await.set_shared_files(files).await;
+        }
+    }
+
+    /// 远程剪贴板中的文件（对端的路径，列表尚未到达时返回 None）
+    pub async fn remote_clipboard_files(&self, desk_id: &str) -> Result<Option<Vec<String>>, String> {
+        let content = match self.read_remote_clipboard(desk_id, ClipboardFormat::FileList).await? {
+            Some(content) => content,
+            None => return Ok(None),
+        };
+        let item = ClipboardItem::new(ClipboardFormat::FileList, content);
+        Ok(Some(item.file_paths().unwrap_or_default()))
+    }
+
+    /// 粘贴远程剪贴板中的文件：通过文件传输连接下载到本地目录，返回传输任务 ID
+    pub async fn paste_remote_clipboard_files(&self, desk_id: &str, local_dir: &str) -> Result<Vec<i32>, String> {
+        let files = self
+            .remote_clipboard_files(desk_id)
+            .await?
+            .ok_or("远程剪贴板的文件列表尚未到达")?;
+        let conn = self.file_connection(desk_id).await?;
+        let conn = conn.lock().await;
+        let mut ids = Vec::new();
+        for remote in files {
+            // 对端可能是 Windows 路径
+            let name = remote.trim_end_matches(['/', '\\']).rsplit(['/', '\\']).next().unwrap_or_default();
+            if name.is_empty() || name == "." || name == ".." {
+                log::warn!("忽略无效的剪贴板文件: {}", remote);
+                continue;
+            }
+            let local = Path::new(local_dir).join(name);
+            ids.push(conn.start_download(&remote, &local.to_string_lossy(), true).await?);
+        }
+        log::info!("粘贴远程剪贴板文件: {} 项 -> {}", ids.len(), local_dir);
+        Ok(ids)
+    }
+
+    /// 取出会话的远程剪贴板变化（变化后的格式）
+    pub async fn take_remote_clipboard_formats(&self, desk_id: &str) -> Option<Vec<ClipboardFormat>> {
+        let conn = self.connection(desk_id).await?;
+        let conn = conn.lock().await;
+        conn.take_remote_clipboard_formats().await
+    }
+
+    /// 读取会话远程剪贴板的指定格式（延迟传输的内容尚未到达时返回 None）
+    pub async fn read_remote_clipboard(
+        &self,
+        desk_id: &str,
+        format: ClipboardFormat,
+    ) -> Result<Option<Vec<u8>>, String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.read_remote_clipboard(format).await
+    }
+
+    /// 会话远程音频的格式（尚未收到音频时为 None）
+    pub async fn remote_audio_format(&self, desk_id: &str) -> Option<AudioFormat> {
+        let conn = self.connection(desk_id).await?;
+        let conn = conn.lock().await;
+        conn.remote_audio_format().await
+    }
+
+    /// 拉取会话的远程音频 PCM（16 位交错，不足部分为静音）
+    pub async fn read_remote_audio(&self, desk_id: &str, frames: usize) -> Option<Vec<i16>> {
+        let conn = self.connection(desk_id).await?;
+        let conn = conn.lock().await;
+        conn.read_remote_audio(frames).await
+    }
+
+    /// 会话静音 / 取消静音（同时通知对端停止 / 恢复发送音频）
+    pub async fn set_audio_muted(&self, desk_id: &str, muted: bool) -> Result<(), String> {
+        if self.connection(desk_id).await.is_none() {
+            return Err(format!("会话不存在: {}", desk_id));
+        }
+        log::info!("会话 {} 静音: {}", desk_id, muted);
+        let option = OptionMessage { disable_audio: Some(muted), ..Default::default() };
+        self.apply_session_option(desk_id, option).await
+    }
+
+    /// 设置会话选项（键与 RustDesk 的选项名一致；未连接时在登录时发送）
+    pub async fn set_session_option(&self, desk_id: &str, key: &str, value: &str) -> Result<(), String> {
+        let option = OptionMessage::parse(key, value)?;
+        log::info!("会话 {} 选项: {} = {}", desk_id, key, value);
+        self.apply_session_option(desk_id, option).await
+    }
+
+    /// 记录会话选项，已连接时立即发送
+    async fn apply_session_option(&self, desk_id: &str, option: OptionMessage) -> Result<(), String> {
+        self.session_options
+            .lock()
+            .await
+            .entry(desk_id.to_string())
+            .or_default()
+            .merge(&option);
+        let conn = match self.connection(desk_id).await {
+            Some(conn) => conn,
+            None => return Ok(()),
+        };
+        let conn = conn.lock().await;
+        if let Some(disable_clipboard) = option.disable_clipboard {
+            conn.set_clipboard_sync(*self.clipboard_sync.lock().await && !disable_clipboard).await;
+        }
+        conn.set_session_option(&option).await
+    }
+
+    /// 发起语音通话，或接听对端的来电
+    pub async fn start_voice_call(&self, desk_id: &str) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        log::info!("会话 {} 发起 / 接听语音通话", desk_id);
+        let conn = conn.lock().await;
+        conn.start_voice_call().await
+    }
+
+    /// 挂断、取消发起或拒绝来电
+    pub async fn end_voice_call(&self, desk_id: &str) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        log::info!("会话 {} 结束语音通话", desk_id);
+        let conn = conn.lock().await;
+        conn.end_voice_call().await
+    }
+
+    /// 会话的语音通话状态
+    pub async fn voice_call_state(&self, desk_id: &str) -> Option<VoiceCallState> {
+        let conn = self.connection(desk_id).await?;
+        let conn = conn.lock().await;
+        Some(conn.voice_call_state().await)
+    }
+
+    /// 发送麦克风 PCM（16 kHz 单声道，只在通话中）
+    pub async fn push_microphone_pcm(&self, desk_id: &str, pcm: &[i16]) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.push_microphone_pcm(pcm).await
+    }
+
+    /// 向会话的对端发送聊天消息
+    pub async fn send_chat_message(&self, desk_id: &str, text: &str) -> Result<ChatMessage, String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let conn = conn.lock().await;
+        conn.send_chat_message(text).await
+    }
+
+    /// 会话的聊天记录
+    pub async fn chat_history(&self, desk_id: &str) -> Vec<ChatMessage> {
+        let conn = match self.connection(desk_id).await {
+            Some(conn) => conn,
+            None => return Vec::new(),
+        };
+        let conn = conn.lock().await;
+        conn.chat_history().await
+    }
+
+    /// 取出会话上次之后收到的聊天消息
+    pub async fn take_chat_messages(&self, desk_id: &str) -> Vec<ChatMessage> {
+        let conn = match self.connection(desk_id).await {
+            Some(conn) => conn,
+            None => return Vec::new(),
+        };
+        let conn = conn.lock().await;
+        conn.take_chat_messages().await
+    }
+
+    /// 设置文件传输策略（关闭时断开所有文件传输连接）
+    pub async fn set_file_transfer_enabled(&self, enabled: bool) {
+        log::info!("文件传输: {}", enabled);
+        *self.file_transfer.lock().await = enabled;
+        if !enabled {
+            self.disconnect_file_transfers().await;
+        }
+    }
+
+    /// 建立文件传输连接（已存在时直接返回）
+    pub async fn connect_file_transfer(&self, desk_id: &str, password: &str) -> Result<(), String> {
+        if !*self.file_transfer.lock().await {
+            return Err("文件传输已被策略禁用".to_string());
+        }
+        if self.file_connections.lock().await.contains_key(desk_id) {
+            return Ok(());
+        }
+        log::info!("CoreManager: Connecting file transfer to {}", desk_id);
+
+        let config = self.build_config(desk_id, password, ConnType::FileTransfer).await;
+        let mut connection = RustDeskConnection::new(config);
+        connection.connect().await?;
+        if let Some(files) = self.file_clipboard.lock().await.get(desk_id) {
+            connection.set_shared_files(files.clone()).await;
+        }
+        self.file_connections
+            .lock()
+            .await
+            .insert(desk_id.to_string(), Arc::new(Mutex::new(connection)));
+        Ok(())
+    }
+
+    /// 断开文件传输连接（取消进行中的传输）
+    pub async fn disconnect_file_transfer(&self, desk_id: &str) -> Result<(), String> {
+        let conn = self.file_connections.lock().await.remove(desk_id);
+        if let Some(conn) = conn {
+            conn.lock().await.disconnect().await?;
+        }
+        Ok(())
+    }
+
+    async fn disconnect_file_transfers(&self) {
+        let conns: Vec<_> = self.file_connections.lock().await.drain().collect();
+        for (desk_id, conn) in conns {
+            if let Err(e) = conn.lock().await.disconnect().await {
+                log::warn!("断开文件传输连接失败: {}: {}", desk_id, e);
+            }
+        }
+    }
+
+    /// 获取文件传输连接（策略禁用时返回错误）
+    async fn file_connection(&self, desk_id: &str) -> Result<Arc<Mutex<RustDeskConnection>>, String> {
+        if !*self.file_transfer.lock().await {
+            return Err("文件传输已被策略禁用".to_string());
+        }
+        self.file_connections
+            .lock()
+            .await
+            .get(desk_id)
+            .cloned()
+            .ok_or_else(|| format!("文件传输连接不存在: {}", desk_id))
+    }
+
+    /// 上传本地文件或目录，返回传输任务 ID
+    pub async fn start_upload(&self, desk_id: &str, local: &str, remote: &str) -> Result<i32, String> {
+        let conn = self.file_connection(desk_id).await?;
+        let conn = conn.lock().await;
+        conn.start_upload(local, remote).await
+    }
+
+    /// 下载远程文件或目录，返回传输任务 ID
+    pub async fn start_download(
+        &self,
+        desk_id: &str,
+        remote: &str,
+        local: &str,
+        include_hidden: bool,
+    ) -> Result<i32, String> {
+        let conn = self.file_connection(desk_id).await?;
+        let conn = conn.lock().await;
+        conn.start_download(remote, local, include_hidden).await
+    }
+
+    /// 取消传输任务，返回任务是否存在
+    pub async fn cancel_transfer(&self, desk_id: &str, id: i32) -> bool {
+        let conn = match self.file_connections.lock().await.get(desk_id) {
+            Some(conn) => conn.clone(),
+            None => return false,
+        };
+        let conn = conn.lock().await;
+        conn.cancel_transfer(id).await
+    }
+
+    /// 设置会话的文件传输限速（字节/秒，0 不限速）
+    pub async fn set_transfer_rate(&self, desk_id: &str, rate: u32) -> Result<(), String> {
+        let conn = self.file_connection(desk_id).await?;
+        conn.lock().await.set_transfer_rate(rate).await;
+        Ok(())
+    }
+
+    /// 取出会话的文件传输事件
+    pub async fn take_transfer_events(&self, desk_id: &str) -> Vec<TransferEvent> {
+        let conn = match self.file_connections.lock().await.get(desk_id) {
+            Some(conn) => conn.clone(),
+            None => return Vec::new(),
+        };
+        let conn = conn.lock().await;
+        conn.take_transfer_events().await
+    }
+
+    /// 在会话的文件传输连接上发起远程文件管理操作，返回操作 ID
+    pub async fn request_file_operation(&self, desk_id: &str, operation: FileOperation) -> Result<i32, String> {
+        let conn = self.file_connection(desk_id).await?;
+        let conn = conn.lock().await;
+        conn.request_file_operation(operation).await
+    }
+
+    /// 取出会话的远程文件管理操作结果
+    pub async fn take_file_results(&self, desk_id: &str) -> Vec<OperationResult> {
+        let conn = match self.file_connections.lock().await.get(desk_id) {
+            Some(conn) => conn.clone(),
+            None => return Vec::new(),
+        };
+        let conn = conn.lock().await;
+        conn.take_file_results().await
+    }
+
+    /// 会话最近一次读取的远程目录列表
+    pub async fn remote_dir_listing(&self, desk_id: &str) -> Option<DirListing> {
+        let conn = self.file_connections.lock().await.get(desk_id)?.clone();
+        let conn = conn.lock().await;
+        conn.remote_dir_listing().await
+    }
+
+    /// 导入宏（JSON 数组，替换现有的全部宏），返回宏数量
+    pub async fn set_macros(&self, json: &str) -> Result<usize, String> {
+        let count = self.macros.lock().await.load_json(json)?;
+        log::info!("已导入 {} 个宏", count);
+        Ok(count)
+    }
+
+    /// 导出全部宏（JSON 数组）
+    pub async fn get_macros(&self) -> String {
+        self.macros.lock().await.to_json()
+    }
+
+    /// 在会话中执行宏（后台执行，立即返回）
+    pub async fn run_macro(&self, desk_id: &str, name: &str) -> Result<(), String> {
+        let conn = self
+            .connection(desk_id)
+            .await
+            .ok_or_else(|| format!("会话不存在: {}", desk_id))?;
+        let item = self
+            .macros
+            .lock()
+            .await
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("宏不存在: {}", name))?;
+
+        let mut tasks = self.macro_tasks.lock().await;
+        if tasks.get(desk_id).is_some_and(|task| !task.is_finished()) {
+            return Err(format!("会话 {} 已有宏在执行", desk_id));
+        }
+        log::info!("执行宏 {} ({} 步): {}", item.name, item.steps.len(), desk_id);
+        tasks.insert(desk_id.to_string(), tokio::spawn(Self::run_macro_steps(conn, item)));
+        Ok(())
+    }
+
+    /// 取消会话中正在执行的宏，返回是否有宏被取消
+    pub async fn cancel_macro(&self, desk_id: &str) -> bool {
+        let task = self.macro_tasks.lock().await.remove(desk_id);
+        let cancelled = match task {
+            Some(task) if !task.is_finished() => {
+                task.abort();
+                true
+            }
+            _ => false,
+        };
+
+        // 中途取消时抬起宏按下的按键
+        if cancelled {
+            log::info!("已取消宏: {}", desk_id);
+            if let Some(conn) = self.connection(desk_id).await {
+                conn.lock().await.release_all_inputs().await;
+            }
+        }
+        cancelled
+    }
+
+    /// 依次执行宏的步骤
+    async fn run_macro_steps(conn: Arc<Mutex<RustDeskConnection>>, item: Macro) {
+        for step in &item.steps {
+            if let Err(e) = Self::run_macro_step(&conn, step).await {
+                log::warn!("宏 {} 执行失败: {}", item.name, e);
+                return;
+            }
+        }
+        log::info!("宏 {} 执行完成", item.name);
+    }
+
+    /// 执行宏的一步（每一步单独锁定连接，延时期间不阻塞其他输入）
+    async fn run_macro_step(conn: &Mutex<RustDeskConnection>, step: &MacroStep) -> Result<(), String> {
+        match *step {
+            MacroStep::Delay { ms } => {
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+            }
+            MacroStep::Key { code, down: Some(down) } => {
+                conn.lock().await.send_key_event(code, down).await?;
+            }
+            MacroStep::Key { code, down: None } => {
+                let conn = conn.lock().await;
+                conn.send_key_event(code, true).await?;
+                conn.send_key_event(code, false).await?;
+            }
+            MacroStep::Text { ref text } => {
+                conn.lock().await.send_text(text).await?;
+            }
+            MacroStep::Click { button, x, y } => {
+                let conn = conn.lock().await;
+                if let (Some(x), Some(y)) = (x, y) {
+                    conn.send_mouse_move_remote(x, y).await?;
+                }
+                conn.send_mouse_click(button, true).await?;
+                conn.send_mouse_click(button, false).await?;
+            }
         }
         Ok(())
     }
@@ -193,15 +1020,11 @@ impl CoreManager {
     /// 获取连接列表
     pub async fn get_connections(&self) -> Vec<SessionInfo> {
         let conns = self.connections.lock().await;
-        conns
-            .keys()
-            .map(|id| SessionInfo {
-                id: id.clone(),
-                connected: true,
-                screen_width: 1920,
-                screen_height: 1080,
-            })
-            .collect()
+        let mut sessions = Vec::with_capacity(conns.len());
+        for (id, conn) in conns.iter() {
+            sessions.push(Self::session_info(id, conn).await);
+        }
+        sessions
     }
 }
 
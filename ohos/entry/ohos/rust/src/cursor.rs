@@ -0,0 +1,193 @@
+/**
+ * 远程光标模块
+ *
+ * 被控端不把光标画进视频时，需要控制端自己绘制：
+ * - 按 ID 缓存光标图像及热点
+ * - 跟踪当前光标和位置
+ * - 可选地把光标合成到 RGBA 输出帧中
+ */
+
+use crate::protocol::{CursorData, DisplayInfo};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 最多缓存的光标图像数量
+const MAX_CACHED_CURSORS: usize = 128;
+
+/// 光标状态
+#[derive(Debug, Default)]
+pub struct CursorState {
+    cache: HashMap<u64, Arc<CursorData>>,
+    current_id: Option<u64>,
+    /// 远程虚拟桌面坐标
+    x: i32,
+    y: i32,
+    /// 是否把光标合成到输出帧
+    composite: bool,
+}
+
+impl CursorState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 缓存光标图像并设为当前光标
+    pub fn update_data(&mut self, data: CursorData) {
+        if self.cache.len() >= MAX_CACHED_CURSORS && !self.cache.contains_key(&data.id) {
+            // 光标数量通常很少，超过上限说明被控端在生成动态光标，直接清空重建
+            log::debug!("Cursor cache full, clearing {} entries", self.cache.len());
+            self.cache.clear();
+        }
+        self.current_id = Some(data.id);
+        self.cache.insert(data.id, Arc::new(data));
+    }
+
+    /// 切换到已缓存的光标
+    pub fn set_current(&mut self, id: u64) {
+        if !self.cache.contains_key(&id) {
+            log::debug!("Cursor {} not cached yet", id);
+        }
+        self.current_id = Some(id);
+    }
+
+    /// 更新光标位置
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    /// 当前光标位置
+    pub fn position(&self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+
+    /// 当前光标图像
+    pub fn current(&self) -> Option<Arc<CursorData>> {
+        self.current_id.and_then(|id| self.cache.get(&id).cloned())
+    }
+
+    /// 设置是否合成光标到输出帧
+    pub fn set_composite(&mut self, enabled: bool) {
+        self.composite = enabled;
+    }
+
+    pub fn composite_enabled(&self) -> bool {
+        self.composite
+    }
+
+    /// 把当前光标合成到某个显示器的 RGBA 帧中
+    ///
+    /// 光标位置是远程虚拟桌面坐标，需要减去显示器原点，
+    /// 并按帧分辨率与显示器分辨率的比例缩放
+    pub fn composite_into(
+        &self,
+        rgba: &mut [u8],
+        frame_width: u32,
+        frame_height: u32,
+        display: &DisplayInfo,
+    ) {
+        let cursor = match self.current() {
+            Some(cursor) => cursor,
+            None => return,
+        };
+        if display.width == 0 || display.height == 0 {
+            return;
+        }
+
+        let scale_x = frame_width as f32 / display.width as f32;
+        let scale_y = frame_height as f32 / display.height as f32;
+        let origin_x = ((self.x - display.x) as f32 * scale_x) as i32 - cursor.hot_x;
+        let origin_y = ((self.y - display.y) as f32 * scale_y) as i32 - cursor.hot_y;
+
+        for cy in 0..cursor.height as i32 {
+            let fy = origin_y + cy;
+            if fy < 0 || fy >= frame_height as i32 {
+                continue;
+            }
+            for cx in 0..cursor.width as i32 {
+                let fx = origin_x + cx;
+                if fx < 0 || fx >= frame_width as i32 {
+                    continue;
+                }
+
+                let src = ((cy as u32 * cursor.width + cx as u32) * 4) as usize;
+                let dst = ((fy as u32 * frame_width + fx as u32) * 4) as usize;
+                if dst + 4 > rgba.len() {
+                    continue;
+                }
+
+                // Alpha 混合
+                let alpha = cursor.colors[src + 3] as u32;
+                if alpha == 0 {
+                    continue;
+                }
+                for c in 0..3 {
+                    let s = cursor.colors[src + c] as u32;
+                    let d = rgba[dst + c] as u32;
+                    rgba[dst + c] = ((s * alpha + d * (255 - alpha)) / 255) as u8;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid_cursor(id: u64, hot: i32) -> CursorData {
+        CursorData {
+            id,
+            hot_x: hot,
+            hot_y: hot,
+            width: 2,
+            height: 2,
+            colors: [255, 0, 0, 255].repeat(4),
+        }
+    }
+
+    fn display(x: i32, width: u32, height: u32) -> DisplayInfo {
+        DisplayInfo {
+            x,
+            y: 0,
+            width,
+            height,
+            scale: 1.0,
+            is_primary: x == 0,
+            name: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_cursor_cache() {
+        let mut state = CursorState::new();
+        state.update_data(solid_cursor(1, 0));
+        state.update_data(solid_cursor(2, 1));
+        assert_eq!(state.current().unwrap().id, 2);
+
+        // 切回已缓存的光标不需要重新传输图像
+        state.set_current(1);
+        assert_eq!(state.current().unwrap().hot_x, 0);
+
+        state.set_current(99);
+        assert!(state.current().is_none());
+    }
+
+    #[test]
+    fn test_composite_on_secondary_display() {
+        let mut state = CursorState::new();
+        state.update_data(solid_cursor(1, 1));
+        // 副显示器原点在 (100, 0)，光标在其 (2, 2) 处
+        state.set_position(102, 2);
+
+        let mut rgba = vec![0u8; 4 * 4 * 4];
+        state.composite_into(&mut rgba, 4, 4, &display(100, 4, 4));
+
+        // 热点 (1, 1) 对齐到 (2, 2)，光标覆盖 (1..3, 1..3)
+        let pixel = |x: usize, y: usize| &rgba[(y * 4 + x) * 4..(y * 4 + x) * 4 + 3];
+        assert_eq!(pixel(1, 1), &[255, 0, 0]);
+        assert_eq!(pixel(2, 2), &[255, 0, 0]);
+        assert_eq!(pixel(0, 0), &[0, 0, 0]);
+        assert_eq!(pixel(3, 3), &[0, 0, 0]);
+    }
+}
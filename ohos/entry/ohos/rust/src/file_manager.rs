@@ -0,0 +1,311 @@
+/**
+ * 远程文件管理模块
+ *
+ * 在文件传输连接上浏览和整理对端的文件系统（与传输任务共用任务 ID）：
+ * - 读取目录（可选显示隐藏文件）、创建目录、递归删除、重命名、按通配符搜索
+ * - 操作结果以事件形式由 ArkTS 轮询
+ * - 缓存每个会话最近一次读取的目录列表，在该目录下创建、删除、重命名成功后同步更新
+ *
+ */
+
+use crate::protocol::{FileAction, FileEntry, FileResponse, FileType};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 路径分隔符（对端可能是 Windows）
+const SEPARATORS: [char; 2] = ['/', '\\'];
+
+/// 文件管理操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileOperation {
+    ReadDir { path: String, include_hidden: bool },
+    CreateDir { path: String },
+    /// 删除文件或目录（目录递归删除）
+    Remove { path: String },
+    Rename { path: String, new_name: String },
+    Search { path: String, pattern: String, include_hidden: bool },
+}
+
+impl FileOperation {
+    /// 操作的目标路径
+    pub fn path(&self) -> &str {
+        match self {
+            FileOperation::ReadDir { path, .. }
+            | FileOperation::CreateDir { path }
+            | FileOperation::Remove { path }
+            | FileOperation::Rename { path, .. }
+            | FileOperation::Search { path, .. } => path,
+        }
+    }
+
+    fn action(&self, id: i32) -> FileAction {
+        match self.clone() {
+            FileOperation::ReadDir { path, include_hidden } => FileAction::ReadDir { id, path, include_hidden },
+            FileOperation::CreateDir { path } => FileAction::CreateDir { id, path },
+            FileOperation::Remove { path } => FileAction::Remove { id, path },
+            FileOperation::Rename { path, new_name } => FileAction::Rename { id, path, new_name },
+            FileOperation::Search { path, pattern, include_hidden } => {
+                FileAction::Search { id, path, pattern, include_hidden }
+            }
+        }
+    }
+
+    /// 对端成功时的回应是否为条目列表
+    fn returns_entries(&self) -> bool {
+        matches!(self, FileOperation::ReadDir { .. } | FileOperation::Search { .. })
+    }
+}
+
+/// 目录列表
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirListing {
+    pub path: String,
+    pub include_hidden: bool,
+    pub entries: Vec<FileEntry>,
+}
+
+/// 操作结果（成功时为条目列表，创建、删除、重命名的列表为空）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationResult {
+    pub id: i32,
+    pub path: String,
+    pub result: Result<Vec<FileEntry>, String>,
+}
+
+/// 会话的远程文件管理状态
+#[derive(Debug, Default)]
+pub struct FileManager {
+    /// 等待对端回应的操作
+    pending: HashMap<i32, FileOperation>,
+    /// 尚未被 ArkTS 取走的结果
+    results: Vec<OperationResult>,
+    /// 最近一次读取的目录列表
+    listing: Option<DirListing>,
+}
+
+/// 去掉末尾的分隔符（根目录去掉后为空）
+fn trim_separators(path: &str) -> &str {
+    path.trim_end_matches(SEPARATORS)
+}
+
+/// 拆分为上级目录和名称
+fn split_path(path: &str) -> Option<(&str, &str)> {
+    let path = trim_separators(path);
+    let index = path.rfind(SEPARATORS)?;
+    Some((trim_separators(&path[..index]), &path[index + 1..]))
+}
+
+/// 检查同一目录下的新名称
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(SEPARATORS)
+}
+
+impl FileManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记操作，返回发给对端的请求
+    pub fn request(&mut self, id: i32, operation: FileOperation) -> Result<FileAction, String> {
+        // 创建、删除、重命名需要完整路径，不能是根目录
+        if !operation.returns_entries() && split_path(operation.path()).is_none() {
+            return Err(format!("无效的路径: {}", operation.path()));
+        }
+        match &operation {
+            FileOperation::Rename { new_name, .. } if !valid_name(new_name) => {
+                return Err(format!("无效的文件名: {}", new_name));
+            }
+            FileOperation::Search { pattern, .. } if pattern.is_empty() => {
+                return Err("搜索条件为空".to_string());
+            }
+            _ => {}
+        }
+        let action = operation.action(id);
+        self.pending.insert(id, operation);
+        Ok(action)
+    }
+
+    /// 请求发送失败时撤销操作
+    pub fn cancel(&mut self, id: i32) {
+        self.pending.remove(&id);
+    }
+
+    /// 连接断开：等待中的操作全部失败
+    pub fn fail_all(&mut self, error: &str) {
+        let mut pending: Vec<_> = self.pending.drain().collect();
+        pending.sort_by_key(|(id, _)| *id);
+        for (id, operation) in pending {
+            self.results.push(OperationResult {
+                id,
+                path: operation.path().to_string(),
+                result: Err(error.to_string()),
+            });
+        }
+    }
+
+    /// 处理对端的回应，不属于文件管理操作时原样返回
+    pub fn handle_response(&mut self, response: FileResponse) -> Option<FileResponse> {
+        let id = response.id();
+        let operation = match self.pending.remove(&id) {
+            Some(operation) => operation,
+            None => return Some(response),
+        };
+        let result = match response {
+            FileResponse::Dir { entries, .. } if operation.returns_entries() => Ok(entries),
+            FileResponse::Done { .. } if !operation.returns_entries() => Ok(Vec::new()),
+            FileResponse::Error { message, .. } => Err(message),
+            _ => Err("对端回应无效".to_string()),
+        };
+        match &result {
+            Ok(entries) => {
+                log::debug!("文件操作完成: {} {}", id, operation.path());
+                self.update_listing(&operation, entries);
+            }
+            Err(error) => log::warn!("文件操作失败: {} {}: {}", id, operation.path(), error),
+        }
+        self.results.push(OperationResult { id, path: operation.path().to_string(), result });
+        None
+    }
+
+    /// 取出操作结果
+    pub fn take_results(&mut self) -> Vec<OperationResult> {
+        std::mem::take(&mut self.results)
+    }
+
+    /// 最近一次读取的目录列表
+    pub fn listing(&self) -> Option<&DirListing> {
+        self.listing.as_ref()
+    }
+
+    /// 操作成功后更新缓存的目录列表
+    fn update_listing(&mut self, operation: &FileOperation, entries: &[FileEntry]) {
+        if let FileOperation::ReadDir { path, include_hidden } = operation {
+            self.listing = Some(DirListing {
+                path: path.clone(),
+                include_hidden: *include_hidden,
+                entries: entries.to_vec(),
+            });
+            return;
+        }
+
+        let (parent, name) = match split_path(operation.path()) {
+            Some(split) => split,
+            None => return,
+        };
+        let listing = match self.listing.as_mut() {
+            Some(listing) if trim_separators(&listing.path) == parent => listing,
+            _ => return,
+        };
+        match operation {
+            FileOperation::CreateDir { .. } => {
+                let is_hidden = name.starts_with('.');
+                if listing.entries.iter().any(|entry| entry.name == name) || (is_hidden && !listing.include_hidden) {
+                    return;
+                }
+                let modified = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or(0);
+                listing.entries.push(FileEntry {
+                    name: name.to_string(),
+                    entry_type: FileType::Dir,
+                    is_hidden,
+                    size: 0,
+                    modified,
+                });
+            }
+            FileOperation::Remove { .. } => listing.entries.retain(|entry| entry.name != name),
+            FileOperation::Rename { new_name, .. } => {
+                listing.entries.retain(|entry| entry.name != *new_name);
+                if let Some(entry) = listing.entries.iter_mut().find(|entry| entry.name == name) {
+                    entry.name = new_name.clone();
+                    entry.is_hidden = new_name.starts_with('.');
+                }
+                if !listing.include_hidden {
+                    listing.entries.retain(|entry| !entry.is_hidden);
+                }
+            }
+            FileOperation::ReadDir { .. } | FileOperation::Search { .. } => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, entry_type: FileType) -> FileEntry {
+        FileEntry { name: name.to_string(), entry_type, is_hidden: false, size: 10, modified: 1 }
+    }
+
+    fn names(manager: &FileManager) -> Vec<&str> {
+        manager.listing().unwrap().entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_listing_cached_and_updated() {
+        let mut manager = FileManager::new();
+        let action = manager
+            .request(1, FileOperation::ReadDir { path: "/home/user/".to_string(), include_hidden: false })
+            .unwrap();
+        assert_eq!(action, FileAction::ReadDir { id: 1, path: "/home/user/".to_string(), include_hidden: false });
+        let entries = vec![entry("docs", FileType::Dir), entry("a.txt", FileType::File)];
+        let dir = FileResponse::Dir { id: 1, path: "/home/user".to_string(), entries: entries.clone() };
+        assert_eq!(manager.handle_response(dir), None);
+        assert_eq!(manager.take_results(), vec![OperationResult {
+            id: 1,
+            path: "/home/user/".to_string(),
+            result: Ok(entries),
+        }]);
+
+        // 在缓存的目录下创建、重命名、删除
+        manager.request(2, FileOperation::CreateDir { path: "/home/user/new".to_string() }).unwrap();
+        manager.handle_response(FileResponse::Done { id: 2, file_num: 0 });
+        assert_eq!(manager.listing().unwrap().entries[2].entry_type, FileType::Dir);
+        let rename = FileOperation::Rename { path: "/home/user/a.txt".to_string(), new_name: ".a.txt".to_string() };
+        manager.request(3, rename).unwrap();
+        manager.handle_response(FileResponse::Done { id: 3, file_num: 0 });
+        manager.request(4, FileOperation::Remove { path: "/home/user/docs".to_string() }).unwrap();
+        manager.handle_response(FileResponse::Done { id: 4, file_num: 0 });
+        assert_eq!(names(&manager), vec!["new"]);
+
+        // 其他目录的操作和搜索不影响缓存
+        manager.request(5, FileOperation::Remove { path: "/home/user/new/x".to_string() }).unwrap();
+        manager.handle_response(FileResponse::Done { id: 5, file_num: 0 });
+        let search = FileOperation::Search {
+            path: "/home".to_string(),
+            pattern: "*.txt".to_string(),
+            include_hidden: false,
+        };
+        manager.request(6, search).unwrap();
+        let found = vec![entry("user/b.txt", FileType::File)];
+        manager.handle_response(FileResponse::Dir { id: 6, path: "/home".to_string(), entries: found.clone() });
+        assert_eq!(names(&manager), vec!["new"]);
+        assert_eq!(manager.take_results().last().unwrap().result, Ok(found));
+    }
+
+    #[test]
+    fn test_errors_and_passthrough() {
+        let mut manager = FileManager::new();
+        let invalid = FileOperation::Rename { path: "/tmp/a".to_string(), new_name: "../b".to_string() };
+        assert!(manager.request(1, invalid).is_err());
+        assert!(manager.request(1, FileOperation::Remove { path: "/".to_string() }).is_err());
+        assert!(manager.request(1, FileOperation::Remove { path: "C:\\".to_string() }).is_err());
+
+        // 传输任务的数据原样返回
+        let block = FileResponse::Block { id: 9, file_num: 0, blk_id: 0, data: vec![1] };
+        assert_eq!(manager.handle_response(block.clone()), Some(block));
+
+        manager.request(2, FileOperation::Remove { path: "C:\\data".to_string() }).unwrap();
+        let error = FileResponse::Error { id: 2, file_num: 0, message: "权限不足".to_string() };
+        assert_eq!(manager.handle_response(error), None);
+        manager.request(3, FileOperation::CreateDir { path: "/tmp/x".to_string() }).unwrap();
+        manager.handle_response(FileResponse::Dir { id: 3, path: String::new(), entries: Vec::new() });
+        manager.request(4, FileOperation::CreateDir { path: "/tmp/y".to_string() }).unwrap();
+        manager.fail_all("连接已断开");
+
+        let errors: Vec<_> = manager.take_results().into_iter().map(|result| result.result.unwrap_err()).collect();
+        assert_eq!(errors, vec!["权限不足", "对端回应无效", "连接已断开"]);
+        assert!(manager.listing().is_none());
+    }
+}
@@ -0,0 +1,1250 @@
+/**
+ * 文件传输模块
+ *
+ * 在独立的 FILE_TRANSFER 连接上上传 / 下载文件和目录（与 RustDesk 一致按块传输）：
+ * - 发送每个文件前先发送摘要（大小、修改时间、SHA-256），接收端已有相同文件时跳过
+ * - 未完成的文件保存为 .download 并记录摘要和已接收的块，再次传输时摘要一致则只传输缺少的块
+ * - 收到完成标记时请求补发丢失的块，全部到达后校验 SHA-256，不一致时丢弃
+ * - SHA-256 在阻塞线程池中计算，不占用异步任务，也不持有传输状态的锁
+ * - 上传按设置的速率限速，下载的限速随请求发给对端执行
+ * - 进度以事件形式由 ArkTS 轮询，同一任务未取走的进度只保留最新的
+ * - 对端粘贴本地复制的文件时按对端的请求上传，只允许剪贴板中的文件
+ *
+ */
+
+use crate::protocol::{
+    FileAction, FileDigest, FileEntry, FileResponse, FileType, InputEventSender, FILE_BLOCK_SIZE,
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+/// 未完成文件的后缀
+const DOWNLOAD_SUFFIX: &str = ".download";
+/// 未完成文件的摘要记录后缀
+const DIGEST_SUFFIX: &str = ".digest";
+/// 上传时等待对端确认摘要的超时时间
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(10);
+/// 对端没有回应完成标记时重发的次数
+const DONE_RETRIES: u32 = 3;
+/// 每接收多少块保存一次已接收块记录
+const RECORD_INTERVAL: u32 = 64;
+/// 一次请求补发的最大块数（一个数据报内）
+const MAX_MISSING_BLOCKS: usize = 4096;
+
+/// 修改时间（Unix 秒）
+fn modified_secs(meta: &fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// 由文件信息构建条目（link 表示条目本身是符号链接）
+pub fn file_entry(name: String, meta: &fs::Metadata, link: bool) -> FileEntry {
+    let entry_type = match (meta.is_dir(), link) {
+        (true, true) => FileType::DirLink,
+        (true, false) => FileType::Dir,
+        (false, true) => FileType::FileLink,
+        (false, false) => FileType::File,
+    };
+    let is_hidden = name.rsplit('/').next().is_some_and(|base| base.starts_with('.'));
+    FileEntry {
+        name,
+        entry_type,
+        is_hidden,
+        size: if meta.is_dir() { 0 } else { meta.len() },
+        modified: modified_secs(meta),
+    }
+}
+
+/// 列出要传输的文件：单个文件返回名称为空的一项，目录递归列出（相对路径，目录在其内容之前）
+pub fn scan(path: &Path, include_hidden: bool) -> io::Result<Vec<FileEntry>> {
+    let meta = fs::metadata(path)?;
+    if !meta.is_dir() {
+        return Ok(vec![file_entry(String::new(), &meta, false)]);
+    }
+    let mut entries = Vec::new();
+    scan_dir(path, "", include_hidden, &mut entries)?;
+    Ok(entries)
+}
+
+fn scan_dir(dir: &Path, prefix: &str, include_hidden: bool, entries: &mut Vec<FileEntry>) -> io::Result<()> {
+    let mut children = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    children.sort_by_key(|child| child.file_name());
+    for child in children {
+        let name = child.file_name().to_string_lossy().into_owned();
+        if !include_hidden && name.starts_with('.') {
+            continue;
+        }
+        let link = child.file_type()?.is_symlink();
+        let meta = fs::metadata(child.path())?;
+        let relative = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+        let entry = file_entry(relative, &meta, link);
+        // 不进入链接的目录，避免循环
+        let descend = entry.entry_type == FileType::Dir;
+        let relative = entry.name.clone();
+        entries.push(entry);
+        if descend {
+            scan_dir(&child.path(), &relative, include_hidden, entries)?;
+        }
+    }
+    Ok(())
+}
+
+/// 把传输列表中的相对路径拼接到目标路径（拒绝越出目标目录的路径）
+pub fn join_relative(base: &Path, name: &str) -> io::Result<PathBuf> {
+    let mut path = base.to_path_buf();
+    if name.is_empty() {
+        return Ok(path);
+    }
+    for part in name.split('/') {
+        if part.is_empty() || part == "." || part == ".." || part.contains(['\\', ':']) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("无效的文件名: {}", name)));
+        }
+        path.push(part);
+    }
+    Ok(path)
+}
+
+/// 计算文件内容的 SHA-256
+fn hash_file(path: &Path) -> io::Result<[u8; 32]> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().into())
+}
+
+/// 计算文件摘要
+pub fn file_digest(path: &Path) -> io::Result<FileDigest> {
+    let meta = fs::metadata(path)?;
+    Ok(FileDigest { size: meta.len(), modified: modified_secs(&meta), hash: hash_file(path)? })
+}
+
+/// 摘要记录（保存在 .digest 文件的第一行）
+fn digest_record(digest: &FileDigest) -> String {
+    let hash: String = digest.hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{} {} {}", digest.size, digest.modified, hash)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// 文件的块数
+fn block_count(size: u64) -> u32 {
+    size.div_ceil(FILE_BLOCK_SIZE as u64) as u32
+}
+
+/// 已接收块的位图（保存在 .digest 文件的第二行，十六进制）
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BlockMap {
+    bits: Vec<u8>,
+    count: u32,
+}
+
+impl BlockMap {
+    fn new(count: u32) -> Self {
+        Self { bits: vec![0; (count as usize).div_ceil(8)], count }
+    }
+
+    /// 从记录解析，块数不符时返回 None
+    fn parse(hex: &str, count: u32) -> Option<Self> {
+        let hex = hex.trim();
+        let mut map = Self::new(count);
+        if hex.len() != map.bits.len() * 2 {
+            return None;
+        }
+        for (byte, chunk) in map.bits.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+        }
+        Some(map)
+    }
+
+    fn to_hex(&self) -> String {
+        self.bits.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn contains(&self, blk_id: u32) -> bool {
+        blk_id < self.count && self.bits[blk_id as usize / 8] & (1 << (blk_id % 8)) != 0
+    }
+
+    fn insert(&mut self, blk_id: u32) {
+        self.bits[blk_id as usize / 8] |= 1 << (blk_id % 8);
+    }
+
+    /// 缺少的块（最多 limit 个）
+    fn missing(&self, limit: usize) -> Vec<u32> {
+        (0..self.count).filter(|blk_id| !self.contains(*blk_id)).take(limit).collect()
+    }
+}
+
+/// 接收中的文件（写入 .download，完成后校验并改名）
+///
+/// 已接收的块记录在 .digest 中（定期保存，取消传输时也会保存），
+/// 乱序到达或丢失的块不会被当作已接收
+#[derive(Debug)]
+pub struct FileReceiver {
+    path: PathBuf,
+    digest: FileDigest,
+    file: File,
+    blocks: BlockMap,
+    /// 上次保存记录后新接收的块数
+    unsaved: u32,
+}
+
+impl FileReceiver {
+    /// 根据发送端的摘要准备接收（计算本地文件的 SHA-256，需在阻塞线程中调用）；
+    /// 本地已有相同文件时返回 None
+    pub fn open(path: PathBuf, digest: FileDigest) -> io::Result<Option<Self>> {
+        let same_size = fs::metadata(&path).is_ok_and(|meta| meta.is_file() && meta.len() == digest.size);
+        if same_size && hash_file(&path)? == digest.hash {
+            return Ok(None);
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let temp = with_suffix(&path, DOWNLOAD_SUFFIX);
+        let record = with_suffix(&path, DIGEST_SUFFIX);
+        let count = block_count(digest.size);
+        // 摘要不同或未完成的文件已不存在时重新开始
+        let saved = fs::read_to_string(&record).ok().filter(|_| temp.is_file()).and_then(|record| {
+            let (head, blocks) = record.split_once('\n')?;
+            (head == digest_record(&digest)).then(|| BlockMap::parse(blocks, count))?
+        });
+        let file = OpenOptions::new().create(true).write(true).truncate(false).open(&temp)?;
+        let mut receiver = Self { path, digest, file, blocks: BlockMap::new(count), unsaved: 0 };
+        match saved {
+            Some(blocks) => receiver.blocks = blocks,
+            None => {
+                receiver.file.set_len(0)?;
+                receiver.save_record()?;
+            }
+        }
+        Ok(Some(receiver))
+    }
+
+    /// 发送端开始发送的块（第一个缺少的块，之后缺少的块在完成时请求补发）
+    pub fn offset_blk(&self) -> u32 {
+        self.blocks.missing(1).first().copied().unwrap_or(self.blocks.count)
+    }
+
+    /// 已接收的字节数
+    pub fn received_bytes(&self) -> u64 {
+        (0..self.blocks.count)
+            .filter(|blk_id| self.blocks.contains(*blk_id))
+            .map(|blk_id| self.block_len(blk_id) as u64)
+            .sum()
+    }
+
+    /// 缺少的块（一次最多请求 MAX_MISSING_BLOCKS 块）
+    pub fn missing(&self) -> Vec<u32> {
+        self.blocks.missing(MAX_MISSING_BLOCKS)
+    }
+
+    fn block_len(&self, blk_id: u32) -> usize {
+        let offset = blk_id as u64 * FILE_BLOCK_SIZE as u64;
+        self.digest.size.saturating_sub(offset).min(FILE_BLOCK_SIZE as u64) as usize
+    }
+
+    /// 写入一块，返回是否为新接收的块（重复的块忽略）
+    pub fn write_block(&mut self, blk_id: u32, data: &[u8]) -> io::Result<bool> {
+        if blk_id >= self.blocks.count || data.len() != self.block_len(blk_id) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "文件块超出文件大小"));
+        }
+        if self.blocks.contains(blk_id) {
+            return Ok(false);
+        }
+        self.file.seek(SeekFrom::Start(blk_id as u64 * FILE_BLOCK_SIZE as u64))?;
+        self.file.write_all(data)?;
+        self.blocks.insert(blk_id);
+        self.unsaved += 1;
+        if self.unsaved >= RECORD_INTERVAL {
+            self.save_record()?;
+        }
+        Ok(true)
+    }
+
+    /// 保存摘要和已接收的块
+    fn save_record(&mut self) -> io::Result<()> {
+        let record = format!("{}\n{}", digest_record(&self.digest), self.blocks.to_hex());
+        fs::write(with_suffix(&self.path, DIGEST_SUFFIX), record)?;
+        self.unsaved = 0;
+        Ok(())
+    }
+
+    /// 接收完成：校验摘要后改名为目标文件，校验失败时删除未完成的文件（需在阻塞线程中调用）
+    pub fn finish(mut self) -> io::Result<()> {
+        self.file.sync_all()?;
+        self.unsaved = 0;
+        let temp = with_suffix(&self.path, DOWNLOAD_SUFFIX);
+        let record = with_suffix(&self.path, DIGEST_SUFFIX);
+        if hash_file(&temp)? != self.digest.hash {
+            let _ = fs::remove_file(&temp);
+            let _ = fs::remove_file(&record);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "文件校验失败"));
+        }
+        fs::rename(&temp, &self.path)?;
+        let _ = fs::remove_file(&record);
+        Ok(())
+    }
+}
+
+impl Drop for FileReceiver {
+    /// 取消或中断时保存已接收的块，下次传输时继续
+    fn drop(&mut self) {
+        if self.unsaved > 0 {
+            let _ = self.save_record();
+        }
+    }
+}
+
+/// 发送中的文件（按块读取）
+#[derive(Debug)]
+pub struct FileSender {
+    file: File,
+    blk_id: u32,
+}
+
+impl FileSender {
+    /// 从指定块开始读取
+    pub fn open(path: &Path, offset_blk: u32) -> io::Result<Self> {
+        Ok(Self { file: File::open(path)?, blk_id: offset_blk })
+    }
+
+    /// 读取下一块，文件结束时返回 None
+    pub fn next_block(&mut self) -> io::Result<Option<(u32, Vec<u8>)>> {
+        let block = self.read_block(self.blk_id)?;
+        if block.is_empty() {
+            return Ok(None);
+        }
+        let blk_id = self.blk_id;
+        self.blk_id += 1;
+        Ok(Some((blk_id, block)))
+    }
+
+    /// 读取指定的块（补发对端缺少的块）
+    pub fn read_block(&mut self, blk_id: u32) -> io::Result<Vec<u8>> {
+        self.file.seek(SeekFrom::Start(blk_id as u64 * FILE_BLOCK_SIZE as u64))?;
+        let mut block = vec![0u8; FILE_BLOCK_SIZE];
+        let mut len = 0;
+        while len < block.len() {
+            let n = self.file.read(&mut block[len..])?;
+            if n == 0 {
+                break;
+            }
+            len += n;
+        }
+        block.truncate(len);
+        Ok(block)
+    }
+}
+
+/// 限速器（字节/秒，0 不限速）
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: u32,
+    start: Instant,
+    sent: u64,
+}
+
+impl RateLimiter {
+    pub fn new(rate: u32) -> Self {
+        Self { rate, start: Instant::now(), sent: 0 }
+    }
+
+    /// 记录已发送的字节，返回发送下一块前需要等待的时间
+    pub fn consume(&mut self, bytes: usize) -> Duration {
+        let elapsed = self.start.elapsed();
+        self.consume_at(bytes, elapsed)
+    }
+
+    fn consume_at(&mut self, bytes: usize, elapsed: Duration) -> Duration {
+        if self.rate == 0 {
+            return Duration::ZERO;
+        }
+        self.sent += bytes as u64;
+        Duration::from_secs_f64(self.sent as f64 / self.rate as f64).saturating_sub(elapsed)
+    }
+}
+
+/// 传输方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    Upload,
+    Download,
+}
+
+/// 传输事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferEvent {
+    /// 进度（file_num 为正在传输的文件在列表中的序号）
+    Progress { id: i32, file_num: usize, file_count: usize, transferred: u64, total: u64 },
+    Done { id: i32 },
+    Failed { id: i32, error: String },
+    Cancelled { id: i32 },
+}
+
+/// 上传任务收到的对端确认
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Confirmation {
+    /// 回应摘要：从指定块开始发送，None 表示对端已有相同文件；
+    /// 回应完成标记：None 表示对端已校验通过
+    Offset { file_num: i32, offset_blk: Option<u32> },
+    /// 回应完成标记：对端缺少的块
+    Missing { file_num: i32, blocks: Vec<u32> },
+}
+
+impl Confirmation {
+    fn file_num(&self) -> i32 {
+        match self {
+            Confirmation::Offset { file_num, .. } | Confirmation::Missing { file_num, .. } => *file_num,
+        }
+    }
+}
+
+/// 上传任务收到的确认（Err 为对端报告的错误）
+pub type ConfirmResult = Result<Confirmation, String>;
+
+/// 下载中需要计算 SHA-256 的文件操作（由调用方在锁外的阻塞线程中执行）
+#[derive(Debug)]
+pub enum HashTask {
+    /// 收到摘要：检查本地文件，准备接收
+    Open { id: i32, file_num: i32, path: PathBuf, digest: FileDigest },
+    /// 全部块已到达：校验后改名
+    Finish { id: i32, file_num: i32, receiver: FileReceiver },
+}
+
+/// HashTask 的执行结果
+#[derive(Debug)]
+pub enum HashResult {
+    Opened { id: i32, file_num: i32, size: u64, result: io::Result<Option<FileReceiver>> },
+    Finished { id: i32, file_num: i32, result: io::Result<()> },
+}
+
+impl HashTask {
+    pub fn id(&self) -> i32 {
+        match self {
+            HashTask::Open { id, .. } | HashTask::Finish { id, .. } => *id,
+        }
+    }
+
+    pub fn run(self) -> HashResult {
+        match self {
+            HashTask::Open { id, file_num, path, digest } => {
+                let size = digest.size;
+                HashResult::Opened { id, file_num, size, result: FileReceiver::open(path, digest) }
+            }
+            HashTask::Finish { id, file_num, receiver } => {
+                HashResult::Finished { id, file_num, result: receiver.finish() }
+            }
+        }
+    }
+}
+
+/// 处理文件传输数据的结果
+#[derive(Debug)]
+pub enum Handled {
+    /// 需要回复对端的操作
+    Reply(Option<FileAction>),
+    /// 需要计算 SHA-256，完成后交给 FileTransfers::hashed
+    Hash(HashTask),
+}
+
+/// 传输任务
+#[derive(Debug)]
+struct TransferJob {
+    direction: TransferDirection,
+    /// 本地路径（上传的来源、下载的目标）
+    local: PathBuf,
+    files: Vec<FileEntry>,
+    /// 尚未完成的文件数
+    remaining: usize,
+    file_num: usize,
+    total: u64,
+    transferred: u64,
+    /// 下载中正在接收的文件
+    receiver: Option<FileReceiver>,
+    /// 下载中最近校验通过的文件（对端重发完成标记时再次确认）
+    last_done: Option<i32>,
+    /// 上传任务等待的摘要确认
+    confirm: Option<mpsc::UnboundedSender<ConfirmResult>>,
+    /// 上传任务
+    task: Option<JoinHandle<()>>,
+}
+
+impl TransferJob {
+    fn new(direction: TransferDirection, local: PathBuf) -> Self {
+        Self {
+            direction,
+            local,
+            files: Vec::new(),
+            remaining: 0,
+            file_num: 0,
+            total: 0,
+            transferred: 0,
+            receiver: None,
+            last_done: None,
+            confirm: None,
+            task: None,
+        }
+    }
+
+    fn set_files(&mut self, files: Vec<FileEntry>) {
+        let transferable = files.iter().filter(|entry| !entry.entry_type.is_dir());
+        self.remaining = transferable.clone().count();
+        self.total = transferable.map(|entry| entry.size).sum();
+        self.files = files;
+    }
+
+    fn file_count(&self) -> usize {
+        self.files.iter().filter(|entry| !entry.entry_type.is_dir()).count()
+    }
+}
+
+/// 会话的文件传输状态
+#[derive(Debug)]
+pub struct FileTransfers {
+    jobs: HashMap<i32, TransferJob>,
+    next_id: i32,
+    /// 尚未被 ArkTS 取走的事件
+    events: Vec<TransferEvent>,
+    /// 限速（字节/秒，0 不限速）
+    rate: u32,
+    /// 本地剪贴板中的文件（允许对端请求）
+    shared: Vec<PathBuf>,
+}
+
+impl Default for FileTransfers {
+    fn default() -> Self {
+        Self { jobs: HashMap::new(), next_id: 1, events: Vec::new(), rate: 0, shared: Vec::new() }
+    }
+}
+
+impl FileTransfers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// 设置限速（对之后开始的任务生效）
+    pub fn set_rate(&mut self, rate: u32) {
+        self.rate = rate;
+    }
+
+    /// 进行中的任务数
+    pub fn active(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// 分配任务 ID（远程文件管理操作共用，避免对端回应混淆）
+    pub fn next_id(&mut self) -> i32 {
+        // 跳过对端请求的上传占用的 ID
+        while self.jobs.contains_key(&self.next_id) {
+            self.next_id += 1;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn add_job(&mut self, job: TransferJob) -> i32 {
+        let id = self.next_id();
+        self.jobs.insert(id, job);
+        id
+    }
+
+    /// 登记下载任务（文件列表由对端回应）
+    pub fn start_download(&mut self, local: PathBuf) -> i32 {
+        self.add_job(TransferJob::new(TransferDirection::Download, local))
+    }
+
+    /// 登记上传任务
+    pub fn start_upload(
+        &mut self,
+        local: PathBuf,
+        files: Vec<FileEntry>,
+        confirm: mpsc::UnboundedSender<ConfirmResult>,
+    ) -> i32 {
+        let mut job = TransferJob::new(TransferDirection::Upload, local);
+        job.set_files(files);
+        job.confirm = Some(confirm);
+        let id = self.add_job(job);
+        self.push_progress(id);
+        id
+    }
+
+    /// 设置本地剪贴板中的文件（替换之前的文件）
+    pub fn set_shared(&mut self, paths: Vec<PathBuf>) {
+        self.shared = paths;
+    }
+
+    /// 登记对端请求的上传（对端粘贴本地复制的文件），只允许剪贴板中的文件
+    pub fn serve(
+        &mut self,
+        id: i32,
+        path: &str,
+        include_hidden: bool,
+        rate: u32,
+        confirm: mpsc::UnboundedSender<ConfirmResult>,
+    ) -> Result<Upload, String> {
+        let local = PathBuf::from(path);
+        if !self.shared.contains(&local) {
+            return Err(format!("文件不在剪贴板中: {}", path));
+        }
+        if self.jobs.contains_key(&id) {
+            return Err(format!("传输任务 ID 冲突: {}", id));
+        }
+        let files = scan(&local, include_hidden).map_err(|e| format!("读取文件失败: {}", e))?;
+        let mut job = TransferJob::new(TransferDirection::Upload, local.clone());
+        job.set_files(files.clone());
+        job.confirm = Some(confirm);
+        self.jobs.insert(id, job);
+        self.push_progress(id);
+        // 双方都限速时取较小的
+        let rate = match (rate, self.rate) {
+            (0, rate) | (rate, 0) => rate,
+            (requested, own) => requested.min(own),
+        };
+        Ok(Upload { id, local, remote: None, files, rate })
+    }
+
+    /// 记录上传任务的后台任务（取消时终止）
+    pub fn set_task(&mut self, id: i32, task: JoinHandle<()>) {
+        match self.jobs.get_mut(&id) {
+            Some(job) => job.task = Some(task),
+            None => task.abort(),
+        }
+    }
+
+    /// 增加传输进度
+    pub fn add_progress(&mut self, id: i32, file_num: usize, bytes: u64) {
+        if let Some(job) = self.jobs.get_mut(&id) {
+            job.file_num = file_num;
+            job.transferred = (job.transferred + bytes).min(job.total);
+            self.push_progress(id);
+        }
+    }
+
+    /// 任务结束（成功或失败）
+    pub fn finish(&mut self, id: i32, result: Result<(), String>) {
+        if self.jobs.remove(&id).is_none() {
+            return;
+        }
+        match result {
+            Ok(()) => {
+                log::info!("文件传输完成: {}", id);
+                self.events.push(TransferEvent::Done { id });
+            }
+            Err(error) => {
+                log::warn!("文件传输失败: {}: {}", id, error);
+                self.events.push(TransferEvent::Failed { id, error });
+            }
+        }
+    }
+
+    /// 取消任务（未完成的文件保留，下次传输时继续），返回任务是否存在
+    pub fn cancel(&mut self, id: i32) -> bool {
+        let job = match self.jobs.remove(&id) {
+            Some(job) => job,
+            None => return false,
+        };
+        if let Some(task) = job.task {
+            task.abort();
+        }
+        self.events.push(TransferEvent::Cancelled { id });
+        true
+    }
+
+    /// 取消全部任务，返回被取消的任务
+    pub fn cancel_all(&mut self) -> Vec<i32> {
+        let ids: Vec<i32> = self.jobs.keys().copied().collect();
+        for id in &ids {
+            self.cancel(*id);
+        }
+        ids
+    }
+
+    /// 取出待处理的事件
+    pub fn take_events(&mut self) -> Vec<TransferEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// 记录进度事件（替换同一任务尚未取走的进度）
+    fn push_progress(&mut self, id: i32) {
+        let job = match self.jobs.get(&id) {
+            Some(job) => job,
+            None => return,
+        };
+        let event = TransferEvent::Progress {
+            id,
+            file_num: job.file_num,
+            file_count: job.file_count(),
+            transferred: job.transferred,
+            total: job.total,
+        };
+        let pending = self
+            .events
+            .iter_mut()
+            .find(|pending| matches!(pending, TransferEvent::Progress { id: pending_id, .. } if *pending_id == id));
+        match pending {
+            Some(pending) => *pending = event,
+            None => self.events.push(event),
+        }
+    }
+
+    /// 处理对端的文件操作（上传任务的确认、补发请求和取消）
+    pub fn handle_action(&mut self, action: FileAction) {
+        match action {
+            FileAction::Confirm { id, file_num, offset_blk } => {
+                self.confirm(id, Confirmation::Offset { file_num, offset_blk });
+            }
+            FileAction::Missing { id, file_num, blocks } => {
+                self.confirm(id, Confirmation::Missing { file_num, blocks });
+            }
+            FileAction::Cancel { id } => {
+                if self.jobs.contains_key(&id) {
+                    log::info!("对端取消了文件传输: {}", id);
+                    self.cancel(id);
+                }
+            }
+            other => log::debug!("忽略文件操作: {:?}", other),
+        }
+    }
+
+    fn confirm(&self, id: i32, confirmation: Confirmation) {
+        if let Some(confirm) = self.jobs.get(&id).and_then(|job| job.confirm.as_ref()) {
+            let _ = confirm.send(Ok(confirmation));
+        }
+    }
+
+    /// 处理对端的文件传输数据（下载中需要计算 SHA-256 时返回 HashTask）
+    pub fn handle_response(&mut self, response: FileResponse) -> Handled {
+        let id = response.id();
+        let job = match self.jobs.get_mut(&id) {
+            Some(job) => job,
+            None => return Handled::Reply(None),
+        };
+        if job.direction == TransferDirection::Upload {
+            // 上传时对端只会报告错误
+            if let (FileResponse::Error { message, .. }, Some(confirm)) = (&response, job.confirm.as_ref()) {
+                let _ = confirm.send(Err(message.clone()));
+            }
+            return Handled::Reply(None);
+        }
+
+        match self.receive(id, response) {
+            Ok(handled) => handled,
+            Err(error) => Handled::Reply(self.fail(id, error)),
+        }
+    }
+
+    /// 下载：SHA-256 计算完成，返回需要回复对端的操作
+    pub fn hashed(&mut self, result: HashResult) -> Option<FileAction> {
+        let (id, result) = match result {
+            HashResult::Opened { id, file_num, size, result } => (id, self.opened(id, file_num, size, result)),
+            HashResult::Finished { id, file_num, result } => (id, self.finished(id, file_num, result)),
+        };
+        result.unwrap_or_else(|error| self.fail(id, error))
+    }
+
+    /// 下载失败：结束任务并通知对端取消（任务已取消时不回复）
+    fn fail(&mut self, id: i32, error: String) -> Option<FileAction> {
+        if !self.jobs.contains_key(&id) {
+            return None;
+        }
+        self.finish(id, Err(error));
+        Some(FileAction::Cancel { id })
+    }
+
+    /// 下载：处理一条文件传输数据
+    fn receive(&mut self, id: i32, response: FileResponse) -> Result<Handled, String> {
+        let job = self.jobs.get_mut(&id).ok_or("传输任务不存在")?;
+        let reply = match response {
+            FileResponse::Dir { entries, .. } => {
+                job.set_files(entries);
+                for entry in job.files.iter().filter(|entry| entry.entry_type.is_dir()) {
+                    let path = join_relative(&job.local, &entry.name).map_err(|e| e.to_string())?;
+                    fs::create_dir_all(&path).map_err(|e| format!("创建目录失败: {}", e))?;
+                }
+                if job.remaining == 0 {
+                    self.finish(id, Ok(()));
+                }
+                None
+            }
+            FileResponse::Digest { file_num, digest, .. } => {
+                let entry = job
+                    .files
+                    .get(file_num as usize)
+                    .filter(|entry| !entry.entry_type.is_dir())
+                    .ok_or_else(|| format!("文件序号无效: {}", file_num))?;
+                let path = join_relative(&job.local, &entry.name).map_err(|e| e.to_string())?;
+                job.file_num = file_num as usize;
+                return Ok(Handled::Hash(HashTask::Open { id, file_num, path, digest }));
+            }
+            FileResponse::Block { file_num, blk_id, data, .. } => {
+                // 已校验通过的文件迟到或重复的块
+                let receiver = match job.receiver.as_mut() {
+                    Some(receiver) if job.file_num == file_num as usize => receiver,
+                    _ if job.last_done == Some(file_num) => return Ok(Handled::Reply(None)),
+                    _ => return Err("未收到文件摘要".to_string()),
+                };
+                if receiver.write_block(blk_id, &data).map_err(|e| format!("写入文件失败: {}", e))? {
+                    job.transferred += data.len() as u64;
+                    self.push_progress(id);
+                }
+                None
+            }
+            FileResponse::Done { file_num, .. } => {
+                let receiver = match job.receiver.as_ref() {
+                    Some(receiver) if job.file_num == file_num as usize => receiver,
+                    // 确认丢失时对端会重发完成标记
+                    _ if job.last_done == Some(file_num) => {
+                        return Ok(Handled::Reply(Some(FileAction::Confirm { id, file_num, offset_blk: None })));
+                    }
+                    _ => return Err("未收到文件摘要".to_string()),
+                };
+                let blocks = receiver.missing();
+                if !blocks.is_empty() {
+                    log::debug!("请求补发文件块: {} #{}: {} 块", id, file_num, blocks.len());
+                    return Ok(Handled::Reply(Some(FileAction::Missing { id, file_num, blocks })));
+                }
+                let receiver = job.receiver.take().ok_or("未收到文件摘要")?;
+                return Ok(Handled::Hash(HashTask::Finish { id, file_num, receiver }));
+            }
+            FileResponse::Error { message, .. } => return Err(message),
+        };
+        Ok(Handled::Reply(reply))
+    }
+
+    /// 下载：摘要检查完成，回复对端从哪一块开始发送
+    fn opened(
+        &mut self,
+        id: i32,
+        file_num: i32,
+        size: u64,
+        result: io::Result<Option<FileReceiver>>,
+    ) -> Result<Option<FileAction>, String> {
+        let job = match self.jobs.get_mut(&id) {
+            Some(job) => job,
+            None => return Ok(None),
+        };
+        let offset_blk = match result.map_err(|e| format!("创建文件失败: {}", e))? {
+            Some(receiver) => {
+                let offset_blk = receiver.offset_blk();
+                job.transferred += receiver.received_bytes();
+                job.receiver = Some(receiver);
+                Some(offset_blk)
+            }
+            None => {
+                // 本地已有相同文件
+                job.transferred += size;
+                job.remaining = job.remaining.saturating_sub(1);
+                None
+            }
+        };
+        let done = job.remaining == 0;
+        self.push_progress(id);
+        if done {
+            self.finish(id, Ok(()));
+        }
+        Ok(Some(FileAction::Confirm { id, file_num, offset_blk }))
+    }
+
+    /// 下载：文件校验完成，确认后对端开始发送下一个文件
+    fn finished(&mut self, id: i32, file_num: i32, result: io::Result<()>) -> Result<Option<FileAction>, String> {
+        let job = match self.jobs.get_mut(&id) {
+            Some(job) => job,
+            None => return Ok(None),
+        };
+        result.map_err(|e| e.to_string())?;
+        job.last_done = Some(file_num);
+        job.remaining = job.remaining.saturating_sub(1);
+        if job.remaining == 0 {
+            self.finish(id, Ok(()));
+        }
+        Ok(Some(FileAction::Confirm { id, file_num, offset_blk: None }))
+    }
+}
+
+/// 处理对端的文件传输数据，返回需要回复对端的操作
+///
+/// SHA-256 在阻塞线程池中计算，期间不持有传输状态的锁
+pub async fn handle_response(transfers: &Mutex<FileTransfers>, response: FileResponse) -> Option<FileAction> {
+    let task = match transfers.lock().await.handle_response(response) {
+        Handled::Reply(reply) => return reply,
+        Handled::Hash(task) => task,
+    };
+    let id = task.id();
+    match tokio::task::spawn_blocking(move || task.run()).await {
+        Ok(result) => transfers.lock().await.hashed(result),
+        Err(e) => transfers.lock().await.fail(id, format!("校验文件失败: {}", e)),
+    }
+}
+
+/// 上传任务参数
+#[derive(Debug, Clone)]
+pub struct Upload {
+    pub id: i32,
+    pub local: PathBuf,
+    /// 对端的目标路径，对端请求的上传为 None（回应文件列表）
+    pub remote: Option<String>,
+    pub files: Vec<FileEntry>,
+    pub rate: u32,
+}
+
+/// 执行上传任务（后台任务）
+pub async fn run_upload(
+    upload: Upload,
+    confirm: mpsc::UnboundedReceiver<ConfirmResult>,
+    transfers: Arc<Mutex<FileTransfers>>,
+    sender: Arc<Mutex<Option<InputEventSender>>>,
+) {
+    let id = upload.id;
+    let result = upload_files(upload, confirm, &transfers, &sender).await;
+    transfers.lock().await.finish(id, result);
+}
+
+/// 通知对端接收（或回应对端请求的文件列表）后依次发送每个文件：
+/// 摘要、等待确认、从确认的块开始发送内容、完成标记，按对端的请求补发缺少的块直到对端校验通过
+async fn upload_files(
+    upload: Upload,
+    mut confirm: mpsc::UnboundedReceiver<ConfirmResult>,
+    transfers: &Mutex<FileTransfers>,
+    sender: &Mutex<Option<InputEventSender>>,
+) -> Result<(), String> {
+    let id = upload.id;
+    match upload.remote {
+        Some(remote) => {
+            let action = FileAction::Receive { id, path: remote, files: upload.files.clone() };
+            let sender = sender.lock().await;
+            let sender = sender.as_ref().ok_or("连接已断开")?;
+            sender.send_file_action(&action).await.map_err(|e| format!("发送文件请求失败: {}", e))?;
+        }
+        None => {
+            let path = upload.local.to_string_lossy().into_owned();
+            send_response(sender, &FileResponse::Dir { id, path, entries: upload.files.clone() }).await?;
+        }
+    }
+
+    let mut limiter = RateLimiter::new(upload.rate);
+    for (index, entry) in upload.files.iter().enumerate() {
+        if entry.entry_type.is_dir() {
+            continue;
+        }
+        let path = join_relative(&upload.local, &entry.name).map_err(|e| e.to_string())?;
+        let digest_path = path.clone();
+        let digest = tokio::task::spawn_blocking(move || file_digest(&digest_path))
+            .await
+            .map_err(|e| format!("读取文件失败: {}", e))?
+            .map_err(|e| format!("读取文件失败: {}", e))?;
+        let size = digest.size;
+        let file_num = index as i32;
+        send_response(sender, &FileResponse::Digest { id, file_num, digest }).await?;
+
+        let offset_blk = match wait_confirm(&mut confirm, file_num).await? {
+            Some(Confirmation::Offset { offset_blk, .. }) => offset_blk,
+            Some(Confirmation::Missing { .. }) => return Err("对端的确认无效".to_string()),
+            None => return Err("等待对端确认超时".to_string()),
+        };
+        let offset_blk = match offset_blk {
+            Some(offset_blk) => offset_blk,
+            None => {
+                // 对端已有相同文件
+                transfers.lock().await.add_progress(id, index, size);
+                continue;
+            }
+        };
+        let resumed = (offset_blk as u64 * FILE_BLOCK_SIZE as u64).min(size);
+        transfers.lock().await.add_progress(id, index, resumed);
+
+        let mut file = FileSender::open(&path, offset_blk).map_err(|e| format!("读取文件失败: {}", e))?;
+        while let Some((blk_id, data)) = file.next_block().map_err(|e| format!("读取文件失败: {}", e))? {
+            let len = data.len();
+            let block = FileResponse::Block { id, file_num, blk_id, data };
+            send_response(sender, &block).await?;
+            transfers.lock().await.add_progress(id, index, len as u64);
+            let wait = limiter.consume(len);
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        let mut retries = 0;
+        loop {
+            send_response(sender, &FileResponse::Done { id, file_num }).await?;
+            let blocks = match wait_confirm(&mut confirm, file_num).await? {
+                Some(Confirmation::Offset { .. }) => break,
+                Some(Confirmation::Missing { blocks, .. }) => blocks,
+                None if retries < DONE_RETRIES => {
+                    retries += 1;
+                    continue;
+                }
+                None => return Err("等待对端确认超时".to_string()),
+            };
+            log::debug!("补发文件块: {} #{}: {} 块", id, file_num, blocks.len());
+            for blk_id in blocks {
+                let data = file.read_block(blk_id).map_err(|e| format!("读取文件失败: {}", e))?;
+                let len = data.len();
+                send_response(sender, &FileResponse::Block { id, file_num, blk_id, data }).await?;
+                let wait = limiter.consume(len);
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 等待对端对指定文件的确认（忽略之前文件迟到的确认），超时返回 None
+async fn wait_confirm(
+    confirm: &mut mpsc::UnboundedReceiver<ConfirmResult>,
+    file_num: i32,
+) -> Result<Option<Confirmation>, String> {
+    let deadline = tokio::time::Instant::now() + CONFIRM_TIMEOUT;
+    loop {
+        match tokio::time::timeout_at(deadline, confirm.recv()).await {
+            Ok(Some(Ok(confirmation))) if confirmation.file_num() == file_num => return Ok(Some(confirmation)),
+            Ok(Some(Ok(_))) => continue,
+            Ok(Some(Err(error))) => return Err(error),
+            Ok(None) => return Err("传输已取消".to_string()),
+            Err(_) => return Ok(None),
+        }
+    }
+}
+
+async fn send_response(sender: &Mutex<Option<InputEventSender>>, response: &FileResponse) -> Result<(), String> {
+    let sender = sender.lock().await;
+    let sender = sender.as_ref().ok_or("连接已断开")?;
+    sender
+        .send_file_response(response)
+        .await
+        .map_err(|e| format!("发送文件数据失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试用临时目录
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("harmonydesk-{}-{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 处理传输数据（在当前线程计算 SHA-256）
+    fn handle(transfers: &mut FileTransfers, response: FileResponse) -> Option<FileAction> {
+        match transfers.handle_response(response) {
+            Handled::Reply(reply) => reply,
+            Handled::Hash(task) => transfers.hashed(task.run()),
+        }
+    }
+
+    /// 把发送端的文件按块交给下载任务
+    fn deliver(transfers: &mut FileTransfers, id: i32, file_num: i32, path: &Path) -> Option<FileAction> {
+        let digest = file_digest(path).unwrap();
+        let reply = handle(transfers, FileResponse::Digest { id, file_num, digest });
+        if let Some(FileAction::Confirm { offset_blk: Some(offset_blk), .. }) = reply {
+            let mut file = FileSender::open(path, offset_blk).unwrap();
+            while let Some((blk_id, data)) = file.next_block().unwrap() {
+                handle(transfers, FileResponse::Block { id, file_num, blk_id, data });
+            }
+            handle(transfers, FileResponse::Done { id, file_num });
+        }
+        reply
+    }
+
+    #[test]
+    fn test_scan_and_join() {
+        let dir = temp_dir("scan");
+        fs::create_dir_all(dir.join("sub/empty")).unwrap();
+        fs::write(dir.join("sub/a.txt"), b"hello").unwrap();
+        fs::write(dir.join(".hidden"), b"x").unwrap();
+
+        let names: Vec<_> = scan(&dir, false).unwrap().into_iter().map(|entry| entry.name).collect();
+        assert_eq!(names, vec!["sub", "sub/a.txt", "sub/empty"]);
+        let entries = scan(&dir, true).unwrap();
+        assert!(entries[0].is_hidden);
+        assert_eq!(entries[2].size, 5);
+
+        let single = scan(&dir.join("sub/a.txt"), false).unwrap();
+        assert_eq!((single[0].name.as_str(), single[0].entry_type), ("", FileType::File));
+
+        assert_eq!(join_relative(&dir, "sub/a.txt").unwrap(), dir.join("sub").join("a.txt"));
+        assert!(join_relative(&dir, "../etc/passwd").is_err());
+        assert!(join_relative(&dir, "/etc").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_download_resume_and_skip() {
+        let dir = temp_dir("download");
+        let source = dir.join("source.bin");
+        let content: Vec<u8> = (0..FILE_BLOCK_SIZE * 3 + 100).map(|i| (i % 251) as u8).collect();
+        fs::write(&source, &content).unwrap();
+        let digest = file_digest(&source).unwrap();
+        let target = dir.join("out").join("target.bin");
+
+        // 收到第一、三块（第二块丢失）后中断
+        let mut transfers = FileTransfers::new();
+        let id = transfers.start_download(target.clone());
+        let entry = scan(&source, false).unwrap();
+        handle(&mut transfers, FileResponse::Dir { id, path: String::new(), entries: entry.clone() });
+        handle(&mut transfers, FileResponse::Digest { id, file_num: 0, digest: digest.clone() });
+        let mut file = FileSender::open(&source, 0).unwrap();
+        for blk_id in [0, 2] {
+            let data = file.read_block(blk_id).unwrap();
+            handle(&mut transfers, FileResponse::Block { id, file_num: 0, blk_id, data });
+        }
+        assert!(transfers.cancel(id));
+        assert!(!target.exists());
+
+        // 重新下载时从缺少的第二块继续
+        let id = transfers.start_download(target.clone());
+        handle(&mut transfers, FileResponse::Dir { id, path: String::new(), entries: entry.clone() });
+        let reply = deliver(&mut transfers, id, 0, &source);
+        assert_eq!(reply, Some(FileAction::Confirm { id, file_num: 0, offset_blk: Some(1) }));
+        assert_eq!(fs::read(&target).unwrap(), content);
+        assert!(!with_suffix(&target, DOWNLOAD_SUFFIX).exists());
+        assert!(!with_suffix(&target, DIGEST_SUFFIX).exists());
+        let events = transfers.take_events();
+        assert_eq!(events.last(), Some(&TransferEvent::Done { id }));
+        assert!(events.contains(&TransferEvent::Progress {
+            id,
+            file_num: 0,
+            file_count: 1,
+            transferred: content.len() as u64,
+            total: content.len() as u64,
+        }));
+
+        // 内容相同的文件直接跳过
+        let id = transfers.start_download(target.clone());
+        handle(&mut transfers, FileResponse::Dir { id, path: String::new(), entries: entry });
+        let reply = deliver(&mut transfers, id, 0, &source);
+        assert_eq!(reply, Some(FileAction::Confirm { id, file_num: 0, offset_blk: None }));
+        assert_eq!(transfers.take_events().last(), Some(&TransferEvent::Done { id }));
+        assert_eq!(transfers.active(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_blocks_requested() {
+        let dir = temp_dir("missing");
+        let source = dir.join("source.bin");
+        let content: Vec<u8> = (0..FILE_BLOCK_SIZE * 4 + 7).map(|i| (i % 241) as u8).collect();
+        fs::write(&source, &content).unwrap();
+        let target = dir.join("target.bin");
+
+        let mut transfers = FileTransfers::new();
+        let id = transfers.start_download(target.clone());
+        handle(&mut transfers, FileResponse::Dir { id, path: String::new(), entries: scan(&source, false).unwrap() });
+        let digest = file_digest(&source).unwrap();
+        let reply = handle(&mut transfers, FileResponse::Digest { id, file_num: 0, digest });
+        assert_eq!(reply, Some(FileAction::Confirm { id, file_num: 0, offset_blk: Some(0) }));
+
+        // 乱序到达，第二块丢失：完成标记时请求补发
+        let mut file = FileSender::open(&source, 0).unwrap();
+        for blk_id in [4, 3, 2, 0] {
+            let data = file.read_block(blk_id).unwrap();
+            handle(&mut transfers, FileResponse::Block { id, file_num: 0, blk_id, data });
+        }
+        let reply = handle(&mut transfers, FileResponse::Done { id, file_num: 0 });
+        assert_eq!(reply, Some(FileAction::Missing { id, file_num: 0, blocks: vec![1] }));
+        assert!(!target.exists());
+
+        // 补发后校验通过并确认
+        let data = file.read_block(1).unwrap();
+        handle(&mut transfers, FileResponse::Block { id, file_num: 0, blk_id: 1, data });
+        let reply = handle(&mut transfers, FileResponse::Done { id, file_num: 0 });
+        assert_eq!(reply, Some(FileAction::Confirm { id, file_num: 0, offset_blk: None }));
+        assert_eq!(fs::read(&target).unwrap(), content);
+        assert_eq!(transfers.take_events().last(), Some(&TransferEvent::Done { id }));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_download_rejected() {
+        let dir = temp_dir("corrupt");
+        let target = dir.join("target.txt");
+        let digest = FileDigest { size: 4, modified: 0, hash: [0; 32] };
+        let entry = FileEntry {
+            name: String::new(),
+            entry_type: FileType::File,
+            is_hidden: false,
+            size: 4,
+            modified: 0,
+        };
+
+        let mut transfers = FileTransfers::new();
+        let id = transfers.start_download(target.clone());
+        handle(&mut transfers, FileResponse::Dir { id, path: String::new(), entries: vec![entry] });
+        handle(&mut transfers, FileResponse::Digest { id, file_num: 0, digest });
+        handle(&mut transfers, FileResponse::Block { id, file_num: 0, blk_id: 0, data: b"abcd".to_vec() });
+        let reply = handle(&mut transfers, FileResponse::Done { id, file_num: 0 });
+
+        assert_eq!(reply, Some(FileAction::Cancel { id }));
+        assert!(!target.exists());
+        assert!(!with_suffix(&target, DOWNLOAD_SUFFIX).exists());
+        assert!(matches!(transfers.take_events().last(), Some(TransferEvent::Failed { .. })));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_serve_shared_files_only() {
+        let dir = temp_dir("serve");
+        let shared = dir.join("shared.txt");
+        fs::write(&shared, b"hello").unwrap();
+        fs::write(dir.join("private.txt"), b"secret").unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+
+        let mut transfers = FileTransfers::new();
+        transfers.set_rate(1000);
+        transfers.set_shared(vec![shared.clone()]);
+        let (confirm, _confirm_rx) = mpsc::unbounded_channel();
+        assert!(transfers.serve(1, &path("private.txt"), false, 0, confirm.clone()).is_err());
+
+        let upload = transfers.serve(1, &path("shared.txt"), false, 500, confirm.clone()).unwrap();
+        assert_eq!((upload.remote, upload.rate, upload.files.len()), (None, 500, 1));
+        assert!(transfers.serve(1, &path("shared.txt"), false, 0, confirm.clone()).is_err());
+        // 本地分配的 ID 跳过对端占用的 ID
+        assert_eq!(transfers.next_id(), 2);
+
+        // 剪贴板变化后不再允许
+        transfers.set_shared(Vec::new());
+        assert!(transfers.serve(3, &path("shared.txt"), false, 0, confirm).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RateLimiter::new(1000);
+        assert_eq!(limiter.consume_at(500, Duration::ZERO), Duration::from_millis(500));
+        assert_eq!(limiter.consume_at(500, Duration::from_millis(400)), Duration::from_millis(600));
+        assert_eq!(limiter.consume_at(0, Duration::from_secs(2)), Duration::ZERO);
+
+        let mut unlimited = RateLimiter::new(0);
+        assert_eq!(unlimited.consume_at(1 << 30, Duration::ZERO), Duration::ZERO);
+    }
+}
@@ -0,0 +1,557 @@
+/**
+ * 触摸手势模块
+ *
+ * 把手机 / 平板上的触摸操作识别为桌面鼠标输入：
+ * - 单指轻点 → 左键单击
+ * - 双指轻点 → 右键单击
+ * - 长按后拖动 → 左键拖拽
+ * - 双指平移 → 滚轮
+ * - 双指捏合 → 缩放
+ *
+ * 支持两种模式：
+ * - 触控板模式：手指移动相对移动光标，点击发生在光标处
+ * - 直接模式：光标跟随手指，点击发生在手指处
+ *
+ * 识别结果使用视图坐标，由视口映射到远程桌面坐标
+ */
+
+use crate::protocol::MouseButton;
+use std::time::Instant;
+
+/// 轻点允许的最长按下时间（毫秒）
+const TAP_TIMEOUT_MS: u64 = 250;
+/// 长按判定时间（毫秒）
+const LONG_PRESS_MS: u64 = 500;
+/// 轻点 / 长按允许的移动距离
+const TOUCH_SLOP: f32 = 10.0;
+/// 判定为捏合的双指距离变化
+const PINCH_SLOP: f32 = 30.0;
+/// 双指平移多少距离产生一格滚轮
+const WHEEL_STEP: f32 = 20.0;
+
+/// 触摸模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchMode {
+    /// 触控板模式（相对移动光标）
+    Touchpad,
+    /// 直接模式（绝对坐标）
+    Direct,
+}
+
+impl TouchMode {
+    /// 从 ArkTS 传入的模式名解析
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "touchpad" => Some(TouchMode::Touchpad),
+            "direct" => Some(TouchMode::Direct),
+            _ => None,
+        }
+    }
+}
+
+/// 触摸动作（对应 HarmonyOS TouchType）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchAction {
+    Down,
+    Up,
+    Move,
+    Cancel,
+}
+
+impl TouchAction {
+    /// 从 ArkTS 传入的动作解析（名称或 TouchType 数值）
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "down" | "0" => Some(TouchAction::Down),
+            "up" | "1" => Some(TouchAction::Up),
+            "move" | "2" => Some(TouchAction::Move),
+            "cancel" | "3" => Some(TouchAction::Cancel),
+            _ => None,
+        }
+    }
+}
+
+/// 触摸点（本次事件中变化的手指）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchPoint {
+    pub id: i32,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// 手势识别结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureAction {
+    /// 光标移动到视图坐标（直接模式）
+    Move { x: f32, y: f32 },
+    /// 光标相对移动（触控板模式，视图像素）
+    MoveBy { dx: f32, dy: f32 },
+    /// 鼠标按键
+    Button { button: MouseButton, down: bool },
+    /// 滚轮（格数）
+    Wheel { dx: i32, dy: i32 },
+    /// 以视图坐标 (center_x, center_y) 为中心缩放 factor 倍
+    Zoom { factor: f32, center_x: f32, center_y: f32 },
+}
+
+/// 按下的手指
+#[derive(Debug, Clone, Copy)]
+struct Pointer {
+    id: i32,
+    /// 按下位置（双指手势开始时重置）
+    start: (f32, f32),
+    current: (f32, f32),
+}
+
+/// 当前识别中的手势
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gesture {
+    /// 没有手指
+    Idle,
+    /// 已按下，尚未判定
+    Pending,
+    /// 单指移动光标
+    Move,
+    /// 长按后拖拽（左键按下中）
+    Drag,
+    /// 双指平移滚动
+    Pan,
+    /// 双指捏合缩放
+    Pinch,
+    /// 手势已结束或无法识别，等待全部手指抬起
+    Finished,
+}
+
+/// 手势识别器（每个会话一个）
+#[derive(Debug)]
+pub struct GestureRecognizer {
+    mode: TouchMode,
+    gesture: Gesture,
+    epoch: Instant,
+    down_time: u64,
+    /// 当前按下的手指
+    pointers: Vec<Pointer>,
+    /// 本次手势中同时按下的最多手指数
+    max_pointers: usize,
+    /// 双指手势状态
+    pinch_start: f32,
+    pinch_last: f32,
+    pan_last: (f32, f32),
+    pan_accum: (f32, f32),
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self {
+            mode: TouchMode::Touchpad,
+            gesture: Gesture::Idle,
+            epoch: Instant::now(),
+            down_time: 0,
+            pointers: Vec::new(),
+            max_pointers: 0,
+            pinch_start: 0.0,
+            pinch_last: 0.0,
+            pan_last: (0.0, 0.0),
+            pan_accum: (0.0, 0.0),
+        }
+    }
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> TouchMode {
+        self.mode
+    }
+
+    /// 切换触摸模式（进行中的手势会被取消）
+    pub fn set_mode(&mut self, mode: TouchMode) -> Vec<GestureAction> {
+        let actions = self.cancel();
+        self.mode = mode;
+        actions
+    }
+
+    /// 处理一次触摸事件（pointers 为本次变化的手指）
+    pub fn handle(&mut self, pointers: &[TouchPoint], action: TouchAction) -> Vec<GestureAction> {
+        let now = self.epoch.elapsed().as_millis() as u64;
+        self.handle_at(pointers, action, now)
+    }
+
+    /// 处理一次触摸事件（指定时间戳，毫秒）
+    pub fn handle_at(
+        &mut self,
+        pointers: &[TouchPoint],
+        action: TouchAction,
+        time_ms: u64,
+    ) -> Vec<GestureAction> {
+        match action {
+            TouchAction::Down => self.on_down(pointers, time_ms),
+            TouchAction::Move => self.on_move(pointers, time_ms),
+            TouchAction::Up => self.on_up(pointers, time_ms),
+            TouchAction::Cancel => self.cancel(),
+        }
+    }
+
+    fn on_down(&mut self, pointers: &[TouchPoint], time_ms: u64) -> Vec<GestureAction> {
+        let mut actions = Vec::new();
+        if self.pointers.is_empty() {
+            self.gesture = Gesture::Pending;
+            self.down_time = time_ms;
+            self.max_pointers = 0;
+        }
+
+        for point in pointers {
+            self.pointers.retain(|pointer| pointer.id != point.id);
+            self.pointers.push(Pointer {
+                id: point.id,
+                start: (point.x, point.y),
+                current: (point.x, point.y),
+            });
+        }
+        self.max_pointers = self.max_pointers.max(self.pointers.len());
+
+        match self.pointers.len() {
+            1 => {}
+            2 if matches!(self.gesture, Gesture::Pending | Gesture::Move) => {
+                // 第二根手指按下，以当前位置作为双指手势起点
+                for pointer in self.pointers.iter_mut() {
+                    pointer.start = pointer.current;
+                }
+                self.pinch_start = self.distance();
+                self.pan_last = self.midpoint();
+                self.pan_accum = (0.0, 0.0);
+                if self.gesture == Gesture::Move {
+                    self.gesture = Gesture::Pending;
+                }
+            }
+            _ => {
+                // 三指及以上或拖拽中追加手指，不再识别
+                actions.extend(self.release_drag());
+                self.gesture = Gesture::Finished;
+            }
+        }
+        actions
+    }
+
+    fn on_move(&mut self, pointers: &[TouchPoint], time_ms: u64) -> Vec<GestureAction> {
+        // 单指移动前的位置，用于触控板模式的相对移动
+        let previous = self.pointers.first().map(|pointer| pointer.current);
+        for point in pointers {
+            if let Some(pointer) = self.pointers.iter_mut().find(|pointer| pointer.id == point.id) {
+                pointer.current = (point.x, point.y);
+            }
+        }
+
+        let mut actions = Vec::new();
+        match (self.gesture, self.pointers.len()) {
+            (Gesture::Pending, 1) => {
+                let Pointer { start, current, .. } = self.pointers[0];
+                if distance(start, current) <= TOUCH_SLOP {
+                    return actions;
+                }
+                if self.max_pointers > 1 {
+                    // 双指轻点抬起一指后又移动，放弃
+                    self.gesture = Gesture::Finished;
+                } else if time_ms.saturating_sub(self.down_time) >= LONG_PRESS_MS {
+                    self.gesture = Gesture::Drag;
+                    if self.mode == TouchMode::Direct {
+                        actions.push(GestureAction::Move { x: start.0, y: start.1 });
+                    }
+                    actions.push(GestureAction::Button { button: MouseButton::Left, down: true });
+                    actions.push(self.follow(start, current));
+                } else {
+                    self.gesture = Gesture::Move;
+                    actions.push(self.follow(start, current));
+                }
+            }
+            (Gesture::Move, 1) | (Gesture::Drag, 1) => {
+                if let Some(previous) = previous {
+                    let current = self.pointers[0].current;
+                    actions.push(self.follow(previous, current));
+                }
+            }
+            (Gesture::Pending, 2) => {
+                let moved = distance(self.pan_last, self.midpoint());
+                if (self.distance() - self.pinch_start).abs() > PINCH_SLOP {
+                    self.gesture = Gesture::Pinch;
+                    self.pinch_last = self.pinch_start;
+                    actions.extend(self.pinch());
+                } else if moved > TOUCH_SLOP {
+                    self.gesture = Gesture::Pan;
+                    actions.extend(self.pan());
+                }
+            }
+            (Gesture::Pan, 2) => actions.extend(self.pan()),
+            (Gesture::Pinch, 2) => actions.extend(self.pinch()),
+            _ => {}
+        }
+        actions
+    }
+
+    fn on_up(&mut self, pointers: &[TouchPoint], time_ms: u64) -> Vec<GestureAction> {
+        for point in pointers {
+            if let Some(pointer) = self.pointers.iter_mut().find(|pointer| pointer.id == point.id) {
+                pointer.current = (point.x, point.y);
+            }
+        }
+        let lifted: Vec<_> = self
+            .pointers
+            .iter()
+            .filter(|pointer| pointers.iter().any(|point| point.id == pointer.id))
+            .copied()
+            .collect();
+        self.pointers.retain(|pointer| !pointers.iter().any(|point| point.id == pointer.id));
+
+        let mut actions = Vec::new();
+        match self.gesture {
+            Gesture::Pan | Gesture::Pinch if !self.pointers.is_empty() => {
+                // 双指手势抬起一指后剩余手指不再移动光标
+                self.gesture = Gesture::Finished;
+            }
+            Gesture::Drag if self.pointers.is_empty() => actions.extend(self.release_drag()),
+            Gesture::Pending if self.pointers.is_empty() => {
+                let elapsed = time_ms.saturating_sub(self.down_time);
+                let still = lifted
+                    .iter()
+                    .all(|pointer| distance(pointer.start, pointer.current) <= TOUCH_SLOP);
+                if elapsed <= TAP_TIMEOUT_MS && still {
+                    let button = if self.max_pointers == 2 {
+                        MouseButton::Right
+                    } else {
+                        MouseButton::Left
+                    };
+                    if self.mode == TouchMode::Direct {
+                        if let Some(pointer) = lifted.first() {
+                            actions.push(GestureAction::Move { x: pointer.start.0, y: pointer.start.1 });
+                        }
+                    }
+                    actions.push(GestureAction::Button { button, down: true });
+                    actions.push(GestureAction::Button { button, down: false });
+                }
+            }
+            _ => {}
+        }
+
+        if self.pointers.is_empty() {
+            self.gesture = Gesture::Idle;
+        }
+        actions
+    }
+
+    /// 取消当前手势（拖拽中会抬起左键）
+    pub fn cancel(&mut self) -> Vec<GestureAction> {
+        let actions = self.release_drag();
+        self.pointers.clear();
+        self.gesture = Gesture::Idle;
+        actions
+    }
+
+    fn release_drag(&mut self) -> Vec<GestureAction> {
+        if self.gesture != Gesture::Drag {
+            return Vec::new();
+        }
+        self.gesture = Gesture::Finished;
+        vec![GestureAction::Button { button: MouseButton::Left, down: false }]
+    }
+
+    /// 单指移动光标：直接模式跟随手指，触控板模式按位移相对移动
+    fn follow(&self, from: (f32, f32), to: (f32, f32)) -> GestureAction {
+        match self.mode {
+            TouchMode::Direct => GestureAction::Move { x: to.0, y: to.1 },
+            TouchMode::Touchpad => GestureAction::MoveBy {
+                dx: to.0 - from.0,
+                dy: to.1 - from.1,
+            },
+        }
+    }
+
+    /// 双指平移：内容跟随手指（手指下移 → 向上滚动）
+    fn pan(&mut self) -> Option<GestureAction> {
+        let midpoint = self.midpoint();
+        self.pan_accum.0 += midpoint.0 - self.pan_last.0;
+        self.pan_accum.1 += midpoint.1 - self.pan_last.1;
+        self.pan_last = midpoint;
+
+        let dx = (self.pan_accum.0 / WHEEL_STEP).trunc();
+        let dy = (self.pan_accum.1 / WHEEL_STEP).trunc();
+        if dx == 0.0 && dy == 0.0 {
+            return None;
+        }
+        self.pan_accum.0 -= dx * WHEEL_STEP;
+        self.pan_accum.1 -= dy * WHEEL_STEP;
+        Some(GestureAction::Wheel { dx: dx as i32, dy: dy as i32 })
+    }
+
+    /// 双指捏合：按距离变化比例缩放
+    fn pinch(&mut self) -> Option<GestureAction> {
+        let distance = self.distance();
+        if self.pinch_last <= 0.0 || distance <= 0.0 {
+            return None;
+        }
+        let factor = distance / self.pinch_last;
+        self.pinch_last = distance;
+        let (center_x, center_y) = self.midpoint();
+        Some(GestureAction::Zoom { factor, center_x, center_y })
+    }
+
+    fn distance(&self) -> f32 {
+        match self.pointers.as_slice() {
+            [a, b, ..] => distance(a.current, b.current),
+            _ => 0.0,
+        }
+    }
+
+    fn midpoint(&self) -> (f32, f32) {
+        match self.pointers.as_slice() {
+            [a, b, ..] => (
+                (a.current.0 + b.current.0) / 2.0,
+                (a.current.1 + b.current.1) / 2.0,
+            ),
+            [a] => a.current,
+            [] => (0.0, 0.0),
+        }
+    }
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(id: i32, x: f32, y: f32) -> TouchPoint {
+        TouchPoint { id, x, y }
+    }
+
+    fn direct() -> GestureRecognizer {
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.set_mode(TouchMode::Direct);
+        recognizer
+    }
+
+    fn left(down: bool) -> GestureAction {
+        GestureAction::Button { button: MouseButton::Left, down }
+    }
+
+    #[test]
+    fn test_tap_is_left_click() {
+        let mut recognizer = direct();
+        assert!(recognizer.handle_at(&[point(0, 100.0, 50.0)], TouchAction::Down, 0).is_empty());
+        let actions = recognizer.handle_at(&[point(0, 102.0, 51.0)], TouchAction::Up, 120);
+        assert_eq!(
+            actions,
+            vec![GestureAction::Move { x: 100.0, y: 50.0 }, left(true), left(false)]
+        );
+
+        // 按太久不算轻点
+        recognizer.handle_at(&[point(0, 100.0, 50.0)], TouchAction::Down, 1000);
+        assert!(recognizer.handle_at(&[point(0, 100.0, 50.0)], TouchAction::Up, 1400).is_empty());
+    }
+
+    #[test]
+    fn test_two_finger_tap_is_right_click() {
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.handle_at(&[point(0, 100.0, 100.0)], TouchAction::Down, 0);
+        recognizer.handle_at(&[point(1, 160.0, 100.0)], TouchAction::Down, 30);
+        assert!(recognizer.handle_at(&[point(0, 100.0, 100.0)], TouchAction::Up, 150).is_empty());
+        let actions = recognizer.handle_at(&[point(1, 160.0, 100.0)], TouchAction::Up, 170);
+        let right = |down| GestureAction::Button { button: MouseButton::Right, down };
+        assert_eq!(actions, vec![right(true), right(false)]);
+    }
+
+    #[test]
+    fn test_long_press_drag() {
+        let mut recognizer = direct();
+        recognizer.handle_at(&[point(0, 10.0, 10.0)], TouchAction::Down, 0);
+        // 长按前的微小抖动不触发
+        assert!(recognizer.handle_at(&[point(0, 12.0, 10.0)], TouchAction::Move, 300).is_empty());
+
+        let actions = recognizer.handle_at(&[point(0, 40.0, 10.0)], TouchAction::Move, 600);
+        assert_eq!(
+            actions,
+            vec![
+                GestureAction::Move { x: 10.0, y: 10.0 },
+                left(true),
+                GestureAction::Move { x: 40.0, y: 10.0 },
+            ]
+        );
+        let actions = recognizer.handle_at(&[point(0, 80.0, 20.0)], TouchAction::Move, 650);
+        assert_eq!(actions, vec![GestureAction::Move { x: 80.0, y: 20.0 }]);
+        let actions = recognizer.handle_at(&[point(0, 80.0, 20.0)], TouchAction::Up, 700);
+        assert_eq!(actions, vec![left(false)]);
+    }
+
+    #[test]
+    fn test_touchpad_moves_cursor_relatively() {
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.handle_at(&[point(0, 10.0, 10.0)], TouchAction::Down, 0);
+        let actions = recognizer.handle_at(&[point(0, 40.0, 30.0)], TouchAction::Move, 50);
+        assert_eq!(actions, vec![GestureAction::MoveBy { dx: 30.0, dy: 20.0 }]);
+        let actions = recognizer.handle_at(&[point(0, 35.0, 30.0)], TouchAction::Move, 80);
+        assert_eq!(actions, vec![GestureAction::MoveBy { dx: -5.0, dy: 0.0 }]);
+        assert!(recognizer.handle_at(&[point(0, 35.0, 30.0)], TouchAction::Up, 100).is_empty());
+
+        // 触控板模式轻点发生在光标处
+        recognizer.handle_at(&[point(0, 300.0, 300.0)], TouchAction::Down, 200);
+        let actions = recognizer.handle_at(&[point(0, 300.0, 300.0)], TouchAction::Up, 250);
+        assert_eq!(actions, vec![left(true), left(false)]);
+    }
+
+    #[test]
+    fn test_two_finger_pan_scrolls() {
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.handle_at(&[point(0, 100.0, 100.0), point(1, 200.0, 100.0)], TouchAction::Down, 0);
+
+        let mut wheel = (0, 0);
+        for step in 1..=5 {
+            let y = 100.0 + step as f32 * 10.0;
+            let moved = [point(0, 100.0, y), point(1, 200.0, y)];
+            for action in recognizer.handle_at(&moved, TouchAction::Move, step * 16) {
+                match action {
+                    GestureAction::Wheel { dx, dy } => wheel = (wheel.0 + dx, wheel.1 + dy),
+                    other => panic!("unexpected action: {:?}", other),
+                }
+            }
+        }
+        // 下移 50 像素 → 向上滚动 2 格
+        assert_eq!(wheel, (0, 2));
+
+        recognizer.handle_at(&[point(0, 100.0, 150.0)], TouchAction::Up, 100);
+        // 剩下的手指不会移动光标
+        assert!(recognizer.handle_at(&[point(1, 300.0, 300.0)], TouchAction::Move, 120).is_empty());
+        assert!(recognizer.handle_at(&[point(1, 300.0, 300.0)], TouchAction::Up, 140).is_empty());
+    }
+
+    #[test]
+    fn test_pinch_zoom() {
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.handle_at(&[point(0, 100.0, 100.0), point(1, 200.0, 100.0)], TouchAction::Down, 0);
+        let actions =
+            recognizer.handle_at(&[point(0, 50.0, 100.0), point(1, 250.0, 100.0)], TouchAction::Move, 50);
+        assert_eq!(
+            actions,
+            vec![GestureAction::Zoom { factor: 2.0, center_x: 150.0, center_y: 100.0 }]
+        );
+
+        // 后续移动按上一次距离计算增量
+        let actions =
+            recognizer.handle_at(&[point(0, 100.0, 100.0), point(1, 200.0, 100.0)], TouchAction::Move, 80);
+        assert_eq!(
+            actions,
+            vec![GestureAction::Zoom { factor: 0.5, center_x: 150.0, center_y: 100.0 }]
+        );
+    }
+
+    #[test]
+    fn test_cancel_releases_drag() {
+        let mut recognizer = direct();
+        recognizer.handle_at(&[point(0, 10.0, 10.0)], TouchAction::Down, 0);
+        recognizer.handle_at(&[point(0, 50.0, 10.0)], TouchAction::Move, 700);
+        assert_eq!(recognizer.handle_at(&[], TouchAction::Cancel, 800), vec![left(false)]);
+        assert!(recognizer.handle_at(&[], TouchAction::Cancel, 900).is_empty());
+    }
+}
@@ -0,0 +1,270 @@
+/**
+ * 输入队列模块
+ *
+ * 手指拖动每秒会产生上百次鼠标移动，逐个发送会堆积延迟：
+ * - 连续的鼠标移动合并为最后一次，连续的相对移动合并为位移之和
+ * - 按键、鼠标按键、滚轮、触摸事件严格保持顺序
+ * - 按配置的频率批量发送，积压过多时丢弃过期的移动
+ */
+
+use crate::protocol::{KeyEvent, MouseEvent, MouseEventType, TouchEvent};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// 默认发送频率（Hz）
+pub const DEFAULT_INPUT_RATE: u32 = 60;
+/// 最高发送频率（Hz）
+const MAX_INPUT_RATE: u32 = 240;
+/// 队列积压上限，超过后只保留最新的鼠标移动
+const MAX_PENDING: usize = 128;
+
+/// 待发送的输入事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputCommand {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    Touch(TouchEvent),
+}
+
+impl InputCommand {
+    /// 是否为可合并 / 丢弃的鼠标移动
+    fn is_move(&self) -> bool {
+        matches!(self, InputCommand::Mouse(event) if event.event_type().ok() == Some(MouseEventType::Move))
+    }
+
+    /// 是否为可合并的相对移动（位移不能丢弃）
+    fn is_relative_move(&self) -> bool {
+        matches!(self, InputCommand::Mouse(event) if event.event_type().ok() == Some(MouseEventType::MoveRelative))
+    }
+}
+
+/// 输入队列（每个会话一个）
+#[derive(Debug)]
+pub struct InputQueue {
+    pending: VecDeque<InputCommand>,
+    /// 发送频率，0 表示不合并、立即发送
+    rate: u32,
+    /// 有需要立即发送的事件
+    urgent: bool,
+    /// 因积压丢弃的移动数量
+    dropped: u64,
+}
+
+impl Default for InputQueue {
+    fn default() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            rate: DEFAULT_INPUT_RATE,
+            urgent: false,
+            dropped: 0,
+        }
+    }
+}
+
+impl InputQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// 设置发送频率（Hz），0 表示立即发送
+    pub fn set_rate(&mut self, rate: u32) {
+        self.rate = rate.min(MAX_INPUT_RATE);
+    }
+
+    /// 批量发送的间隔，立即发送时返回 None
+    pub fn flush_interval(&self) -> Option<Duration> {
+        match self.rate {
+            0 => None,
+            rate => Some(Duration::from_micros(1_000_000 / rate as u64)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// 是否有需要立即发送的事件
+    pub fn is_urgent(&self) -> bool {
+        self.urgent
+    }
+
+    /// 加入队列，返回是否需要立即发送（非移动事件或立即发送模式）
+    pub fn push(&mut self, command: InputCommand) -> bool {
+        let is_move = command.is_move() || command.is_relative_move();
+        // 触摸移动和鼠标移动一样按发送频率批量发送（不合并）
+        let batched = is_move || matches!(&command, InputCommand::Touch(event) if event.is_update());
+        if is_move {
+            if let Some(InputCommand::Mouse(last)) = self.pending.back_mut() {
+                if let InputCommand::Mouse(event) = &command {
+                    // 类型、按住的按键和修饰键相同时合并：绝对移动只保留最新位置，相对移动累加位移
+                    if last.mask == event.mask && last.modifiers == event.modifiers {
+                        if command.is_relative_move() {
+                            last.x = last.x.saturating_add(event.x);
+                            last.y = last.y.saturating_add(event.y);
+                        } else {
+                            *last = event.clone();
+                        }
+                        self.urgent |= self.rate == 0;
+                        return self.rate == 0;
+                    }
+                }
+            }
+        }
+
+        self.pending.push_back(command);
+        if self.pending.len() > MAX_PENDING {
+            self.drop_stale_moves();
+        }
+        let urgent = !batched || self.rate == 0;
+        self.urgent |= urgent;
+        urgent
+    }
+
+    /// 取出全部待发送事件
+    pub fn drain(&mut self) -> Vec<InputCommand> {
+        self.urgent = false;
+        self.pending.drain(..).collect()
+    }
+
+    /// 只保留最新的一次鼠标移动（按键事件自带坐标，丢弃中间移动不影响点击位置）
+    fn drop_stale_moves(&mut self) {
+        let last_move = self.pending.iter().rposition(InputCommand::is_move);
+        let before = self.pending.len();
+        let mut index = 0;
+        self.pending.retain(|command| {
+            let keep = !command.is_move() || Some(index) == last_move;
+            index += 1;
+            keep
+        });
+        let dropped = before - self.pending.len();
+        if dropped > 0 {
+            self.dropped += dropped as u64;
+            log::debug!("Input queue backlog, dropped {} stale moves", dropped);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{ControlKey, KeyValue, KeyboardMode, MouseButton};
+
+    fn mouse(event_type: MouseEventType, buttons: u8, x: i32) -> InputCommand {
+        InputCommand::Mouse(MouseEvent::new(event_type, buttons, x, 0))
+    }
+
+    fn key() -> InputCommand {
+        InputCommand::Key(KeyEvent {
+            down: true,
+            press: false,
+            value: KeyValue::Control(ControlKey::Return),
+            modifiers: vec![],
+            mode: KeyboardMode::Map,
+        })
+    }
+
+    #[test]
+    fn test_coalesce_moves_keeps_order() {
+        let mut queue = InputQueue::new();
+        let left = MouseButton::Left as u8;
+
+        assert!(!queue.push(mouse(MouseEventType::Move, 0, 1)));
+        queue.push(mouse(MouseEventType::Move, 0, 2));
+        assert!(!queue.is_urgent());
+        assert!(queue.push(mouse(MouseEventType::Down, left, 2)));
+        assert!(queue.is_urgent());
+        queue.push(mouse(MouseEventType::Move, left, 3));
+        queue.push(mouse(MouseEventType::Move, left, 4));
+        queue.push(key());
+        queue.push(mouse(MouseEventType::Move, left, 5));
+        queue.push(mouse(MouseEventType::Up, left, 5));
+
+        assert_eq!(
+            queue.drain(),
+            vec![
+                mouse(MouseEventType::Move, 0, 2),
+                mouse(MouseEventType::Down, left, 2),
+                mouse(MouseEventType::Move, left, 4),
+                key(),
+                mouse(MouseEventType::Move, left, 5),
+                mouse(MouseEventType::Up, left, 5),
+            ]
+        );
+        assert!(queue.is_empty());
+        assert!(!queue.is_urgent());
+    }
+
+    #[test]
+    fn test_backlog_drops_stale_moves() {
+        let mut queue = InputQueue::new();
+        for i in 0..MAX_PENDING {
+            // 滚轮和移动交替，移动无法合并
+            queue.push(mouse(MouseEventType::Move, 0, i as i32));
+            queue.push(mouse(MouseEventType::Wheel, 0, 1));
+        }
+
+        // 非移动事件全部保留，移动只保留最新的
+        let drained = queue.drain();
+        assert_eq!(drained.iter().filter(|c| !c.is_move()).count(), MAX_PENDING);
+        let moves: Vec<_> = drained.iter().filter(|c| c.is_move()).collect();
+        assert!(moves.len() < MAX_PENDING);
+        assert_eq!(*moves.last().unwrap(), &mouse(MouseEventType::Move, 0, MAX_PENDING as i32 - 1));
+        assert!(queue.dropped() > 0);
+    }
+
+    #[test]
+    fn test_relative_moves_sum() {
+        let mut queue = InputQueue::new();
+        assert!(!queue.push(mouse(MouseEventType::MoveRelative, 0, 3)));
+        queue.push(mouse(MouseEventType::MoveRelative, 0, -1));
+        queue.push(mouse(MouseEventType::Move, 0, 10));
+        queue.push(mouse(MouseEventType::MoveRelative, 0, 2));
+        queue.push(mouse(MouseEventType::MoveRelative, 0, 2));
+
+        assert_eq!(
+            queue.drain(),
+            vec![
+                mouse(MouseEventType::MoveRelative, 0, 2),
+                mouse(MouseEventType::Move, 0, 10),
+                mouse(MouseEventType::MoveRelative, 0, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_touch_moves_batched() {
+        let mut queue = InputQueue::new();
+        assert!(queue.push(InputCommand::Touch(TouchEvent::Down { id: 0, x: 0, y: 0 })));
+        queue.drain();
+        assert!(!queue.push(InputCommand::Touch(TouchEvent::Move { id: 0, x: 1, y: 1 })));
+        assert!(!queue.push(InputCommand::Touch(TouchEvent::Move { id: 0, x: 2, y: 2 })));
+        assert!(queue.push(InputCommand::Touch(TouchEvent::Up { id: 0, x: 2, y: 2 })));
+        assert_eq!(queue.drain().len(), 3);
+    }
+
+    #[test]
+    fn test_rate() {
+        let mut queue = InputQueue::new();
+        assert_eq!(queue.flush_interval(), Some(Duration::from_micros(16_666)));
+
+        queue.set_rate(1000);
+        assert_eq!(queue.rate(), MAX_INPUT_RATE);
+
+        // 立即发送模式下移动也需要立即发送
+        queue.set_rate(0);
+        assert_eq!(queue.flush_interval(), None);
+        assert!(queue.push(mouse(MouseEventType::Move, 0, 1)));
+    }
+}
@@ -0,0 +1,662 @@
+/**
+ * 键盘映射模块
+ *
+ * 将 HarmonyOS KeyCode 转换为 RustDesk 键盘事件：
+ * - HarmonyOS KeyCode → ControlKey / 字符 / 物理扫描码 映射表
+ * - Shift / Ctrl / Alt / Meta 修饰键状态跟踪
+ * - 支持 RustDesk 的 map / translate / legacy 三种键盘模式
+ */
+
+use crate::protocol::{ControlKey, KeyEvent, KeyValue, KeyboardMode};
+
+/// 单个按键的映射
+#[derive(Debug, Clone, Copy)]
+pub struct KeyMapping {
+    /// HarmonyOS KeyCode
+    pub harmony: u32,
+    /// 对应的 RustDesk 控制键（可打印字符键为 None）
+    pub control: Option<ControlKey>,
+    /// 可打印字符（未按 Shift，按下 Shift）
+    pub chr: Option<(char, char)>,
+    /// 物理按键扫描码（Linux evdev，映射模式使用）
+    pub scancode: u32,
+}
+
+const fn ctrl(harmony: u32, key: ControlKey, scancode: u32) -> KeyMapping {
+    KeyMapping { harmony, control: Some(key), chr: None, scancode }
+}
+
+const fn chr(harmony: u32, lower: char, upper: char, scancode: u32) -> KeyMapping {
+    KeyMapping { harmony, control: None, chr: Some((lower, upper)), scancode }
+}
+
+/// HarmonyOS KeyCode 映射表（@ohos.multimodalInput.keyCode）
+static KEY_MAP: &[KeyMapping] = &[
+    // 数字键
+    chr(2000, '0', ')', 11),
+    chr(2001, '1', '!', 2),
+    chr(2002, '2', '@', 3),
+    chr(2003, '3', '#', 4),
+    chr(2004, '4', '$', 5),
+    chr(2005, '5', '%', 6),
+    chr(2006, '6', '^', 7),
+    chr(2007, '7', '&', 8),
+    chr(2008, '8', '*', 9),
+    chr(2009, '9', '(', 10),
+    // 方向键
+    ctrl(2012, ControlKey::UpArrow, 103),
+    ctrl(2013, ControlKey::DownArrow, 108),
+    ctrl(2014, ControlKey::LeftArrow, 105),
+    ctrl(2015, ControlKey::RightArrow, 106),
+    // 字母键
+    chr(2017, 'a', 'A', 30),
+    chr(2018, 'b', 'B', 48),
+    chr(2019, 'c', 'C', 46),
+    chr(2020, 'd', 'D', 32),
+    chr(2021, 'e', 'E', 18),
+    chr(2022, 'f', 'F', 33),
+    chr(2023, 'g', 'G', 34),
+    chr(2024, 'h', 'H', 35),
+    chr(2025, 'i', 'I', 23),
+    chr(2026, 'j', 'J', 36),
+    chr(2027, 'k', 'K', 37),
+    chr(2028, 'l', 'L', 38),
+    chr(2029, 'm', 'M', 50),
+    chr(2030, 'n', 'N', 49),
+    chr(2031, 'o', 'O', 24),
+    chr(2032, 'p', 'P', 25),
+    chr(2033, 'q', 'Q', 16),
+    chr(2034, 'r', 'R', 19),
+    chr(2035, 's', 'S', 31),
+    chr(2036, 't', 'T', 20),
+    chr(2037, 'u', 'U', 22),
+    chr(2038, 'v', 'V', 47),
+    chr(2039, 'w', 'W', 17),
+    chr(2040, 'x', 'X', 45),
+    chr(2041, 'y', 'Y', 21),
+    chr(2042, 'z', 'Z', 44),
+    // 符号键
+    chr(2043, ',', '<', 51),
+    chr(2044, '.', '>', 52),
+    // 修饰键
+    ctrl(2045, ControlKey::Alt, 56),
+    ctrl(2046, ControlKey::RAlt, 100),
+    ctrl(2047, ControlKey::Shift, 42),
+    ctrl(2048, ControlKey::RShift, 54),
+    ctrl(2049, ControlKey::Tab, 15),
+    ctrl(2050, ControlKey::Space, 57),
+    ctrl(2054, ControlKey::Return, 28),
+    ctrl(2055, ControlKey::Backspace, 14),
+    chr(2056, '`', '~', 41),
+    chr(2057, '-', '_', 12),
+    chr(2058, '=', '+', 13),
+    chr(2059, '[', '{', 26),
+    chr(2060, ']', '}', 27),
+    chr(2061, '\\', '|', 43),
+    chr(2062, ';', ':', 39),
+    chr(2063, '\'', '"', 40),
+    chr(2064, '/', '?', 53),
+    ctrl(2067, ControlKey::Menu, 127),
+    ctrl(2068, ControlKey::PageUp, 104),
+    ctrl(2069, ControlKey::PageDown, 109),
+    ctrl(2070, ControlKey::Escape, 1),
+    ctrl(2071, ControlKey::Delete, 111),
+    ctrl(2072, ControlKey::Control, 29),
+    ctrl(2073, ControlKey::RControl, 97),
+    ctrl(2074, ControlKey::CapsLock, 58),
+    ctrl(2075, ControlKey::Scroll, 70),
+    ctrl(2076, ControlKey::Meta, 125),
+    ctrl(2077, ControlKey::RWin, 126),
+    ctrl(2079, ControlKey::Snapshot, 99),
+    ctrl(2080, ControlKey::Pause, 119),
+    ctrl(2081, ControlKey::Home, 102),
+    ctrl(2082, ControlKey::End, 107),
+    ctrl(2083, ControlKey::Insert, 110),
+    // 功能键
+    ctrl(2090, ControlKey::F1, 59),
+    ctrl(2091, ControlKey::F2, 60),
+    ctrl(2092, ControlKey::F3, 61),
+    ctrl(2093, ControlKey::F4, 62),
+    ctrl(2094, ControlKey::F5, 63),
+    ctrl(2095, ControlKey::F6, 64),
+    ctrl(2096, ControlKey::F7, 65),
+    ctrl(2097, ControlKey::F8, 66),
+    ctrl(2098, ControlKey::F9, 67),
+    ctrl(2099, ControlKey::F10, 68),
+    ctrl(2100, ControlKey::F11, 87),
+    ctrl(2101, ControlKey::F12, 88),
+    // 小键盘
+    ctrl(2102, ControlKey::NumLock, 69),
+    ctrl(2103, ControlKey::Numpad0, 82),
+    ctrl(2104, ControlKey::Numpad1, 79),
+    ctrl(2105, ControlKey::Numpad2, 80),
+    ctrl(2106, ControlKey::Numpad3, 81),
+    ctrl(2107, ControlKey::Numpad4, 75),
+    ctrl(2108, ControlKey::Numpad5, 76),
+    ctrl(2109, ControlKey::Numpad6, 77),
+    ctrl(2110, ControlKey::Numpad7, 71),
+    ctrl(2111, ControlKey::Numpad8, 72),
+    ctrl(2112, ControlKey::Numpad9, 73),
+    ctrl(2113, ControlKey::Divide, 98),
+    ctrl(2114, ControlKey::Multiply, 55),
+    ctrl(2115, ControlKey::Subtract, 74),
+    ctrl(2116, ControlKey::Add, 78),
+    ctrl(2117, ControlKey::Decimal, 83),
+    ctrl(2119, ControlKey::NumpadEnter, 96),
+    ctrl(2120, ControlKey::Equals, 117),
+    // 多媒体键
+    ctrl(16, ControlKey::VolumeUp, 115),
+    ctrl(17, ControlKey::VolumeDown, 114),
+    ctrl(18, ControlKey::Power, 116),
+    ctrl(22, ControlKey::VolumeMute, 113),
+];
+
+/// 查找 HarmonyOS KeyCode 对应的映射
+pub fn lookup(key_code: u32) -> Option<&'static KeyMapping> {
+    KEY_MAP.iter().find(|mapping| mapping.harmony == key_code)
+}
+
+/// 修饰键状态（左右两侧分别记录，抬起一侧时另一侧仍然有效）
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift_l: bool,
+    pub shift_r: bool,
+    pub ctrl_l: bool,
+    pub ctrl_r: bool,
+    pub alt_l: bool,
+    pub alt_r: bool,
+    pub meta_l: bool,
+    pub meta_r: bool,
+    /// 大写锁定（切换状态）
+    pub caps_lock: bool,
+}
+
+impl Modifiers {
+    /// 更新修饰键按下状态，返回该键是否为修饰键
+    fn update(&mut self, key: ControlKey, down: bool) -> bool {
+        match key {
+            ControlKey::Shift => self.shift_l = down,
+            ControlKey::RShift => self.shift_r = down,
+            ControlKey::Control => self.ctrl_l = down,
+            ControlKey::RControl => self.ctrl_r = down,
+            ControlKey::Alt => self.alt_l = down,
+            ControlKey::RAlt => self.alt_r = down,
+            ControlKey::Meta => self.meta_l = down,
+            ControlKey::RWin => self.meta_r = down,
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn shift(self) -> bool {
+        self.shift_l || self.shift_r
+    }
+
+    pub fn ctrl(self) -> bool {
+        self.ctrl_l || self.ctrl_r
+    }
+
+    pub fn alt(self) -> bool {
+        self.alt_l || self.alt_r
+    }
+
+    pub fn meta(self) -> bool {
+        self.meta_l || self.meta_r
+    }
+
+    /// 当前按住的修饰键列表（随每个 KeyEvent 发送）
+    pub fn to_control_keys(self) -> Vec<ControlKey> {
+        let mut keys = Vec::new();
+        if self.shift() {
+            keys.push(ControlKey::Shift);
+        }
+        if self.ctrl() {
+            keys.push(ControlKey::Control);
+        }
+        if self.alt() {
+            keys.push(ControlKey::Alt);
+        }
+        if self.meta() {
+            keys.push(ControlKey::Meta);
+        }
+        if self.caps_lock {
+            keys.push(ControlKey::CapsLock);
+        }
+        keys
+    }
+
+    /// 是否有会改变按键语义的修饰键（快捷键组合）
+    fn has_shortcut_modifier(self) -> bool {
+        self.ctrl() || self.alt() || self.meta()
+    }
+}
+
+/// 键盘事件转换器（每个会话一个，保存修饰键和按住的按键）
+#[derive(Debug)]
+pub struct KeyboardTranslator {
+    mode: KeyboardMode,
+    modifiers: Modifiers,
+    /// 已发送按下、尚未抬起的按键（HarmonyOS KeyCode, 按下事件）
+    pressed: Vec<(u32, KeyEvent)>,
+}
+
+impl Default for KeyboardTranslator {
+    fn default() -> Self {
+        Self {
+            mode: KeyboardMode::Map,
+            modifiers: Modifiers::default(),
+            pressed: Vec::new(),
+        }
+    }
+}
+
+impl KeyboardTranslator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> KeyboardMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: KeyboardMode) {
+        self.mode = mode;
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// 转换一个 HarmonyOS 按键事件，未知按键或无需发送时返回 None
+    pub fn translate(&mut self, key_code: u32, down: bool) -> Option<KeyEvent> {
+        let mapping = match lookup(key_code) {
+            Some(mapping) => mapping,
+            None => {
+                log::debug!("Unmapped HarmonyOS key code: {}", key_code);
+                return None;
+            }
+        };
+
+        if let Some(key) = mapping.control {
+            if key == ControlKey::CapsLock && down {
+                self.modifiers.caps_lock = !self.modifiers.caps_lock;
+            }
+            self.modifiers.update(key, down);
+        }
+
+        let modifiers = self.modifiers.to_control_keys();
+        let value = match self.mode {
+            KeyboardMode::Map => KeyValue::Chr(mapping.scancode),
+            KeyboardMode::Legacy => match (mapping.control, mapping.chr) {
+                (Some(key), _) => KeyValue::Control(key),
+                (None, Some((lower, _))) => KeyValue::Chr(lower as u32),
+                (None, None) => return None,
+            },
+            KeyboardMode::Translate => match (mapping.control, mapping.chr) {
+                (Some(key), _) => KeyValue::Control(key),
+                // 快捷键组合（如 Ctrl+C）需要按键本身而不是产生的字符
+                (None, Some((lower, _))) if self.modifiers.has_shortcut_modifier() => {
+                    KeyValue::Chr(lower as u32)
+                }
+                (None, Some(pair)) => {
+                    // 翻译模式只在按下时发送产生的字符
+                    if !down {
+                        return None;
+                    }
+                    return Some(KeyEvent {
+                        down: true,
+                        press: true,
+                        value: KeyValue::Unicode(self.produce_char(pair) as u32),
+                        modifiers,
+                        mode: self.mode,
+                    });
+                }
+                (None, None) => return None,
+            },
+        };
+
+        let event = KeyEvent {
+            down,
+            press: false,
+            value,
+            modifiers,
+            mode: self.mode,
+        };
+        self.pressed.retain(|(code, _)| *code != key_code);
+        if down {
+            self.pressed.push((key_code, event.clone()));
+        }
+        Some(event)
+    }
+
+    /// 按住的按键数量
+    pub fn pressed_count(&self) -> usize {
+        self.pressed.len()
+    }
+
+    /// 抬起所有按住的按键（后按下的先抬起），清除修饰键状态（保留大写锁定）
+    pub fn release_all(&mut self) -> Vec<KeyEvent> {
+        self.modifiers = Modifiers {
+            caps_lock: self.modifiers.caps_lock,
+            ..Modifiers::default()
+        };
+        let modifiers = self.modifiers.to_control_keys();
+        self.pressed
+            .drain(..)
+            .rev()
+            .map(|(_, event)| KeyEvent {
+                down: false,
+                modifiers: modifiers.clone(),
+                ..event
+            })
+            .collect()
+    }
+
+    /// 按当前 Shift / CapsLock 状态计算产生的字符
+    fn produce_char(&self, (lower, upper): (char, char)) -> char {
+        let shifted = if lower.is_ascii_alphabetic() {
+            self.modifiers.shift() != self.modifiers.caps_lock
+        } else {
+            self.modifiers.shift()
+        };
+        if shifted {
+            upper
+        } else {
+            lower
+        }
+    }
+}
+
+/// 单个字符序列事件最多包含的字符数（避免超出字符串长度字段）
+const MAX_SEQ_CHARS: usize = 1024;
+
+/// 将一段文本（软键盘、输入法上屏内容）转换为键盘事件
+///
+/// - 按 Unicode 码点处理，代理对组成的字符（如 emoji）作为一个字符发送
+/// - 单个字符使用 Unicode 事件，多个字符使用字符序列事件
+/// - 换行（\n、\r\n、\r）和制表符作为回车 / Tab 按键发送
+pub fn text_events(text: &str, mode: KeyboardMode) -> Vec<KeyEvent> {
+    let mut events = Vec::new();
+    let mut segment: Vec<char> = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\r' => {
+                // \r\n 只算一次换行
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                Some(ControlKey::Return)
+            }
+            '\n' => Some(ControlKey::Return),
+            '\t' => Some(ControlKey::Tab),
+            c if c.is_control() => continue,
+            _ => None,
+        };
+
+        match key {
+            Some(key) => {
+                flush_segment(&mut events, &mut segment, mode);
+                events.push(press_event(KeyValue::Control(key), mode));
+            }
+            None => {
+                segment.push(c);
+                if segment.len() >= MAX_SEQ_CHARS {
+                    flush_segment(&mut events, &mut segment, mode);
+                }
+            }
+        }
+    }
+    flush_segment(&mut events, &mut segment, mode);
+    events
+}
+
+fn flush_segment(events: &mut Vec<KeyEvent>, segment: &mut Vec<char>, mode: KeyboardMode) {
+    let value = match segment.as_slice() {
+        [] => return,
+        [c] => KeyValue::Unicode(*c as u32),
+        chars => KeyValue::Seq(chars.iter().collect()),
+    };
+    events.push(press_event(value, mode));
+    segment.clear();
+}
+
+fn press_event(value: KeyValue, mode: KeyboardMode) -> KeyEvent {
+    KeyEvent {
+        down: true,
+        press: true,
+        value,
+        modifiers: Vec::new(),
+        mode,
+    }
+}
+
+/// 特殊操作（会话菜单中的快捷操作）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialAction {
+    CtrlAltDel,
+    LockScreen,
+    /// Win+Tab（Windows）/ Cmd+Tab（macOS）
+    SwitchApp,
+    /// Alt+F4
+    CloseWindow,
+    PrintScreen,
+    /// Android 返回（移动端被控）
+    MobileBack,
+    /// Android 主页
+    MobileHome,
+    /// Android 最近任务
+    MobileRecents,
+}
+
+impl SpecialAction {
+    /// 从 ArkTS 传入的操作名解析
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "ctrl_alt_del" => Some(SpecialAction::CtrlAltDel),
+            "lock_screen" => Some(SpecialAction::LockScreen),
+            "switch_app" => Some(SpecialAction::SwitchApp),
+            "close_window" => Some(SpecialAction::CloseWindow),
+            "print_screen" => Some(SpecialAction::PrintScreen),
+            "back" => Some(SpecialAction::MobileBack),
+            "home" => Some(SpecialAction::MobileHome),
+            "recents" => Some(SpecialAction::MobileRecents),
+            _ => None,
+        }
+    }
+
+    /// 是否为 Android 导航操作（以鼠标按键发送）
+    pub fn is_mobile(self) -> bool {
+        matches!(
+            self,
+            SpecialAction::MobileBack | SpecialAction::MobileHome | SpecialAction::MobileRecents
+        )
+    }
+
+    /// 转换为键盘事件（platform 为被控端系统，Android 导航操作返回 None）
+    ///
+    /// 与 RustDesk 客户端一致：Windows 被控端使用专用的 CtrlAltDel 控制键，
+    /// 其他系统发送 Ctrl+Alt+Delete 组合键
+    pub fn key_event(self, platform: &str, mode: KeyboardMode) -> Option<KeyEvent> {
+        let (key, modifiers) = match self {
+            SpecialAction::CtrlAltDel if platform.eq_ignore_ascii_case("windows") => {
+                (ControlKey::CtrlAltDel, vec![])
+            }
+            SpecialAction::CtrlAltDel => {
+                (ControlKey::Delete, vec![ControlKey::Control, ControlKey::Alt])
+            }
+            SpecialAction::LockScreen => (ControlKey::LockScreen, vec![]),
+            SpecialAction::SwitchApp => (ControlKey::Tab, vec![ControlKey::Meta]),
+            SpecialAction::CloseWindow => (ControlKey::F4, vec![ControlKey::Alt]),
+            SpecialAction::PrintScreen => (ControlKey::Snapshot, vec![]),
+            SpecialAction::MobileBack
+            | SpecialAction::MobileHome
+            | SpecialAction::MobileRecents => return None,
+        };
+        Some(KeyEvent {
+            modifiers,
+            ..press_event(KeyValue::Control(key), mode)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: u32 = 2017;
+    const KEY_C: u32 = 2019;
+    const KEY_1: u32 = 2001;
+    const KEY_SHIFT_LEFT: u32 = 2047;
+    const KEY_SHIFT_RIGHT: u32 = 2048;
+    const KEY_CTRL_LEFT: u32 = 2072;
+    const KEY_CTRL_RIGHT: u32 = 2073;
+    const KEY_CAPS_LOCK: u32 = 2074;
+    const KEY_F5: u32 = 2094;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(lookup(KEY_F5).unwrap().control, Some(ControlKey::F5));
+        assert_eq!(lookup(KEY_A).unwrap().chr, Some(('a', 'A')));
+        assert!(lookup(9999).is_none());
+    }
+
+    #[test]
+    fn test_map_mode_sends_scancode_with_modifiers() {
+        let mut translator = KeyboardTranslator::new();
+        translator.set_mode(KeyboardMode::Map);
+
+        translator.translate(KEY_CTRL_LEFT, true).unwrap();
+        let event = translator.translate(KEY_C, true).unwrap();
+        assert_eq!(event.value, KeyValue::Chr(46));
+        assert_eq!(event.modifiers, vec![ControlKey::Control]);
+
+        translator.translate(KEY_CTRL_LEFT, false).unwrap();
+        let event = translator.translate(KEY_C, false).unwrap();
+        assert!(!event.down);
+        assert!(event.modifiers.is_empty());
+    }
+
+    #[test]
+    fn test_left_and_right_modifiers_tracked_separately() {
+        let mut translator = KeyboardTranslator::new();
+
+        // 按住左 Ctrl 时抬起右 Ctrl，Ctrl 仍然有效
+        translator.translate(KEY_CTRL_LEFT, true);
+        translator.translate(KEY_CTRL_RIGHT, true);
+        translator.translate(KEY_CTRL_RIGHT, false);
+        let event = translator.translate(KEY_C, true).unwrap();
+        assert_eq!(event.modifiers, vec![ControlKey::Control]);
+        translator.translate(KEY_C, false);
+        translator.translate(KEY_CTRL_LEFT, false);
+
+        translator.set_mode(KeyboardMode::Translate);
+        translator.translate(KEY_SHIFT_RIGHT, true);
+        translator.translate(KEY_SHIFT_LEFT, true);
+        translator.translate(KEY_SHIFT_RIGHT, false);
+        let event = translator.translate(KEY_1, true).unwrap();
+        assert_eq!(event.value, KeyValue::Unicode('!' as u32));
+        translator.translate(KEY_SHIFT_LEFT, false);
+        assert!(translator.modifiers().to_control_keys().is_empty());
+    }
+
+    #[test]
+    fn test_translate_mode_applies_shift_and_caps_lock() {
+        let mut translator = KeyboardTranslator::new();
+        translator.set_mode(KeyboardMode::Translate);
+
+        translator.translate(KEY_SHIFT_LEFT, true);
+        let event = translator.translate(KEY_1, true).unwrap();
+        assert_eq!(event.value, KeyValue::Unicode('!' as u32));
+        // 抬起不再发送字符
+        assert!(translator.translate(KEY_1, false).is_none());
+        translator.translate(KEY_SHIFT_LEFT, false);
+
+        translator.translate(KEY_CAPS_LOCK, true);
+        translator.translate(KEY_CAPS_LOCK, false);
+        let event = translator.translate(KEY_A, true).unwrap();
+        assert_eq!(event.value, KeyValue::Unicode('A' as u32));
+
+        // 快捷键发送按键本身
+        translator.translate(KEY_CTRL_LEFT, true);
+        let event = translator.translate(KEY_C, true).unwrap();
+        assert_eq!(event.value, KeyValue::Chr('c' as u32));
+    }
+
+    #[test]
+    fn test_legacy_mode() {
+        let mut translator = KeyboardTranslator::new();
+        translator.set_mode(KeyboardMode::Legacy);
+
+        let event = translator.translate(KEY_F5, true).unwrap();
+        assert_eq!(event.value, KeyValue::Control(ControlKey::F5));
+        let event = translator.translate(KEY_A, true).unwrap();
+        assert_eq!(event.value, KeyValue::Chr('a' as u32));
+        assert!(translator.translate(9999, true).is_none());
+    }
+
+    #[test]
+    fn test_text_events() {
+        let events = text_events("你好\r\n😀", KeyboardMode::Translate);
+        let values: Vec<_> = events.iter().map(|e| e.value.clone()).collect();
+        assert_eq!(
+            values,
+            vec![
+                KeyValue::Seq("你好".to_string()),
+                KeyValue::Control(ControlKey::Return),
+                // 代理对作为一个码点发送
+                KeyValue::Unicode(0x1F600),
+            ]
+        );
+        assert!(events.iter().all(|e| e.press && e.modifiers.is_empty()));
+
+        let values: Vec<_> = text_events("a\n\nb", KeyboardMode::Map)
+            .into_iter()
+            .map(|e| e.value)
+            .collect();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[1], KeyValue::Control(ControlKey::Return));
+        assert_eq!(values[2], KeyValue::Control(ControlKey::Return));
+
+        let long = "字".repeat(MAX_SEQ_CHARS + 1);
+        assert_eq!(text_events(&long, KeyboardMode::Translate).len(), 2);
+    }
+
+    #[test]
+    fn test_release_all() {
+        let mut translator = KeyboardTranslator::new();
+        translator.translate(KEY_CTRL_LEFT, true);
+        translator.translate(KEY_C, true);
+        translator.translate(KEY_A, true);
+        translator.translate(KEY_A, false);
+        assert_eq!(translator.pressed_count(), 2);
+
+        let released = translator.release_all();
+        let values: Vec<_> = released.iter().map(|event| event.value.clone()).collect();
+        assert_eq!(values, vec![KeyValue::Chr(46), KeyValue::Chr(29)]);
+        assert!(released.iter().all(|event| !event.down && event.modifiers.is_empty()));
+        assert_eq!(translator.modifiers(), Modifiers::default());
+        assert!(translator.release_all().is_empty());
+    }
+
+    #[test]
+    fn test_special_actions() {
+        let event = SpecialAction::CtrlAltDel.key_event("Windows", KeyboardMode::Legacy).unwrap();
+        assert_eq!(event.value, KeyValue::Control(ControlKey::CtrlAltDel));
+        assert!(event.press && event.modifiers.is_empty());
+
+        let event = SpecialAction::CtrlAltDel.key_event("Linux", KeyboardMode::Legacy).unwrap();
+        assert_eq!(event.value, KeyValue::Control(ControlKey::Delete));
+        assert_eq!(event.modifiers, vec![ControlKey::Control, ControlKey::Alt]);
+
+        let event = SpecialAction::parse("SWITCH_APP").unwrap().key_event("Mac OS", KeyboardMode::Map).unwrap();
+        assert_eq!(event.value, KeyValue::Control(ControlKey::Tab));
+        assert_eq!(event.modifiers, vec![ControlKey::Meta]);
+
+        let home = SpecialAction::parse("home").unwrap();
+        assert!(home.is_mobile());
+        assert_eq!(home.key_event("Android", KeyboardMode::Map), None);
+        assert_eq!(SpecialAction::parse("reboot"), None);
+    }
+}
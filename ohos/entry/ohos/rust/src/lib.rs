@@ -6,8 +6,9 @@
 #[macro_use]
 extern crate napi_derive_ohos;
 
-use napi_ohos::{CallContext, Env, Error, JsObject, Result};
+use napi_ohos::{CallContext, Env, Error, JsArrayBuffer, JsNumber, JsObject, Result};
 use napi_ohos::bindgen_prelude::{Null, Object, ToNapiValue, Unknown};
+use once_cell::sync::OnceCell;
 use std::sync::{Arc, Mutex};
 use std::panic;
 
@@ -16,18 +17,68 @@ mod core;
 mod protocol;
 mod video;
 mod log_collector;
-
+mod screenshot;
+mod cursor;
+mod keyboard;
+mod mouse;
+mod gesture;
+mod viewport;
+mod input_queue;
+mod touch;
+mod macros;
+mod clipboard;
+mod file_transfer;
+mod file_manager;
+mod audio;
+mod chat;
+
+use audio::VoiceCallState;
+use chat::ChatMessage;
 use core::{CoreManager, ServerConfig};
-use video::{DecodedFrame, PixelFormat};
+use file_manager::{DirListing, FileOperation};
+use file_transfer::TransferEvent;
+use protocol::{ClipboardData, ClipboardFormat, ClipboardItem, DisplayInfo, FileEntry, FileType, KeyboardMode};
+use gesture::{TouchAction, TouchMode, TouchPoint};
+use viewport::{FitMode, ViewportState};
+use keyboard::SpecialAction;
 use log_collector::get_log_collector;
+use screenshot::{ScreenshotError, ScreenshotFormat};
 
 // 全局核心管理器
 static CORE_MANAGER: Mutex<Option<Arc<CoreManager>>> = Mutex::new(None);
 
+// 后台运行时（接收/解码等常驻任务和耗时操作在此执行，必须在调用之间保持存活）
+static BACKGROUND_RUNTIME: OnceCell<tokio::runtime::Runtime> = OnceCell::new();
+
+// 获取后台运行时（首次调用时创建）
+fn background_runtime() -> Result<&'static tokio::runtime::Runtime> {
+    BACKGROUND_RUNTIME.get_or_try_init(tokio::runtime::Runtime::new)
+        .map_err(|e| {
+            log_error!("Failed to create runtime: {}", e);
+            Error::from_reason("Failed to create runtime")
+        })
+}
+
+// 获取已初始化的核心管理器
+fn get_manager() -> Result<Arc<CoreManager>> {
+    let manager = CORE_MANAGER.lock()
+        .map_err(|e| {
+            log_error!("Lock error: {}", e);
+            Error::from_reason("Failed to acquire lock")
+        })?;
+
+    manager.as_ref()
+        .cloned()
+        .ok_or_else(|| {
+            log_error!("Module not initialized");
+            Error::from_reason("Module not initialized. Call init() first.")
+        })
+}
+
 // 设置 Panic Hook
 fn init_panic_hook() {
     let previous_hook = panic::take_hook();
-    panic::set_hook(Box::move |panic_info| {
+    panic::set_hook(Box::new(move |panic_info| {
         let message = if let Some(s) = panic_info.payload().downcast_ref::<&str>() {
             format!("Panic: {}", s)
         } else if let Some(s) = panic_info.payload().downcast_ref::<String>() {
@@ -51,7 +102,7 @@ fn init_panic_hook() {
 
         // 调用之前的 hook
         previous_hook(panic_info);
-    });
+    }));
 }
 
 // 初始化模块
@@ -148,11 +199,7 @@ fn set_server_config(ctx: CallContext) -> Result<u32> {
         key: if key.is_empty() { None } else { Some(key) },
     };
 
-    let rt = tokio::runtime::Runtime::new()
-        .map_err(|e| {
-            log_error!("Failed to create runtime: {}", e);
-            Error::from_reason("Failed to create runtime")
-        })?;
+    let rt = background_runtime()?;
 
     let manager = manager.clone();
     rt.block_on(async move {
@@ -187,11 +234,7 @@ fn connect(ctx: CallContext) -> Result<u32> {
             Error::from_reason("Module not initialized. Call init() first.")
         })?;
 
-    let rt = tokio::runtime::Runtime::new()
-        .map_err(|e| {
-            log_error!("Failed to create runtime: {}", e);
-            Error::from_reason("Failed to create runtime")
-        })?;
+    let rt = background_runtime()?;
 
     let manager = manager.clone();
     let desk_id_clone = desk_id.clone();
@@ -229,11 +272,7 @@ fn disconnect(_ctx: CallContext) -> Result<()> {
         })?;
 
     if let Some(manager) = manager.as_ref() {
-        let rt = tokio::runtime::Runtime::new()
-            .map_err(|e| {
-                log_error!("Failed to create runtime: {}", e);
-                Error::from_reason("Failed to create runtime")
-            })?;
+        let rt = background_runtime()?;
 
         let manager = manager.clone();
         let _ = rt.block_on(async move {
@@ -258,11 +297,7 @@ fn cleanup(_ctx: CallContext) -> Result<()> {
         })?;
 
     if let Some(manager) = manager.as_ref() {
-        let rt = tokio::runtime::Runtime::new()
-            .map_err(|e| {
-                log_error!("Failed to create runtime: {}", e);
-                Error::from_reason("Failed to create runtime")
-            })?;
+        let rt = background_runtime()?;
 
         let manager = manager.clone();
         let _ = rt.block_on(async move {
@@ -286,11 +321,7 @@ fn get_connection_status(_ctx: CallContext) -> Result<u32> {
         })?;
 
     if let Some(manager) = manager.as_ref() {
-        let rt = tokio::runtime::Runtime::new()
-            .map_err(|e| {
-                log_error!("Failed to create runtime: {}", e);
-                Error::from_reason("Failed to create runtime")
-            })?;
+        let rt = background_runtime()?;
 
         let manager = manager.clone();
         let connections = rt.block_on(async move {
@@ -305,205 +336,1627 @@ fn get_connection_status(_ctx: CallContext) -> Result<u32> {
     }
 }
 
-// 发送键盘事件
-#[js_function(2)]
-fn send_key_event(ctx: CallContext) -> Result<()> {
-    let key_code: u32 = ctx.get(0)?;
-    let pressed: bool = ctx.get(1)?;
+// 发送键盘事件（返回 0 成功，1 失败）
+#[js_function(3)]
+fn send_key_event(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let key_code: u32 = ctx.get(1)?;
+    let pressed: bool = ctx.get(2)?;
 
     log_debug!("Sending key event: key={}, pressed={}", key_code, pressed);
 
-    let manager = CORE_MANAGER.lock()
-        .map_err(|e| {
-            log_error!("Lock error: {}", e);
-            Error::from_reason("Failed to acquire lock")
-        })?;
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
 
-    if let Some(manager) = manager.as_ref() {
-        let rt = tokio::runtime::Runtime::new()
-            .map_err(|e| {
-                log_error!("Failed to create runtime: {}", e);
-                Error::from_reason("Failed to create runtime")
-            })?;
+    let result = rt.block_on(async move {
+        manager.send_key(&session, key_code, pressed).await
+    });
 
-        let connections = rt.block_on(async move {
-            manager.get_connections().await
-        });
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to send key event: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 发送文本输入（软键盘 / 输入法，返回 0 成功，1 失败）
+#[js_function(2)]
+fn send_text(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let text: String = ctx.get(1)?;
 
-        if let Some(first_conn) = connections.first() {
-            let desk_id = &first_conn.id;
-            let result = rt.block_on(async move {
-                manager.send_key(desk_id, key_code, pressed).await
-            });
+    log_debug!("Sending text: {} chars", text.chars().count());
 
-            if let Err(e) = result {
-                log_error!("Failed to send key event: {}", e);
-            }
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.send_text(&session, &text).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to send text: {}", e);
+            Ok(1)
         }
     }
-
-    Ok(())
 }
 
-// 发送鼠标移动
+// 设置键盘模式（"map" / "translate" / "legacy"，返回 0 成功，1 失败）
 #[js_function(2)]
-fn send_mouse_move(ctx: CallContext) -> Result<()> {
-    let x: i32 = ctx.get(0)?;
-    let y: i32 = ctx.get(1)?;
+fn set_keyboard_mode(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let mode: String = ctx.get(1)?;
+
+    let mode = match KeyboardMode::parse(&mode) {
+        Some(mode) => mode,
+        None => {
+            log_error!("Unknown keyboard mode: {}", mode);
+            return Ok(1);
+        }
+    };
 
-    log_debug!("Sending mouse move: x={}, y={}", x, y);
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
 
-    let manager = CORE_MANAGER.lock()
-        .map_err(|e| {
-            log_error!("Lock error: {}", e);
-            Error::from_reason("Failed to acquire lock")
-        })?;
+    let result = rt.block_on(async move {
+        manager.set_keyboard_mode(&session, mode).await
+    });
 
-    if let Some(manager) = manager.as_ref() {
-        let rt = tokio::runtime::Runtime::new()
-            .map_err(|e| {
-                log_error!("Failed to create runtime: {}", e);
-                Error::from_reason("Failed to create runtime")
-            })?;
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to set keyboard mode: {}", e);
+            Ok(1)
+        }
+    }
+}
 
-        let connections = rt.block_on(async move {
-            manager.get_connections().await
-        });
+// 发送鼠标移动（设置视口后为视图坐标，否则为远程坐标，返回 0 成功，1 失败）
+// 高频调用：只取一次管理器，移动事件进入输入队列合并发送
+#[js_function(3)]
+fn send_mouse_move(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let x: i32 = ctx.get(1)?;
+    let y: i32 = ctx.get(2)?;
 
-        if let Some(first_conn) = connections.first() {
-            let desk_id = &first_conn.id;
-            let result = rt.block_on(async move {
-                manager.send_mouse_move(desk_id, x, y).await
-            });
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
 
-            if let Err(e) = result {
-                log_error!("Failed to send mouse move: {}", e);
-            }
+    let result = rt.block_on(async move {
+        manager.send_mouse_move(&session, x, y).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to send mouse move: {}", e);
+            Ok(1)
         }
     }
+}
 
-    Ok(())
+// 发送鼠标点击（返回 0 成功，1 失败）
+#[js_function(3)]
+fn send_mouse_click(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let button: u32 = ctx.get(1)?;
+    let pressed: bool = ctx.get(2)?;
+
+    log_debug!("Sending mouse click: button={}, pressed={}", button, pressed);
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.send_mouse_click(&session, button, pressed).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to send mouse click: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 发送鼠标滚轮（dx 水平，dy 垂直，返回 0 成功，1 失败）
+#[js_function(3)]
+fn send_mouse_wheel(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let dx: i32 = ctx.get(1)?;
+    let dy: i32 = ctx.get(2)?;
+
+    log_debug!("Sending mouse wheel: dx={}, dy={}", dx, dy);
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.send_mouse_wheel(&session, dx, dy).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to send mouse wheel: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 发送相对鼠标移动（远程像素位移，需先开启相对鼠标模式，返回 0 成功，1 失败）
+#[js_function(3)]
+fn send_mouse_relative(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let dx: i32 = ctx.get(1)?;
+    let dy: i32 = ctx.get(2)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.send_mouse_relative(&session, dx, dy).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to send relative mouse move: {}", e);
+            Ok(1)
+        }
+    }
 }
 
-// 发送鼠标点击
+// 开启 / 关闭相对鼠标模式（开启后触控板模式的手指移动以位移发送，返回 0 成功，1 失败）
 #[js_function(2)]
-fn send_mouse_click(ctx: CallContext) -> Result<()> {
-    let button: u32 = ctx.get(0)?;
-    let pressed: bool = ctx.get(1)?;
+fn set_relative_mouse(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let enabled: bool = ctx.get(1)?;
 
-    log_debug!("Sending mouse click: button={}, pressed={}", button, pressed);
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
 
-    let manager = CORE_MANAGER.lock()
-        .map_err(|e| {
-            log_error!("Lock error: {}", e);
-            Error::from_reason("Failed to acquire lock")
-        })?;
+    let result = rt.block_on(async move {
+        manager.set_relative_mouse(&session, enabled).await
+    });
 
-    if let Some(manager) = manager.as_ref() {
-        let rt = tokio::runtime::Runtime::new()
-            .map_err(|e| {
-                log_error!("Failed to create runtime: {}", e);
-                Error::from_reason("Failed to create runtime")
-            })?;
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to set relative mouse mode: {}", e);
+            Ok(1)
+        }
+    }
+}
 
-        let connections = rt.block_on(async move {
-            manager.get_connections().await
+// 发送触摸事件（pointers: [{ id, x, y }] 为本次变化的手指，视图坐标，action: down / move / up / cancel）
+// 被控端支持触摸时直接转发触摸，否则识别为鼠标手势；返回当前视口状态，失败时返回 null
+#[js_function(3)]
+fn send_touch(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+    let pointers: JsObject = ctx.get(1)?;
+    let action: String = ctx.get(2)?;
+
+    let action = match TouchAction::parse(&action) {
+        Some(action) => action,
+        None => {
+            log_error!("Unknown touch action: {}", action);
+            return Null.into_unknown(&*ctx.env);
+        }
+    };
+
+    let mut points = Vec::new();
+    for i in 0..pointers.get_array_length()? {
+        let pointer: JsObject = pointers.get_element(i)?;
+        points.push(TouchPoint {
+            id: pointer.get_named_property::<JsNumber>("id")?.get_int32()?,
+            x: pointer.get_named_property::<JsNumber>("x")?.get_double()? as f32,
+            y: pointer.get_named_property::<JsNumber>("y")?.get_double()? as f32,
         });
+    }
 
-        if let Some(first_conn) = connections.first() {
-            let desk_id = &first_conn.id;
-            let result = rt.block_on(async move {
-                manager.send_mouse_click(desk_id, button, pressed).await
-            });
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
 
-            if let Err(e) = result {
-                log_error!("Failed to send mouse click: {}", e);
-            }
+    let result = rt.block_on(async move {
+        manager.send_touch(&session, &points, action).await
+    });
+
+    match result {
+        Ok(state) => Ok(create_viewport_object(&ctx.env, &state)?.into_unknown()),
+        Err(e) => {
+            log_error!("Failed to send touch: {}", e);
+            Null.into_unknown(&*ctx.env)
         }
     }
+}
 
-    Ok(())
+// 发送特殊操作（返回 0 成功，1 失败）
+// action："ctrl_alt_del" / "lock_screen" / "switch_app"（Win/Cmd+Tab）/ "close_window"（Alt+F4）/
+//         "print_screen" / "back" / "home" / "recents"（后三项仅 Android 被控端）
+#[js_function(2)]
+fn send_special_action(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let action: String = ctx.get(1)?;
+
+    let action = match SpecialAction::parse(&action) {
+        Some(action) => action,
+        None => {
+            log_error!("Unknown special action: {}", action);
+            return Ok(1);
+        }
+    };
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.send_special_action(&session, action).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to send special action: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 设置触摸模式（"touchpad" / "direct"，返回 0 成功，1 失败）
+#[js_function(2)]
+fn set_touch_mode(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let mode: String = ctx.get(1)?;
+
+    let mode = match TouchMode::parse(&mode) {
+        Some(mode) => mode,
+        None => {
+            log_error!("Unknown touch mode: {}", mode);
+            return Ok(1);
+        }
+    };
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_touch_mode(&session, mode).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to set touch mode: {}", e);
+            Ok(1)
+        }
+    }
 }
 
 // 获取视频帧数据（返回 RGBA 格式的像素数据）
-#[js_function(0)]
+// 参数：session，可选 displayIndex（未指定时取当前显示器）
+#[js_function(2)]
 fn get_video_frame(ctx: CallContext) -> Result<Unknown> {
-    let manager = CORE_MANAGER.lock()
-        .map_err(|e| {
-            log_error!("Lock error: {}", e);
-            Error::from_reason("Failed to acquire lock")
-        })?;
+    let session: String = ctx.get(0)?;
+    let display: Option<usize> = if ctx.length > 1 {
+        let index: u32 = ctx.get(1)?;
+        Some(index as usize)
+    } else {
+        None
+    };
 
-    if let Some(manager) = manager.as_ref() {
-        let rt = tokio::runtime::Runtime::new()
-            .map_err(|e| {
-                log_error!("Failed to create runtime: {}", e);
-                Error::from_reason("Failed to create runtime")
-            })?;
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
 
-        let connections = rt.block_on(async move {
-            manager.get_connections().await
-        });
+    let rendered = rt.block_on(async move {
+        let frame = manager.get_latest_frame(&session, display).await?;
+        let mut data = match frame.to_rgba() {
+            Ok(data) => data,
+            Err(e) => return Some(Err(e)),
+        };
+        // 被控端未把光标画进视频时，按会话设置合成远程光标
+        manager.composite_cursor(&session, display, &mut data, frame.width, frame.height).await;
+        Some(Ok((frame, data)))
+    });
+
+    let (frame, data) = match rendered {
+        Some(Ok(rendered)) => rendered,
+        Some(Err(e)) => {
+            log_error!("Failed to convert frame: {}", e);
+            return Err(Error::from_reason("Failed to convert frame"));
+        }
+        None => return Null.into_unknown(&*ctx.env),
+    };
 
-        if connections.first().is_some() {
-            // TODO: 从实际连接中获取最新视频帧
-            // 当前返回模拟帧数据用于测试
-            let frame = create_test_frame(1920, 1080);
-            let data = frame.data;
+    let mut array_buffer = ctx.env.create_arraybuffer(data.len())?;
+    array_buffer.as_mut().copy_from_slice(&data);
+    let array_buffer = array_buffer.into_raw();
 
-            let mut array_buffer = ctx.env.create_arraybuffer(data.len())?;
-            array_buffer.as_mut().copy_from_slice(&data);
-            let array_buffer = array_buffer.into_raw();
+    let mut obj = ctx.env.create_object()?;
+    obj.set_named_property("width", frame.width)?;
+    obj.set_named_property("height", frame.height)?;
+    obj.set_named_property("data", array_buffer)?;
+    obj.set_named_property("timestamp", frame.timestamp)?;
 
-            let mut obj = ctx.env.create_object()?;
-            obj.set_named_property("width", frame.width)?;
-            obj.set_named_property("height", frame.height)?;
-            obj.set_named_property("data", array_buffer)?;
-            obj.set_named_property("timestamp", frame.timestamp)?;
+    Ok(obj.into_unknown())
+}
 
-            return Ok(obj.into_unknown());
-        }
+// 获取远程光标（返回 { id, hotX, hotY, width, height, data, x, y }，
+// 尚未收到光标图像时只返回位置，会话不存在时返回 null）
+#[js_function(1)]
+fn get_cursor(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let cursor = rt.block_on(async move {
+        manager.get_cursor(&session).await
+    });
+
+    let (image, (x, y)) = match cursor {
+        Some(cursor) => cursor,
+        None => return Null.into_unknown(&*ctx.env),
+    };
+
+    let mut obj = ctx.env.create_object()?;
+    obj.set_named_property("x", x)?;
+    obj.set_named_property("y", y)?;
+
+    if let Some(image) = image {
+        let mut array_buffer = ctx.env.create_arraybuffer(image.colors.len())?;
+        array_buffer.as_mut().copy_from_slice(&image.colors);
+        let array_buffer = array_buffer.into_raw();
+
+        obj.set_named_property("id", image.id as f64)?;
+        obj.set_named_property("hotX", image.hot_x)?;
+        obj.set_named_property("hotY", image.hot_y)?;
+        obj.set_named_property("width", image.width)?;
+        obj.set_named_property("height", image.height)?;
+        obj.set_named_property("data", array_buffer)?;
     }
 
-    Null.into_unknown(&*ctx.env)
+    Ok(obj.into_unknown())
+}
+
+// 设置是否把远程光标合成到 getVideoFrame 输出帧
+#[js_function(2)]
+fn set_cursor_composite(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let enabled: bool = ctx.get(1)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_cursor_composite(&session, enabled).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to set cursor composite: {}", e);
+            Ok(1)
+        }
+    }
 }
 
-// 创建测试帧（用于开发调试）
-fn create_test_frame(width: u32, height: u32) -> DecodedFrame {
-    let mut frame = DecodedFrame::new(width, height, PixelFormat::RGBA);
+// 切换远程显示器（返回 0 成功，1 失败）
+#[js_function(2)]
+fn switch_display(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let index: u32 = ctx.get(1)?;
+
+    log_info!("Switching display: session={}, index={}", session, index);
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.switch_display(&session, index as usize).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            let collector = get_log_collector();
+            let mut guard = collector.lock().unwrap_or_else(|e| e.into_inner());
+            guard.set_error(format!("Switch display failed: {}", e));
+            Ok(1)
+        }
+    }
+}
 
-    // 生成渐变测试图案
-    for y in 0..height {
-        for x in 0..width {
-            let idx = ((y * width + x) * 4) as usize;
+// 同时查看多个远程显示器（返回 0 成功，1 失败）
+#[js_function(2)]
+fn capture_displays(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let indices: JsObject = ctx.get(1)?;
+
+    let mut displays = Vec::new();
+    for i in 0..indices.get_array_length()? {
+        let index: u32 = indices.get_element::<JsNumber>(i)?.get_uint32()?;
+        displays.push(index as usize);
+    }
 
-            // 创建渐变
-            let r = (x * 255 / width) as u8;
-            let g = (y * 255 / height) as u8;
-            let b = 128;
+    log_info!("Capturing displays: session={}, displays={:?}", session, displays);
 
-            // 添加棋盘格效果
-            let block_size = 64;
-            let is_dark = ((x / block_size) + (y / block_size)) % 2 == 0;
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
 
-            let multiplier = if is_dark { 0.7 } else { 1.0 };
+    let result = rt.block_on(async move {
+        manager.capture_displays(&session, &displays).await
+    });
 
-            frame.data[idx] = (r as f32 * multiplier) as u8;
-            frame.data[idx + 1] = (g as f32 * multiplier) as u8;
-            frame.data[idx + 2] = (b as f32 * multiplier) as u8;
-            frame.data[idx + 3] = 255; // Alpha
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            let collector = get_log_collector();
+            let mut guard = collector.lock().unwrap_or_else(|e| e.into_inner());
+            guard.set_error(format!("Capture displays failed: {}", e));
+            Ok(1)
         }
     }
+}
+
+// 获取远程显示器列表（返回 { current, displays: [...] }，会话不存在时返回 null）
+#[js_function(1)]
+fn get_displays(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let state = rt.block_on(async move {
+        manager.get_displays(&session).await
+    });
 
-    // 在中心添加时间戳区域
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+    let (displays, current) = match state {
+        Some(state) => state,
+        None => return Null.into_unknown(&*ctx.env),
+    };
+
+    let mut result = ctx.env.create_object()?;
+    result.set_named_property("current", current as u32)?;
+    result.set_named_property("displays", create_display_array(&ctx.env, &displays)?)?;
+    Ok(result.into_unknown())
+}
+
+// 获取会话详细信息（对端设备信息和显示器列表，会话不存在时返回 null）
+#[js_function(1)]
+fn get_session_info(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
 
-    frame.timestamp = timestamp;
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let info = rt.block_on(async move {
+        manager.get_session_info(&session).await
+    });
+
+    let info = match info {
+        Some(info) => info,
+        None => return Null.into_unknown(&*ctx.env),
+    };
 
-    frame
+    let mut features = ctx.env.create_array_with_length(info.features.len())?;
+    for (index, feature) in info.features.iter().enumerate() {
+        features.set_element(index as u32, ctx.env.create_string(feature)?)?;
+    }
+
+    let mut obj = ctx.env.create_object()?;
+    obj.set_named_property("id", info.id.as_str())?;
+    obj.set_named_property("connected", info.connected)?;
+    obj.set_named_property("screenWidth", info.screen_width)?;
+    obj.set_named_property("screenHeight", info.screen_height)?;
+    obj.set_named_property("username", info.username.as_str())?;
+    obj.set_named_property("hostname", info.hostname.as_str())?;
+    obj.set_named_property("platform", info.platform.as_str())?;
+    obj.set_named_property("version", info.version.as_str())?;
+    obj.set_named_property("supportsTouch", info.supports_touch)?;
+    obj.set_named_property("currentDisplay", info.current_display as u32)?;
+    obj.set_named_property("displays", create_display_array(&ctx.env, &info.displays)?)?;
+    obj.set_named_property("features", features)?;
+    Ok(obj.into_unknown())
+}
+
+// 设置剪贴板同步策略（对所有会话生效，返回 0 成功）
+#[js_function(1)]
+fn set_clipboard_sync(ctx: CallContext) -> Result<u32> {
+    let enabled: bool = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    rt.block_on(async move {
+        manager.set_clipboard_sync(enabled).await
+    });
+
+    Ok(0)
+}
+
+// 本地剪贴板变化时同步到对端（返回 0 已发送，1 失败，2 未发送：同步已关闭或内容刚从对端收到）
+#[js_function(2)]
+fn set_local_clipboard(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let text: String = ctx.get(1)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_local_clipboard(&session, ClipboardData::new(text)).await
+    });
+
+    match result {
+        Ok(true) => Ok(0),
+        Ok(false) => Ok(2),
+        Err(e) => {
+            log_error!("Failed to set local clipboard: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 本地剪贴板（多格式）变化时同步到对端，items 为 [{ mime, data }]，文本格式 data 为字符串，图片为 ArrayBuffer
+// （返回值同 setLocalClipboard，不支持的格式被忽略）
+#[js_function(2)]
+fn set_local_clipboard_data(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let records: JsObject = ctx.get(1)?;
+
+    let mut data = ClipboardData::default();
+    for i in 0..records.get_array_length()? {
+        let record: JsObject = records.get_element(i)?;
+        let mime: String = record.get_named_property("mime")?;
+        let format = match ClipboardFormat::from_mime(&mime) {
+            Some(format) if data.item(format).is_none() => format,
+            Some(_) => continue,
+            None => {
+                log_warn!("Unsupported clipboard format: {}", mime);
+                continue;
+            }
+        };
+        let content = if format.is_text() {
+            record.get_named_property::<String>("data")?.into_bytes()
+        } else {
+            record.get_named_property::<JsArrayBuffer>("data")?.into_value()?.to_vec()
+        };
+        data.items.push(ClipboardItem::new(format, content));
+    }
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_local_clipboard(&session, data).await
+    });
+
+    match result {
+        Ok(true) => Ok(0),
+        Ok(false) => Ok(2),
+        Err(e) => {
+            log_error!("Failed to set local clipboard: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 取出远程剪贴板变化事件（对端剪贴板有新内容时返回 MIME 类型列表，否则返回 null）
+#[js_function(1)]
+fn get_remote_clipboard_formats(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let formats = rt.block_on(async move {
+        manager.take_remote_clipboard_formats(&session).await
+    });
+
+    let formats = match formats {
+        Some(formats) => formats,
+        None => return Null.into_unknown(&*ctx.env),
+    };
+    let mut array = ctx.env.create_array_with_length(formats.len())?;
+    for (index, format) in formats.iter().enumerate() {
+        array.set_element(index as u32, ctx.env.create_string(format.mime())?)?;
+    }
+    Ok(array.into_unknown())
+}
+
+// 读取远程剪贴板的指定格式（文本格式返回字符串，图片返回 ArrayBuffer；
+// 较大的内容首次读取时向对端请求，到达前返回 null，需要稍后重试）
+#[js_function(2)]
+fn read_remote_clipboard(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+    let mime: String = ctx.get(1)?;
+
+    let format = match ClipboardFormat::from_mime(&mime) {
+        Some(format) => format,
+        None => {
+            log_error!("Unsupported clipboard format: {}", mime);
+            return Null.into_unknown(&*ctx.env);
+        }
+    };
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.read_remote_clipboard(&session, format).await
+    });
+
+    let content = match result {
+        Ok(Some(content)) => content,
+        Ok(None) => return Null.into_unknown(&*ctx.env),
+        Err(e) => {
+            log_error!("Failed to read remote clipboard: {}", e);
+            return Null.into_unknown(&*ctx.env);
+        }
+    };
+
+    if format.is_text() {
+        let text = String::from_utf8_lossy(&content).into_owned();
+        return ctx.env.create_string_from_std(text).map(|s| s.into_unknown());
+    }
+    let mut array_buffer = ctx.env.create_arraybuffer(content.len())?;
+    array_buffer.as_mut().copy_from_slice(&content);
+    Ok(array_buffer.into_raw().into_unknown())
+}
+
+// 本地复制文件时同步到对端（paths 为本地绝对路径，对端粘贴时通过文件传输连接请求；返回值同 setLocalClipboard）
+#[js_function(2)]
+fn set_local_clipboard_files(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let array: JsObject = ctx.get(1)?;
+
+    let mut paths = Vec::new();
+    for i in 0..array.get_array_length()? {
+        paths.push(array.get_element::<String>(i)?);
+    }
+    let data = ClipboardData { items: vec![ClipboardItem::file_list(&paths)] };
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_local_clipboard(&session, data).await
+    });
+
+    match result {
+        Ok(true) => Ok(0),
+        Ok(false) => Ok(2),
+        Err(e) => {
+            log_error!("Failed to set local clipboard files: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 读取远程剪贴板中的文件（对端的路径列表；列表尚未到达或没有文件时返回 null，需要稍后重试）
+#[js_function(1)]
+fn get_remote_clipboard_files(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.remote_clipboard_files(&session).await
+    });
+
+    let files = match result {
+        Ok(Some(files)) => files,
+        Ok(None) => return Null.into_unknown(&*ctx.env),
+        Err(e) => {
+            log_error!("Failed to read remote clipboard files: {}", e);
+            return Null.into_unknown(&*ctx.env);
+        }
+    };
+    let mut array = ctx.env.create_array_with_length(files.len())?;
+    for (index, file) in files.iter().enumerate() {
+        array.set_element(index as u32, ctx.env.create_string(file)?)?;
+    }
+    Ok(array.into_unknown())
+}
+
+// 粘贴远程剪贴板中的文件到本地目录（通过文件传输连接下载，返回传输任务 ID 数组，失败时为空数组）
+#[js_function(2)]
+fn paste_remote_clipboard_files(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+    let local_dir: String = ctx.get(1)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.paste_remote_clipboard_files(&session, &local_dir).await
+    });
+
+    let ids = result.unwrap_or_else(|e| {
+        log_error!("Failed to paste remote clipboard files: {}", e);
+        Vec::new()
+    });
+    let mut array = ctx.env.create_array_with_length(ids.len())?;
+    for (index, id) in ids.iter().enumerate() {
+        array.set_element(index as u32, ctx.env.create_int32(*id)?)?;
+    }
+    Ok(array.into_unknown())
+}
+
+// 获取远程音频格式（{ sampleRate, channels }，尚未收到音频时返回 null）
+#[js_function(1)]
+fn get_remote_audio_format(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let format = rt.block_on(async move {
+        manager.remote_audio_format(&session).await
+    });
+
+    let format = match format {
+        Some(format) => format,
+        None => return Null.into_unknown(&*ctx.env),
+    };
+    let mut obj = ctx.env.create_object()?;
+    obj.set_named_property("sampleRate", format.sample_rate)?;
+    obj.set_named_property("channels", format.channels as u32)?;
+    Ok(obj.into_unknown())
+}
+
+// 拉取远程音频 PCM（frames 帧，16 位小端交错的 ArrayBuffer，缓冲不足的部分为静音；
+// 尚未收到音频时返回 null）
+#[js_function(2)]
+fn read_remote_audio(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+    let frames: u32 = ctx.get(1)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    // 单次最多拉取 1 秒（48 kHz）
+    let frames = frames.min(48_000) as usize;
+    let pcm = rt.block_on(async move {
+        manager.read_remote_audio(&session, frames).await
+    });
+
+    let pcm = match pcm {
+        Some(pcm) => pcm,
+        None => return Null.into_unknown(&*ctx.env),
+    };
+    let mut array_buffer = ctx.env.create_arraybuffer(pcm.len() * 2)?;
+    for (bytes, sample) in array_buffer.as_mut().chunks_exact_mut(2).zip(&pcm) {
+        bytes.copy_from_slice(&sample.to_le_bytes());
+    }
+    Ok(array_buffer.into_raw().into_unknown())
+}
+
+// 会话静音 / 取消静音（同时通知对端停止 / 恢复发送音频，返回 0 成功，1 失败）
+#[js_function(2)]
+fn set_audio_muted(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let muted: bool = ctx.get(1)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_audio_muted(&session, muted).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to set audio muted: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 设置会话选项（image-quality / custom-fps / show-remote-cursor / disable-audio / disable-clipboard /
+// lock-after-session-end，未连接时在登录时发送，返回 0 成功，1 失败）
+#[js_function(3)]
+fn set_session_option(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let key: String = ctx.get(1)?;
+    let value: String = ctx.get(2)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_session_option(&session, &key, &value).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to set session option: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 发起语音通话，或接听对端的来电（返回 0 成功，1 失败）
+#[js_function(1)]
+fn start_voice_call(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.start_voice_call(&session).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to start voice call: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 挂断、取消发起或拒绝来电（返回 0 成功，1 失败）
+#[js_function(1)]
+fn end_voice_call(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.end_voice_call(&session).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to end voice call: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 获取语音通话状态（idle / calling / incoming / active，会话不存在时为 idle）
+#[js_function(1)]
+fn get_voice_call_state(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let state = rt.block_on(async move {
+        manager.voice_call_state(&session).await
+    });
+
+    let state = match state.unwrap_or(VoiceCallState::Idle) {
+        VoiceCallState::Idle => "idle",
+        VoiceCallState::Calling { .. } => "calling",
+        VoiceCallState::Incoming { .. } => "incoming",
+        VoiceCallState::Active { .. } => "active",
+    };
+    ctx.env.create_string(state).map(|s| s.into_unknown())
+}
+
+// 发送麦克风 PCM（16 kHz 单声道 16 位小端，只在通话中，返回 0 成功，1 失败）
+#[js_function(2)]
+fn push_microphone_pcm(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let data = ctx.get::<JsArrayBuffer>(1)?.into_value()?;
+    let pcm: Vec<i16> = data
+        .chunks_exact(2)
+        .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+        .collect();
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.push_microphone_pcm(&session, &pcm).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to push microphone audio: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 发送聊天消息（返回记入聊天记录的消息 { text, timestamp, incoming }，失败时返回 null）
+#[js_function(2)]
+fn send_chat_message(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+    let text: String = ctx.get(1)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.send_chat_message(&session, &text).await
+    });
+
+    match result {
+        Ok(message) => Ok(create_chat_message(&ctx.env, &message)?.into_unknown()),
+        Err(e) => {
+            log_error!("Failed to send chat message: {}", e);
+            Null.into_unknown(&*ctx.env)
+        }
+    }
+}
+
+// 获取会话的聊天记录（按时间顺序）
+#[js_function(1)]
+fn get_chat_history(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let messages = rt.block_on(async move {
+        manager.chat_history(&session).await
+    });
+
+    Ok(create_chat_messages(&ctx.env, &messages)?.into_unknown())
+}
+
+// 取出上次之后收到的聊天消息
+#[js_function(1)]
+fn take_chat_messages(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let messages = rt.block_on(async move {
+        manager.take_chat_messages(&session).await
+    });
+
+    Ok(create_chat_messages(&ctx.env, &messages)?.into_unknown())
+}
+
+fn create_chat_message(env: &Env, message: &ChatMessage) -> Result<JsObject> {
+    let mut obj = env.create_object()?;
+    obj.set_named_property("text", message.text.as_str())?;
+    obj.set_named_property("timestamp", message.timestamp as f64)?;
+    obj.set_named_property("incoming", message.incoming)?;
+    Ok(obj)
+}
+
+fn create_chat_messages(env: &Env, messages: &[ChatMessage]) -> Result<JsObject> {
+    let mut array = env.create_array_with_length(messages.len())?;
+    for (index, message) in messages.iter().enumerate() {
+        array.set_element(index as u32, create_chat_message(env, message)?)?;
+    }
+    Ok(array)
+}
+
+// 设置文件传输策略（对所有会话生效，关闭时断开所有文件传输连接，返回 0 成功）
+#[js_function(1)]
+fn set_file_transfer_enabled(ctx: CallContext) -> Result<u32> {
+    let enabled: bool = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    rt.block_on(async move {
+        manager.set_file_transfer_enabled(enabled).await
+    });
+
+    Ok(0)
+}
+
+// 建立文件传输连接（独立于远程桌面连接，返回 0 成功，1 失败）
+#[js_function(2)]
+fn connect_file_transfer(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let password: String = ctx.get(1)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.connect_file_transfer(&session, &password).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to connect file transfer: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 断开文件传输连接（取消进行中的传输，返回 0 成功，1 失败）
+#[js_function(1)]
+fn disconnect_file_transfer(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.disconnect_file_transfer(&session).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to disconnect file transfer: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 上传本地文件或目录到远程路径（返回传输任务 ID，失败返回 -1）
+#[js_function(3)]
+fn start_upload(ctx: CallContext) -> Result<i32> {
+    let session: String = ctx.get(0)?;
+    let local: String = ctx.get(1)?;
+    let remote: String = ctx.get(2)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.start_upload(&session, &local, &remote).await
+    });
+
+    match result {
+        Ok(id) => Ok(id),
+        Err(e) => {
+            log_error!("Failed to start upload: {}", e);
+            Ok(-1)
+        }
+    }
+}
+
+// 下载远程文件或目录到本地路径（返回传输任务 ID，失败返回 -1）
+#[js_function(4)]
+fn start_download(ctx: CallContext) -> Result<i32> {
+    let session: String = ctx.get(0)?;
+    let remote: String = ctx.get(1)?;
+    let local: String = ctx.get(2)?;
+    let include_hidden: bool = ctx.get(3)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.start_download(&session, &remote, &local, include_hidden).await
+    });
+
+    match result {
+        Ok(id) => Ok(id),
+        Err(e) => {
+            log_error!("Failed to start download: {}", e);
+            Ok(-1)
+        }
+    }
+}
+
+// 取消传输任务（未完成的文件保留，下次传输时继续；返回 0 已取消，1 任务不存在）
+#[js_function(2)]
+fn cancel_file_transfer(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let id: i32 = ctx.get(1)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let cancelled = rt.block_on(async move {
+        manager.cancel_transfer(&session, id).await
+    });
+
+    Ok(if cancelled { 0 } else { 1 })
+}
+
+// 设置文件传输限速（字节/秒，0 不限速，对之后开始的任务生效；返回 0 成功，1 失败）
+#[js_function(2)]
+fn set_file_transfer_rate(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let rate: u32 = ctx.get(1)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_transfer_rate(&session, rate).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to set file transfer rate: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 取出文件传输事件（[{ id, type, fileNum, fileCount, transferred, total, error }]，
+// type 为 progress / done / failed / cancelled）
+#[js_function(1)]
+fn get_file_transfer_events(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let events = rt.block_on(async move {
+        manager.take_transfer_events(&session).await
+    });
+
+    let mut array = ctx.env.create_array_with_length(events.len())?;
+    for (index, event) in events.iter().enumerate() {
+        array.set_element(index as u32, create_transfer_event(&ctx.env, event)?)?;
+    }
+    Ok(array.into_unknown())
+}
+
+fn create_transfer_event(env: &Env, event: &TransferEvent) -> Result<JsObject> {
+    let mut obj = env.create_object()?;
+    match event {
+        TransferEvent::Progress { id, file_num, file_count, transferred, total } => {
+            obj.set_named_property("id", *id)?;
+            obj.set_named_property("type", "progress")?;
+            obj.set_named_property("fileNum", *file_num as u32)?;
+            obj.set_named_property("fileCount", *file_count as u32)?;
+            obj.set_named_property("transferred", *transferred as f64)?;
+            obj.set_named_property("total", *total as f64)?;
+        }
+        TransferEvent::Done { id } => {
+            obj.set_named_property("id", *id)?;
+            obj.set_named_property("type", "done")?;
+        }
+        TransferEvent::Failed { id, error } => {
+            obj.set_named_property("id", *id)?;
+            obj.set_named_property("type", "failed")?;
+            obj.set_named_property("error", error.as_str())?;
+        }
+        TransferEvent::Cancelled { id } => {
+            obj.set_named_property("id", *id)?;
+            obj.set_named_property("type", "cancelled")?;
+        }
+    }
+    Ok(obj)
+}
+
+/// 发起远程文件管理操作，返回操作 ID，失败返回 -1
+fn start_file_operation(session: String, operation: FileOperation) -> Result<i32> {
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.request_file_operation(&session, operation).await
+    });
+
+    match result {
+        Ok(id) => Ok(id),
+        Err(e) => {
+            log_error!("Failed to request file operation: {}", e);
+            Ok(-1)
+        }
+    }
+}
+
+// 读取远程目录（返回操作 ID，失败返回 -1；结果由 getFileOperationResults 取出，并缓存为最近的目录列表）
+#[js_function(3)]
+fn read_remote_dir(ctx: CallContext) -> Result<i32> {
+    let session: String = ctx.get(0)?;
+    let path: String = ctx.get(1)?;
+    let include_hidden: bool = ctx.get(2)?;
+
+    start_file_operation(session, FileOperation::ReadDir { path, include_hidden })
+}
+
+// 创建远程目录（返回操作 ID，失败返回 -1）
+#[js_function(2)]
+fn create_remote_dir(ctx: CallContext) -> Result<i32> {
+    let session: String = ctx.get(0)?;
+    let path: String = ctx.get(1)?;
+
+    start_file_operation(session, FileOperation::CreateDir { path })
+}
+
+// 删除远程文件或目录（目录递归删除；返回操作 ID，失败返回 -1）
+#[js_function(2)]
+fn remove_remote_file(ctx: CallContext) -> Result<i32> {
+    let session: String = ctx.get(0)?;
+    let path: String = ctx.get(1)?;
+
+    start_file_operation(session, FileOperation::Remove { path })
+}
+
+// 重命名远程文件或目录（newName 为同一目录下的新名称；返回操作 ID，失败返回 -1）
+#[js_function(3)]
+fn rename_remote_file(ctx: CallContext) -> Result<i32> {
+    let session: String = ctx.get(0)?;
+    let path: String = ctx.get(1)?;
+    let new_name: String = ctx.get(2)?;
+
+    start_file_operation(session, FileOperation::Rename { path, new_name })
+}
+
+// 在远程目录下递归搜索名称匹配通配符（* 和 ?）的条目（返回操作 ID，失败返回 -1）
+#[js_function(4)]
+fn search_remote_files(ctx: CallContext) -> Result<i32> {
+    let session: String = ctx.get(0)?;
+    let path: String = ctx.get(1)?;
+    let pattern: String = ctx.get(2)?;
+    let include_hidden: bool = ctx.get(3)?;
+
+    start_file_operation(session, FileOperation::Search { path, pattern, include_hidden })
+}
+
+// 取出远程文件管理操作的结果（[{ id, path, success, entries, error }]）
+#[js_function(1)]
+fn get_file_operation_results(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let results = rt.block_on(async move {
+        manager.take_file_results(&session).await
+    });
+
+    let mut array = ctx.env.create_array_with_length(results.len())?;
+    for (index, result) in results.iter().enumerate() {
+        let mut obj = ctx.env.create_object()?;
+        obj.set_named_property("id", result.id)?;
+        obj.set_named_property("path", result.path.as_str())?;
+        match &result.result {
+            Ok(entries) => {
+                obj.set_named_property("success", true)?;
+                obj.set_named_property("entries", create_file_entries(&ctx.env, entries)?)?;
+            }
+            Err(error) => {
+                obj.set_named_property("success", false)?;
+                obj.set_named_property("entries", ctx.env.create_array_with_length(0)?)?;
+                obj.set_named_property("error", error.as_str())?;
+            }
+        }
+        array.set_element(index as u32, obj)?;
+    }
+    Ok(array.into_unknown())
+}
+
+// 获取最近一次读取的远程目录列表（{ path, includeHidden, entries }，没有时返回 null）
+#[js_function(1)]
+fn get_remote_dir_listing(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let listing: Option<DirListing> = rt.block_on(async move {
+        manager.remote_dir_listing(&session).await
+    });
+
+    let listing = match listing {
+        Some(listing) => listing,
+        None => return Null.into_unknown(&*ctx.env),
+    };
+    let mut obj = ctx.env.create_object()?;
+    obj.set_named_property("path", listing.path.as_str())?;
+    obj.set_named_property("includeHidden", listing.include_hidden)?;
+    obj.set_named_property("entries", create_file_entries(&ctx.env, &listing.entries)?)?;
+    Ok(obj.into_unknown())
+}
+
+fn create_file_entries(env: &Env, entries: &[FileEntry]) -> Result<JsObject> {
+    let mut array = env.create_array_with_length(entries.len())?;
+    for (index, entry) in entries.iter().enumerate() {
+        let entry_type = match entry.entry_type {
+            FileType::Dir => "dir",
+            FileType::DirLink => "dirLink",
+            FileType::DirDrive => "drive",
+            FileType::File => "file",
+            FileType::FileLink => "fileLink",
+        };
+        let mut obj = env.create_object()?;
+        obj.set_named_property("name", entry.name.as_str())?;
+        obj.set_named_property("type", entry_type)?;
+        obj.set_named_property("hidden", entry.is_hidden)?;
+        obj.set_named_property("size", entry.size as f64)?;
+        obj.set_named_property("modified", entry.modified as f64)?;
+        array.set_element(index as u32, obj)?;
+    }
+    Ok(array)
+}
+
+// 导入宏（JSON 数组，替换现有的全部宏，返回 0 成功，1 失败）
+#[js_function(1)]
+fn set_macros(ctx: CallContext) -> Result<u32> {
+    let json: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_macros(&json).await
+    });
+
+    match result {
+        Ok(_) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to set macros: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 导出全部宏（JSON 数组字符串）
+#[js_function(0)]
+fn get_macros(ctx: CallContext) -> Result<Unknown> {
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let json = rt.block_on(async move {
+        manager.get_macros().await
+    });
+
+    ctx.env.create_string_from_std(json).map(|s| s.into_unknown())
+}
+
+// 在会话中执行宏（后台执行，立即返回，返回 0 成功，1 失败）
+#[js_function(2)]
+fn run_macro(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let name: String = ctx.get(1)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.run_macro(&session, &name).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to run macro: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 取消会话中正在执行的宏（返回 0 已取消，1 没有正在执行的宏）
+#[js_function(1)]
+fn cancel_macro(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let cancelled = rt.block_on(async move {
+        manager.cancel_macro(&session).await
+    });
+
+    Ok(if cancelled { 0 } else { 1 })
+}
+
+// 抬起所有按住的按键和鼠标按键（应用失去焦点时调用，返回 0 成功，1 失败）
+#[js_function(1)]
+fn release_all_inputs(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.release_all_inputs(&session).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to release inputs: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 设置输入发送频率（Hz，默认 60，0 表示不合并、立即发送，返回 0 成功，1 失败）
+#[js_function(2)]
+fn set_input_rate(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let rate: u32 = ctx.get(1)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_input_rate(&session, rate).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to set input rate: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 设置视口（视图尺寸和适配模式 "fit" / "fill" / "original"，返回 0 成功，1 失败）
+// 设置后 sendMouseMove / sendTouch 的坐标按视图坐标处理
+#[js_function(4)]
+fn set_viewport(ctx: CallContext) -> Result<u32> {
+    let session: String = ctx.get(0)?;
+    let width: f64 = ctx.get(1)?;
+    let height: f64 = ctx.get(2)?;
+    let fit: String = ctx.get(3)?;
+
+    let fit = match FitMode::parse(&fit) {
+        Some(fit) => fit,
+        None => {
+            log_error!("Unknown fit mode: {}", fit);
+            return Ok(1);
+        }
+    };
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_viewport(&session, width as f32, height as f32, fit).await
+    });
+
+    match result {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            log_error!("Failed to set viewport: {}", e);
+            Ok(1)
+        }
+    }
+}
+
+// 以视图坐标 (centerX, centerY) 为中心设置缩放倍数（返回视口状态，失败返回 null）
+#[js_function(4)]
+fn set_view_zoom(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+    let zoom: f64 = ctx.get(1)?;
+    let center_x: f64 = ctx.get(2)?;
+    let center_y: f64 = ctx.get(3)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.set_view_zoom(&session, zoom as f32, center_x as f32, center_y as f32).await
+    });
+
+    match result {
+        Ok(state) => Ok(create_viewport_object(&ctx.env, &state)?.into_unknown()),
+        Err(e) => {
+            log_error!("Failed to set view zoom: {}", e);
+            Null.into_unknown(&*ctx.env)
+        }
+    }
+}
+
+// 平移画面（视图像素，返回视口状态，失败返回 null）
+#[js_function(3)]
+fn pan_view(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+    let dx: f64 = ctx.get(1)?;
+    let dy: f64 = ctx.get(2)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        manager.pan_view(&session, dx as f32, dy as f32).await
+    });
+
+    match result {
+        Ok(state) => Ok(create_viewport_object(&ctx.env, &state)?.into_unknown()),
+        Err(e) => {
+            log_error!("Failed to pan view: {}", e);
+            Null.into_unknown(&*ctx.env)
+        }
+    }
+}
+
+// 获取视口状态（会话不存在时返回 null）
+#[js_function(1)]
+fn get_viewport(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let state = rt.block_on(async move {
+        manager.get_viewport(&session).await
+    });
+
+    match state {
+        Some(state) => Ok(create_viewport_object(&ctx.env, &state)?.into_unknown()),
+        None => Null.into_unknown(&*ctx.env),
+    }
+}
+
+// 构建视口状态对象
+fn create_viewport_object(env: &Env, state: &ViewportState) -> Result<JsObject> {
+    let mut obj = env.create_object()?;
+    obj.set_named_property("scale", state.scale as f64)?;
+    obj.set_named_property("zoom", state.zoom as f64)?;
+    obj.set_named_property("offsetX", state.offset_x as f64)?;
+    obj.set_named_property("offsetY", state.offset_y as f64)?;
+    obj.set_named_property("contentWidth", state.content_width as f64)?;
+    obj.set_named_property("contentHeight", state.content_height as f64)?;
+    Ok(obj)
+}
+
+// 构建显示器信息数组
+fn create_display_array(env: &Env, displays: &[DisplayInfo]) -> Result<JsObject> {
+    let mut array = env.create_array_with_length(displays.len())?;
+    for (index, display) in displays.iter().enumerate() {
+        let mut obj = env.create_object()?;
+        obj.set_named_property("index", index as u32)?;
+        obj.set_named_property("x", display.x)?;
+        obj.set_named_property("y", display.y)?;
+        obj.set_named_property("width", display.width)?;
+        obj.set_named_property("height", display.height)?;
+        obj.set_named_property("scale", display.scale as f64)?;
+        obj.set_named_property("isPrimary", display.is_primary)?;
+        obj.set_named_property("name", display.name.as_str())?;
+        array.set_element(index as u32, obj)?;
+    }
+    Ok(array)
+}
+
+// 保存当前远程画面截图（返回 { width, height, path }，失败返回 null）
+#[js_function(3)]
+fn take_screenshot(ctx: CallContext) -> Result<Unknown> {
+    let session: String = ctx.get(0)?;
+    let path: String = ctx.get(1)?;
+    let format: String = ctx.get(2)?;
+
+    log_info!("Taking screenshot: session={}, path={}, format={}", session, path, format);
+
+    let manager = get_manager()?;
+    let rt = background_runtime()?;
+
+    let result = rt.block_on(async move {
+        let format = ScreenshotFormat::parse(&format)?;
+        let frame = manager.get_latest_frame(&session, None).await
+            .ok_or(ScreenshotError::NoFrame)?;
+
+        // 颜色转换和编码较耗时，放到阻塞线程池执行
+        tokio::task::spawn_blocking(move || screenshot::save_frame(&frame, &path, format))
+            .await
+            .map_err(|e| ScreenshotError::Encode(e.to_string()))?
+    });
+
+    match result {
+        Ok(shot) => {
+            let mut obj = ctx.env.create_object()?;
+            obj.set_named_property("width", shot.width)?;
+            obj.set_named_property("height", shot.height)?;
+            obj.set_named_property("path", shot.path)?;
+            Ok(obj.into_unknown())
+        }
+        Err(e) => {
+            let collector = get_log_collector();
+            let mut guard = collector.lock().unwrap_or_else(|e| e.into_inner());
+            guard.set_error(format!("Screenshot failed: {}", e));
+            Null.into_unknown(&*ctx.env)
+        }
+    }
 }
 
 // 导出模块
@@ -517,9 +1970,68 @@ fn init_module(mut exports: JsObject, _env: Env) -> Result<()> {
     exports.create_named_method("cleanup", cleanup)?;
     exports.create_named_method("getConnectionStatus", get_connection_status)?;
     exports.create_named_method("sendKeyEvent", send_key_event)?;
+    exports.create_named_method("setKeyboardMode", set_keyboard_mode)?;
+    exports.create_named_method("sendText", send_text)?;
     exports.create_named_method("sendMouseMove", send_mouse_move)?;
     exports.create_named_method("sendMouseClick", send_mouse_click)?;
+    exports.create_named_method("sendMouseWheel", send_mouse_wheel)?;
+    exports.create_named_method("sendMouseRelative", send_mouse_relative)?;
+    exports.create_named_method("setRelativeMouse", set_relative_mouse)?;
+    exports.create_named_method("sendTouch", send_touch)?;
+    exports.create_named_method("setTouchMode", set_touch_mode)?;
+    exports.create_named_method("sendSpecialAction", send_special_action)?;
+    exports.create_named_method("setInputRate", set_input_rate)?;
+    exports.create_named_method("releaseAllInputs", release_all_inputs)?;
+    exports.create_named_method("setClipboardSync", set_clipboard_sync)?;
+    exports.create_named_method("setLocalClipboard", set_local_clipboard)?;
+    exports.create_named_method("setLocalClipboardData", set_local_clipboard_data)?;
+    exports.create_named_method("getRemoteClipboardFormats", get_remote_clipboard_formats)?;
+    exports.create_named_method("readRemoteClipboard", read_remote_clipboard)?;
+    exports.create_named_method("setFileTransferEnabled", set_file_transfer_enabled)?;
+    exports.create_named_method("connectFileTransfer", connect_file_transfer)?;
+    exports.create_named_method("disconnectFileTransfer", disconnect_file_transfer)?;
+    exports.create_named_method("startUpload", start_upload)?;
+    exports.create_named_method("startDownload", start_download)?;
+    exports.create_named_method("cancelFileTransfer", cancel_file_transfer)?;
+    exports.create_named_method("setFileTransferRate", set_file_transfer_rate)?;
+    exports.create_named_method("getFileTransferEvents", get_file_transfer_events)?;
+    exports.create_named_method("readRemoteDir", read_remote_dir)?;
+    exports.create_named_method("createRemoteDir", create_remote_dir)?;
+    exports.create_named_method("removeRemoteFile", remove_remote_file)?;
+    exports.create_named_method("renameRemoteFile", rename_remote_file)?;
+    exports.create_named_method("searchRemoteFiles", search_remote_files)?;
+    exports.create_named_method("getFileOperationResults", get_file_operation_results)?;
+    exports.create_named_method("getRemoteDirListing", get_remote_dir_listing)?;
+    exports.create_named_method("setLocalClipboardFiles", set_local_clipboard_files)?;
+    exports.create_named_method("getRemoteClipboardFiles", get_remote_clipboard_files)?;
+    exports.create_named_method("pasteRemoteClipboardFiles", paste_remote_clipboard_files)?;
+    exports.create_named_method("getRemoteAudioFormat", get_remote_audio_format)?;
+    exports.create_named_method("readRemoteAudio", read_remote_audio)?;
+    exports.create_named_method("setAudioMuted", set_audio_muted)?;
+    exports.create_named_method("startVoiceCall", start_voice_call)?;
+    exports.create_named_method("endVoiceCall", end_voice_call)?;
+    exports.create_named_method("getVoiceCallState", get_voice_call_state)?;
+    exports.create_named_method("pushMicrophonePcm", push_microphone_pcm)?;
+    exports.create_named_method("sendChatMessage", send_chat_message)?;
+    exports.create_named_method("getChatHistory", get_chat_history)?;
+    exports.create_named_method("takeChatMessages", take_chat_messages)?;
+    exports.create_named_method("setSessionOption", set_session_option)?;
+    exports.create_named_method("setMacros", set_macros)?;
+    exports.create_named_method("getMacros", get_macros)?;
+    exports.create_named_method("runMacro", run_macro)?;
+    exports.create_named_method("cancelMacro", cancel_macro)?;
+    exports.create_named_method("setViewport", set_viewport)?;
+    exports.create_named_method("setViewZoom", set_view_zoom)?;
+    exports.create_named_method("panView", pan_view)?;
+    exports.create_named_method("getViewport", get_viewport)?;
     exports.create_named_method("getVideoFrame", get_video_frame)?;
+    exports.create_named_method("getCursor", get_cursor)?;
+    exports.create_named_method("setCursorComposite", set_cursor_composite)?;
+    exports.create_named_method("takeScreenshot", take_screenshot)?;
+    exports.create_named_method("switchDisplay", switch_display)?;
+    exports.create_named_method("captureDisplays", capture_displays)?;
+    exports.create_named_method("getDisplays", get_displays)?;
+    exports.create_named_method("getSessionInfo", get_session_info)?;
     // 调试函数
     exports.create_named_method("getLogs", get_logs)?;
     exports.create_named_method("getLastError", get_last_error)?;
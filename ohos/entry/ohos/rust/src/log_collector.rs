@@ -61,6 +61,9 @@ impl LogCollector {
             .unwrap_or_default()
             .as_millis() as u64;
 
+        // 同时打印到 stderr（可以在 hdc log 中看到）
+        eprintln!("[Rust][{:?}] {}", level, message);
+
         self.entries.push(LogEntry {
             timestamp,
             level,
@@ -73,9 +76,6 @@ impl LogCollector {
         if self.entries.len() > self.max_entries {
             self.entries.remove(0);
         }
-
-        // 同时打印到 stderr（可以在 hdc log 中看到）
-        eprintln!("[Rust][{:?}] {}", level, message);
     }
 
     /// 获取所有日志
@@ -107,8 +107,8 @@ impl LogCollector {
 
     /// 设置错误信息
     pub fn set_error(&mut self, message: String) {
-        self.error_message = Some(message);
-        self.log(LogLevel::Error, message.clone(), None, None);
+        self.error_message = Some(message.clone());
+        self.log(LogLevel::Error, message, None, None);
     }
 
     /// 获取错误信息
@@ -118,8 +118,8 @@ impl LogCollector {
 
     /// 设置 Panic 消息
     pub fn set_panic(&mut self, message: String) {
-        self.panic_message = Some(message);
         self.log(LogLevel::Error, format!("PANIC: {}", message), None, None);
+        self.panic_message = Some(message);
     }
 
     /// 获取 Panic 消息
@@ -142,51 +142,51 @@ pub fn get_log_collector() -> &'static Mutex<LogCollector> {
 /// 记录错误日志
 #[macro_export]
 macro_rules! log_error {
-    ($($arg:tt)*) => {
+    ($($arg:tt)*) => {{
         let message = format!($($arg)*);
         let collector = $crate::log_collector::get_log_collector();
         let mut guard = collector.lock().unwrap_or_else(|e| {
             e.into_inner()
         });
         guard.log($crate::log_collector::LogLevel::Error, message, Some(file!().to_string()), Some(line!()));
-    };
+    }};
 }
 
 /// 记录警告日志
 #[macro_export]
 macro_rules! log_warn {
-    ($($arg:tt)*) => {
+    ($($arg:tt)*) => {{
         let message = format!($($arg)*);
         let collector = $crate::log_collector::get_log_collector();
         let mut guard = collector.lock().unwrap_or_else(|e| {
             e.into_inner()
         });
         guard.log($crate::log_collector::LogLevel::Warn, message, None, None);
-    };
+    }};
 }
 
 /// 记录信息日志
 #[macro_export]
 macro_rules! log_info {
-    ($($arg:tt)*) => {
+    ($($arg:tt)*) => {{
         let message = format!($($arg)*);
         let collector = $crate::log_collector::get_log_collector();
         let mut guard = collector.lock().unwrap_or_else(|e| {
             e.into_inner()
         });
         guard.log($crate::log_collector::LogLevel::Info, message, None, None);
-    };
+    }};
 }
 
 /// 记录调试日志
 #[macro_export]
 macro_rules! log_debug {
-    ($($arg:tt)*) => {
+    ($($arg:tt)*) => {{
         let message = format!($($arg)*);
         let collector = $crate::log_collector::get_log_collector();
         let mut guard = collector.lock().unwrap_or_else(|e| {
             e.into_inner()
         });
         guard.log($crate::log_collector::LogLevel::Debug, message, None, None);
-    };
+    }};
 }
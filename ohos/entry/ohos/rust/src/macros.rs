@@ -0,0 +1,173 @@
+/**
+ * 输入宏模块
+ *
+ * 把常用的操作序列（如打开运行对话框、输入 cmd、粘贴诊断命令）保存为命名宏：
+ * - 宏以 JSON 保存，由 ArkTS 持久化后整体导入
+ * - 每一步为按键、文本、鼠标点击或延时
+ * - 执行时复用会话现有的按键 / 文本 / 鼠标发送路径
+ *
+ * JSON 格式：
+ * [{ "name": "诊断", "steps": [
+ *     { "type": "key", "code": 2076, "down": true },
+ *     { "type": "key", "code": 2034 },
+ *     { "type": "key", "code": 2076, "down": false },
+ *     { "type": "delay", "ms": 300 },
+ *     { "type": "text", "text": "cmd\n" },
+ *     { "type": "click", "button": 0, "x": 100, "y": 200 }
+ * ] }]
+ */
+
+use serde::{Deserialize, Serialize};
+
+/// 单个宏最多包含的步骤数
+const MAX_STEPS: usize = 1000;
+/// 单次延时上限（毫秒）
+const MAX_DELAY_MS: u64 = 60_000;
+
+/// 宏的一个步骤
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MacroStep {
+    /// 按键（HarmonyOS KeyCode），未指定 down 时按下并抬起
+    Key {
+        code: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        down: Option<bool>,
+    },
+    /// 输入文本（换行、Tab 作为按键发送）
+    Text { text: String },
+    /// 鼠标单击（0 左键，1 中键，2 右键），指定坐标（远程桌面坐标，与视口缩放无关）时先移动过去
+    Click {
+        #[serde(default)]
+        button: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        x: Option<i32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        y: Option<i32>,
+    },
+    /// 延时（毫秒）
+    Delay { ms: u64 },
+}
+
+/// 命名宏
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Macro {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+}
+
+impl Macro {
+    /// 检查宏是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("宏名称为空".to_string());
+        }
+        if self.steps.len() > MAX_STEPS {
+            return Err(format!("宏 {} 步骤过多: {} (最多 {})", self.name, self.steps.len(), MAX_STEPS));
+        }
+        for step in &self.steps {
+            match step {
+                MacroStep::Delay { ms } if *ms > MAX_DELAY_MS => {
+                    return Err(format!("宏 {} 延时过长: {}ms (最多 {}ms)", self.name, ms, MAX_DELAY_MS));
+                }
+                MacroStep::Click { x, y, .. } if x.is_some() != y.is_some() => {
+                    return Err(format!("宏 {} 的点击坐标不完整", self.name));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 宏列表（按导入顺序保存）
+#[derive(Debug, Default)]
+pub struct MacroStore {
+    macros: Vec<Macro>,
+}
+
+impl MacroStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从 JSON 数组导入，替换现有的全部宏，返回宏数量
+    pub fn load_json(&mut self, json: &str) -> Result<usize, String> {
+        let macros: Vec<Macro> =
+            serde_json::from_str(json).map_err(|e| format!("宏 JSON 无效: {}", e))?;
+        for (index, item) in macros.iter().enumerate() {
+            item.validate()?;
+            if macros[..index].iter().any(|other| other.name == item.name) {
+                return Err(format!("宏名称重复: {}", item.name));
+            }
+        }
+        self.macros = macros;
+        Ok(self.macros.len())
+    }
+
+    /// 导出为 JSON 数组
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.macros).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// 按名称查找
+    pub fn get(&self, name: &str) -> Option<&Macro> {
+        self.macros.iter().find(|item| item.name == name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.macros.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.macros.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_json() {
+        let json = r#"[{ "name": "diag", "steps": [
+            { "type": "key", "code": 2076, "down": true },
+            { "type": "key", "code": 2034 },
+            { "type": "delay", "ms": 300 },
+            { "type": "text", "text": "cmd\n" },
+            { "type": "click", "x": 10, "y": 20 }
+        ] }]"#;
+
+        let mut store = MacroStore::new();
+        assert_eq!(store.load_json(json), Ok(1));
+        let steps = &store.get("diag").unwrap().steps;
+        assert_eq!(steps[0], MacroStep::Key { code: 2076, down: Some(true) });
+        assert_eq!(steps[1], MacroStep::Key { code: 2034, down: None });
+        assert_eq!(steps[4], MacroStep::Click { button: 0, x: Some(10), y: Some(20) });
+
+        // 导出后可以重新导入
+        let exported = store.to_json();
+        let mut reloaded = MacroStore::new();
+        reloaded.load_json(&exported).unwrap();
+        assert_eq!(reloaded.get("diag"), store.get("diag"));
+    }
+
+    #[test]
+    fn test_invalid_macros_rejected() {
+        let mut store = MacroStore::new();
+        store.load_json(r#"[{ "name": "a", "steps": [] }]"#).unwrap();
+
+        assert!(store.load_json("{").is_err());
+        assert!(store.load_json(r#"[{ "name": "b", "steps": [{ "type": "jump" }] }]"#).is_err());
+        assert!(store.load_json(r#"[{ "name": " ", "steps": [] }]"#).is_err());
+        assert!(store.load_json(r#"[{ "name": "b", "steps": [{ "type": "delay", "ms": 600000 }] }]"#).is_err());
+        assert!(store.load_json(r#"[{ "name": "b", "steps": [{ "type": "click", "x": 1 }] }]"#).is_err());
+        assert!(store
+            .load_json(r#"[{ "name": "b", "steps": [] }, { "name": "b", "steps": [] }]"#)
+            .is_err());
+
+        // 导入失败时保留原有的宏
+        assert_eq!(store.len(), 1);
+        assert!(store.get("a").is_some());
+    }
+}
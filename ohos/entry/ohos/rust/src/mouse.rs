@@ -0,0 +1,167 @@
+/**
+ * 鼠标输入模块
+ *
+ * 将 ArkTS 的鼠标操作转换为 RustDesk 鼠标事件：
+ * - 记录最后位置，按键和滚轮事件携带当前坐标
+ * - 跟踪按住的按键，移动事件携带按键位（拖拽）
+ * - 相对鼠标模式：移动以位移发送，不足一像素的部分累积到下次
+ */
+
+use crate::protocol::{MouseButton, MouseEvent, MouseEventType};
+
+/// 鼠标状态（每个会话一个）
+#[derive(Debug, Default)]
+pub struct MouseState {
+    x: i32,
+    y: i32,
+    /// 按住的按键（MouseButton 的组合）
+    buttons: u8,
+    /// 相对鼠标模式
+    relative: bool,
+    /// 尚未发送的小数位移
+    remainder: (f32, f32),
+}
+
+impl MouseState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 最后的鼠标位置
+    pub fn position(&self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+
+    /// 按住的按键位
+    pub fn buttons(&self) -> u8 {
+        self.buttons
+    }
+
+    pub fn is_relative(&self) -> bool {
+        self.relative
+    }
+
+    /// 开启 / 关闭相对鼠标模式
+    pub fn set_relative(&mut self, relative: bool) {
+        self.relative = relative;
+        self.remainder = (0.0, 0.0);
+    }
+
+    /// 相对移动（远程像素），累积后不足一像素时返回 None
+    pub fn move_by(&mut self, dx: f32, dy: f32) -> Option<MouseEvent> {
+        let x = self.remainder.0 + dx;
+        let y = self.remainder.1 + dy;
+        let (step_x, step_y) = (x.trunc(), y.trunc());
+        self.remainder = (x - step_x, y - step_y);
+        if step_x == 0.0 && step_y == 0.0 {
+            return None;
+        }
+        Some(MouseEvent::new(
+            MouseEventType::MoveRelative,
+            self.buttons,
+            step_x as i32,
+            step_y as i32,
+        ))
+    }
+
+    /// 移动到远程坐标
+    pub fn move_to(&mut self, x: i32, y: i32) -> MouseEvent {
+        self.x = x;
+        self.y = y;
+        MouseEvent::new(MouseEventType::Move, self.buttons, x, y)
+    }
+
+    /// 按下 / 抬起按键（在最后位置）
+    pub fn button(&mut self, button: MouseButton, down: bool) -> MouseEvent {
+        let event_type = if down {
+            self.buttons |= button as u8;
+            MouseEventType::Down
+        } else {
+            self.buttons &= !(button as u8);
+            MouseEventType::Up
+        };
+        MouseEvent::new(event_type, button as u8, self.x, self.y)
+    }
+
+    /// 抬起所有按住的按键
+    pub fn release_all(&mut self) -> Vec<MouseEvent> {
+        let buttons = [
+            MouseButton::Left,
+            MouseButton::Right,
+            MouseButton::Middle,
+            MouseButton::Back,
+            MouseButton::Forward,
+        ];
+        let held = self.buttons;
+        buttons
+            .into_iter()
+            .filter(|button| held & *button as u8 != 0)
+            .map(|button| self.button(button, false))
+            .collect()
+    }
+
+    /// 滚轮（dx 水平，dy 垂直）
+    pub fn wheel(&self, dx: i32, dy: i32) -> MouseEvent {
+        MouseEvent::new(MouseEventType::Wheel, 0, dx, dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag_carries_buttons() {
+        let mut mouse = MouseState::new();
+        mouse.move_to(100, 200);
+
+        let down = mouse.button(MouseButton::Left, true);
+        assert_eq!(down.event_type().unwrap(), MouseEventType::Down);
+        assert_eq!((down.x, down.y), (100, 200));
+
+        let drag = mouse.move_to(150, 220);
+        assert_eq!(drag.event_type().unwrap(), MouseEventType::Move);
+        assert_eq!(drag.buttons(), MouseButton::Left as u8);
+
+        let up = mouse.button(MouseButton::Left, false);
+        assert_eq!(up.event_type().unwrap(), MouseEventType::Up);
+        assert_eq!(up.buttons(), MouseButton::Left as u8);
+        assert_eq!(mouse.buttons(), 0);
+
+        let wheel = mouse.wheel(0, -2);
+        assert_eq!(wheel.event_type().unwrap(), MouseEventType::Wheel);
+        assert_eq!((wheel.x, wheel.y), (0, -2));
+    }
+
+    #[test]
+    fn test_release_all() {
+        let mut mouse = MouseState::new();
+        mouse.button(MouseButton::Left, true);
+        mouse.button(MouseButton::Right, true);
+
+        let released = mouse.release_all();
+        assert_eq!(released.len(), 2);
+        assert!(released.iter().all(|event| event.event_type().unwrap() == MouseEventType::Up));
+        assert_eq!(mouse.buttons(), 0);
+        assert!(mouse.release_all().is_empty());
+    }
+
+    #[test]
+    fn test_relative_move_accumulates() {
+        let mut mouse = MouseState::new();
+        mouse.set_relative(true);
+        mouse.button(MouseButton::Left, true);
+
+        assert_eq!(mouse.move_by(0.4, -0.6), None);
+        let event = mouse.move_by(0.8, -0.6).unwrap();
+        assert_eq!(event.event_type().unwrap(), MouseEventType::MoveRelative);
+        assert_eq!(event.buttons(), MouseButton::Left as u8);
+        assert_eq!((event.x, event.y), (1, -1));
+        assert_eq!(mouse.position(), (0, 0));
+
+        // 切换模式清除累积的位移
+        mouse.move_by(0.5, 0.0);
+        mouse.set_relative(false);
+        assert_eq!(mouse.move_by(0.5, 0.0), None);
+    }
+}
@@ -50,16 +50,43 @@ pub enum MessageType {
     ConnectionRequest = 0x03,
     ConnectionResponse = 0x04,
     Disconnect = 0x05,
+    PeerInfo = 0x06,
 
     // 视频相关
     VideoFrame = 0x10,
     VideoConfig = 0x11,
     KeepAlive = 0x12,
+    SwitchDisplay = 0x13,
+    CaptureDisplays = 0x14,
 
     // 输入事件
     KeyEvent = 0x20,
     MouseEvent = 0x21,
     ClipboardEvent = 0x22,
+    TouchEvent = 0x23,
+    ClipboardRequest = 0x24,
+    ClipboardResponse = 0x25,
+
+    // 文件传输
+    FileAction = 0x40,
+    FileResponse = 0x41,
+
+    // 音频
+    AudioFormat = 0x50,
+    AudioFrame = 0x51,
+    VoiceCallRequest = 0x52,
+    VoiceCallResponse = 0x53,
+
+    // 会话选项
+    OptionMessage = 0x60,
+
+    // 聊天
+    ChatMessage = 0x70,
+
+    // 光标
+    CursorData = 0x30,
+    CursorId = 0x31,
+    CursorPosition = 0x32,
 
     // 其他
     Ping = 0xF0,
@@ -77,12 +104,29 @@ impl TryFrom<u16> for MessageType {
             0x03 => Ok(MessageType::ConnectionRequest),
             0x04 => Ok(MessageType::ConnectionResponse),
             0x05 => Ok(MessageType::Disconnect),
+            0x06 => Ok(MessageType::PeerInfo),
             0x10 => Ok(MessageType::VideoFrame),
             0x11 => Ok(MessageType::VideoConfig),
             0x12 => Ok(MessageType::KeepAlive),
+            0x13 => Ok(MessageType::SwitchDisplay),
+            0x14 => Ok(MessageType::CaptureDisplays),
             0x20 => Ok(MessageType::KeyEvent),
             0x21 => Ok(MessageType::MouseEvent),
             0x22 => Ok(MessageType::ClipboardEvent),
+            0x23 => Ok(MessageType::TouchEvent),
+            0x24 => Ok(MessageType::ClipboardRequest),
+            0x25 => Ok(MessageType::ClipboardResponse),
+            0x30 => Ok(MessageType::CursorData),
+            0x31 => Ok(MessageType::CursorId),
+            0x32 => Ok(MessageType::CursorPosition),
+            0x40 => Ok(MessageType::FileAction),
+            0x41 => Ok(MessageType::FileResponse),
+            0x50 => Ok(MessageType::AudioFormat),
+            0x51 => Ok(MessageType::AudioFrame),
+            0x52 => Ok(MessageType::VoiceCallRequest),
+            0x53 => Ok(MessageType::VoiceCallResponse),
+            0x60 => Ok(MessageType::OptionMessage),
+            0x70 => Ok(MessageType::ChatMessage),
             0xF0 => Ok(MessageType::Ping),
             0xF1 => Ok(MessageType::Pong),
             0xFF => Ok(MessageType::Error),
@@ -136,6 +180,163 @@ impl Packet {
     }
 }
 
+/// 写入字符串（u16 长度 + UTF-8 数据）
+fn put_string(buf: &mut BytesMut, value: &str) {
+    buf.put_u16(value.len() as u16);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+/// 读取字符串（u16 长度 + UTF-8 数据）
+fn get_string(data: &mut &[u8]) -> Result<String, ProtocolError> {
+    if data.remaining() < 2 {
+        return Err(ProtocolError::InvalidPacket);
+    }
+    let len = data.get_u16() as usize;
+    if data.remaining() < len {
+        return Err(ProtocolError::InvalidPacket);
+    }
+    let value = String::from_utf8(data[..len].to_vec())
+        .map_err(|_| ProtocolError::InvalidPacket)?;
+    data.advance(len);
+    Ok(value)
+}
+
+/// 远程显示器信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayInfo {
+    /// 显示器在远程虚拟桌面中的位置
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    /// 缩放比例（HiDPI）
+    pub scale: f32,
+    pub is_primary: bool,
+    pub name: String,
+}
+
+impl DisplayInfo {
+    /// 单个显示器的固定字段长度（不含名称）
+    const FIXED_SIZE: usize = 4 + 4 + 4 + 4 + 4 + 1;
+
+    fn encode(&self, buf: &mut BytesMut) {
+        buf.put_i32(self.x);
+        buf.put_i32(self.y);
+        buf.put_u32(self.width);
+        buf.put_u32(self.height);
+        buf.put_f32(self.scale);
+        buf.put_u8(self.is_primary as u8);
+        put_string(buf, &self.name);
+    }
+
+    fn decode(data: &mut &[u8]) -> Result<Self, ProtocolError> {
+        if data.remaining() < Self::FIXED_SIZE {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        Ok(Self {
+            x: data.get_i32(),
+            y: data.get_i32(),
+            width: data.get_u32(),
+            height: data.get_u32(),
+            scale: data.get_f32(),
+            is_primary: data.get_u8() != 0,
+            name: get_string(data)?,
+        })
+    }
+}
+
+/// 对端信息（登录成功后由被控端发送）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PeerInfo {
+    pub username: String,
+    pub hostname: String,
+    pub platform: String,
+    pub version: String,
+    pub displays: Vec<DisplayInfo>,
+    pub current_display: usize,
+    pub features: Vec<String>,
+}
+
+impl PeerInfo {
+    /// 序列化为数据包负载
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        put_string(&mut buf, &self.username);
+        put_string(&mut buf, &self.hostname);
+        put_string(&mut buf, &self.platform);
+        put_string(&mut buf, &self.version);
+        buf.put_u8(self.displays.len() as u8);
+        for display in &self.displays {
+            display.encode(&mut buf);
+        }
+        buf.put_u8(self.current_display as u8);
+        buf.put_u8(self.features.len() as u8);
+        for feature in &self.features {
+            put_string(&mut buf, feature);
+        }
+        buf.to_vec()
+    }
+
+    /// 从数据包负载解析
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        let mut data = payload;
+
+        let username = get_string(&mut data)?;
+        let hostname = get_string(&mut data)?;
+        let platform = get_string(&mut data)?;
+        let version = get_string(&mut data)?;
+
+        if data.remaining() < 1 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let display_count = data.get_u8() as usize;
+        let mut displays = Vec::with_capacity(display_count);
+        for _ in 0..display_count {
+            displays.push(DisplayInfo::decode(&mut data)?);
+        }
+
+        if data.remaining() < 2 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let current_display = data.get_u8() as usize;
+        let feature_count = data.get_u8() as usize;
+        let mut features = Vec::with_capacity(feature_count);
+        for _ in 0..feature_count {
+            features.push(get_string(&mut data)?);
+        }
+
+        Ok(Self {
+            username,
+            hostname,
+            platform,
+            version,
+            displays,
+            current_display,
+            features,
+        })
+    }
+
+    /// 当前显示器信息
+    pub fn current(&self) -> Option<&DisplayInfo> {
+        self.displays.get(self.current_display)
+    }
+
+    /// 被控端是否支持触摸输入（Android 设备，或声明了 touch 特性的触屏 Windows 设备）
+    pub fn supports_touch(&self) -> bool {
+        self.platform.eq_ignore_ascii_case("android")
+            || self.features.iter().any(|feature| feature.eq_ignore_ascii_case("touch"))
+    }
+}
+
+/// 连接类型（与 RustDesk ConnType 对应，文件传输使用独立的连接）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ConnType {
+    #[default]
+    DefaultConn = 0,
+    FileTransfer = 1,
+}
+
 /// ID 服务器通信
 pub struct IdServerClient {
     server_addr: String,
@@ -252,7 +453,11 @@ impl IdServerClient {
     }
 
     /// 请求连接到远程 ID
-    pub async fn request_connection(&self, remote_id: &str) -> Result<SocketAddr, ProtocolError> {
+    pub async fn request_connection(
+        &self,
+        remote_id: &str,
+        conn_type: ConnType,
+    ) -> Result<SocketAddr, ProtocolError> {
         log::info!("=== 请求远程设备信息 ===");
         log::info!("远程设备 ID: {}", remote_id);
 
@@ -269,6 +474,7 @@ impl IdServerClient {
         payload.put_u8(0x02); // 连接请求命令
         payload.put_u16(remote_id.len() as u16);
         payload.extend_from_slice(remote_id.as_bytes());
+        payload.put_u8(conn_type as u8);
 
         let packet = Packet::new(MessageType::ConnectionRequest, payload.to_vec());
         let data = packet.serialize();
@@ -451,6 +657,7 @@ impl SecureHandshake {
         socket: &mut UdpSocket,
         peer_addr: SocketAddr,
         password: &str,
+        conn_type: ConnType,
     ) -> Result<(), ProtocolError> {
         log::info!("Starting secure handshake with {}", peer_addr);
 
@@ -466,6 +673,7 @@ impl SecureHandshake {
         let mut payload = BytesMut::new();
         payload.put_u16(password_hash.len() as u16);
         payload.extend_from_slice(&password_hash);
+        payload.put_u8(conn_type as u8);
 
         let packet = Packet::new(MessageType::Handshake, payload.to_vec());
         let data = packet.serialize();
@@ -519,118 +727,2202 @@ impl SecureHandshake {
     }
 }
 
-/// 视频流接收器
-pub struct VideoStreamReceiver {
-    frame_sender: mpsc::Sender<VideoFrame>,
+/// RustDesk 控制键（取值与 RustDesk 协议的 ControlKey 一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ControlKey {
+    Unknown = 0,
+    Alt = 1,
+    Backspace = 2,
+    CapsLock = 3,
+    Control = 4,
+    Delete = 5,
+    DownArrow = 6,
+    End = 7,
+    Escape = 8,
+    F1 = 9,
+    F2 = 10,
+    F3 = 11,
+    F4 = 12,
+    F5 = 13,
+    F6 = 14,
+    F7 = 15,
+    F8 = 16,
+    F9 = 17,
+    F10 = 18,
+    F11 = 19,
+    F12 = 20,
+    Home = 21,
+    LeftArrow = 22,
+    Meta = 23,
+    Option = 24,
+    PageDown = 25,
+    PageUp = 26,
+    Return = 27,
+    RightArrow = 28,
+    Shift = 29,
+    Space = 30,
+    Tab = 31,
+    UpArrow = 32,
+    Numpad0 = 33,
+    Numpad1 = 34,
+    Numpad2 = 35,
+    Numpad3 = 36,
+    Numpad4 = 37,
+    Numpad5 = 38,
+    Numpad6 = 39,
+    Numpad7 = 40,
+    Numpad8 = 41,
+    Numpad9 = 42,
+    Cancel = 43,
+    Clear = 44,
+    Menu = 45,
+    Pause = 46,
+    Kana = 47,
+    Hangul = 48,
+    Junja = 49,
+    Final = 50,
+    Hanja = 51,
+    Kanji = 52,
+    Convert = 53,
+    Select = 54,
+    Print = 55,
+    Execute = 56,
+    Snapshot = 57,
+    Insert = 58,
+    Help = 59,
+    Sleep = 60,
+    Separator = 61,
+    Scroll = 62,
+    NumLock = 63,
+    RWin = 64,
+    Apps = 65,
+    Multiply = 66,
+    Add = 67,
+    Subtract = 68,
+    Decimal = 69,
+    Divide = 70,
+    Equals = 71,
+    NumpadEnter = 72,
+    RShift = 73,
+    RControl = 74,
+    RAlt = 75,
+    VolumeMute = 76,
+    VolumeUp = 77,
+    VolumeDown = 78,
+    Power = 79,
+    CtrlAltDel = 100,
+    LockScreen = 101,
 }
 
-/// 视频帧
-#[derive(Debug, Clone)]
-pub struct VideoFrame {
-    pub width: u32,
-    pub height: u32,
-    pub data: Vec<u8>,
-    pub timestamp: u64,
+impl ControlKey {
+    /// 从协议取值解析（未知取值返回 Unknown）
+    pub fn from_u8(value: u8) -> Self {
+        const KEYS: [ControlKey; 80] = [
+            ControlKey::Unknown, ControlKey::Alt, ControlKey::Backspace, ControlKey::CapsLock,
+            ControlKey::Control, ControlKey::Delete, ControlKey::DownArrow, ControlKey::End,
+            ControlKey::Escape, ControlKey::F1, ControlKey::F2, ControlKey::F3,
+            ControlKey::F4, ControlKey::F5, ControlKey::F6, ControlKey::F7,
+            ControlKey::F8, ControlKey::F9, ControlKey::F10, ControlKey::F11,
+            ControlKey::F12, ControlKey::Home, ControlKey::LeftArrow, ControlKey::Meta,
+            ControlKey::Option, ControlKey::PageDown, ControlKey::PageUp, ControlKey::Return,
+            ControlKey::RightArrow, ControlKey::Shift, ControlKey::Space, ControlKey::Tab,
+            ControlKey::UpArrow, ControlKey::Numpad0, ControlKey::Numpad1, ControlKey::Numpad2,
+            ControlKey::Numpad3, ControlKey::Numpad4, ControlKey::Numpad5, ControlKey::Numpad6,
+            ControlKey::Numpad7, ControlKey::Numpad8, ControlKey::Numpad9, ControlKey::Cancel,
+            ControlKey::Clear, ControlKey::Menu, ControlKey::Pause, ControlKey::Kana,
+            ControlKey::Hangul, ControlKey::Junja, ControlKey::Final, ControlKey::Hanja,
+            ControlKey::Kanji, ControlKey::Convert, ControlKey::Select, ControlKey::Print,
+            ControlKey::Execute, ControlKey::Snapshot, ControlKey::Insert, ControlKey::Help,
+            ControlKey::Sleep, ControlKey::Separator, ControlKey::Scroll, ControlKey::NumLock,
+            ControlKey::RWin, ControlKey::Apps, ControlKey::Multiply, ControlKey::Add,
+            ControlKey::Subtract, ControlKey::Decimal, ControlKey::Divide, ControlKey::Equals,
+            ControlKey::NumpadEnter, ControlKey::RShift, ControlKey::RControl, ControlKey::RAlt,
+            ControlKey::VolumeMute, ControlKey::VolumeUp, ControlKey::VolumeDown, ControlKey::Power,
+        ];
+        match value {
+            100 => ControlKey::CtrlAltDel,
+            101 => ControlKey::LockScreen,
+            v => KEYS.get(v as usize).copied().unwrap_or(ControlKey::Unknown),
+        }
+    }
 }
 
-impl VideoStreamReceiver {
-    pub fn new() -> (Self, mpsc::Receiver<VideoFrame>) {
-        let (sender, receiver) = mpsc::channel(100);
-        (Self { frame_sender: sender }, receiver)
+/// 键盘模式（与 RustDesk 的 KeyboardMode 一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum KeyboardMode {
+    /// 旧模式：控制键 + 字符
+    Legacy = 0,
+    /// 映射模式：发送物理按键位置，由被控端按其键盘布局解释
+    Map = 1,
+    /// 翻译模式：发送本地键盘布局产生的字符
+    Translate = 2,
+}
+
+impl KeyboardMode {
+    /// 从 ArkTS 传入的模式名解析
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "legacy" => Some(KeyboardMode::Legacy),
+            "map" => Some(KeyboardMode::Map),
+            "translate" => Some(KeyboardMode::Translate),
+            _ => None,
+        }
     }
 
-    /// 处理视频数据包
-    pub fn handle_packet(&self, packet: &Packet) -> Result<(), ProtocolError> {
-        if packet.msg_type == MessageType::VideoFrame {
-            // 简化的视频帧解析
-            let mut data = BytesMut::from(&packet.payload[..]);
+    fn from_u8(value: u8) -> Result<Self, ProtocolError> {
+        match value {
+            0 => Ok(KeyboardMode::Legacy),
+            1 => Ok(KeyboardMode::Map),
+            2 => Ok(KeyboardMode::Translate),
+            _ => Err(ProtocolError::InvalidPacket),
+        }
+    }
+}
 
-            if data.len() < 12 {
-                return Err(ProtocolError::InvalidPacket);
+/// 按键内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyValue {
+    /// 控制键
+    Control(ControlKey),
+    /// 字符码（映射模式下为物理按键扫描码）
+    Chr(u32),
+    /// Unicode 字符
+    Unicode(u32),
+    /// 字符序列（输入法上屏文本等）
+    Seq(String),
+}
+
+/// 键盘事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// 按下/抬起
+    pub down: bool,
+    /// 单次按键（按下并抬起）
+    pub press: bool,
+    pub value: KeyValue,
+    /// 事件发生时按住的修饰键
+    pub modifiers: Vec<ControlKey>,
+    pub mode: KeyboardMode,
+}
+
+impl KeyEvent {
+    /// 序列化为数据包负载
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        buf.put_u8(self.down as u8);
+        buf.put_u8(self.press as u8);
+        buf.put_u8(self.mode as u8);
+        match &self.value {
+            KeyValue::Control(key) => {
+                buf.put_u8(0);
+                buf.put_u32(*key as u32);
             }
+            KeyValue::Chr(chr) => {
+                buf.put_u8(1);
+                buf.put_u32(*chr);
+            }
+            KeyValue::Unicode(unicode) => {
+                buf.put_u8(2);
+                buf.put_u32(*unicode);
+            }
+            KeyValue::Seq(seq) => {
+                buf.put_u8(3);
+                put_string(&mut buf, seq);
+            }
+        }
+        buf.put_u8(self.modifiers.len() as u8);
+        for modifier in &self.modifiers {
+            buf.put_u8(*modifier as u8);
+        }
+        buf.to_vec()
+    }
 
-            let width = data.get_u32();
-            let height = data.get_u32();
-            let timestamp = data.get_u64();
-            let frame_data = data.to_vec();
+    /// 从数据包负载解析
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        let mut data = payload;
+        if data.remaining() < 4 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let down = data.get_u8() != 0;
+        let press = data.get_u8() != 0;
+        let mode = KeyboardMode::from_u8(data.get_u8())?;
+        let value = match data.get_u8() {
+            3 => KeyValue::Seq(get_string(&mut data)?),
+            kind => {
+                if data.remaining() < 4 {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                let value = data.get_u32();
+                match kind {
+                    0 => KeyValue::Control(ControlKey::from_u8(value as u8)),
+                    1 => KeyValue::Chr(value),
+                    2 => KeyValue::Unicode(value),
+                    _ => return Err(ProtocolError::InvalidPacket),
+                }
+            }
+        };
 
-            let frame = VideoFrame {
-                width,
-                height,
-                data: frame_data,
-                timestamp,
-            };
+        if data.remaining() < 1 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let count = data.get_u8() as usize;
+        if data.remaining() < count {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let modifiers = (0..count).map(|_| ControlKey::from_u8(data.get_u8())).collect();
+
+        Ok(Self {
+            down,
+            press,
+            value,
+            modifiers,
+            mode,
+        })
+    }
+}
 
-            // 发送到接收通道
-            let _ = self.frame_sender.try_send(frame);
+/// 鼠标事件类型（RustDesk MouseEvent.mask 的低 3 位）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MouseEventType {
+    Move = 0,
+    Down = 1,
+    Up = 2,
+    Wheel = 3,
+    Trackpad = 4,
+    /// 相对移动（x / y 为位移，用于锁定指针的游戏、3D / CAD 软件）
+    MoveRelative = 5,
+}
+
+impl MouseEventType {
+    pub fn from_u8(value: u8) -> Result<Self, ProtocolError> {
+        match value {
+            0 => Ok(MouseEventType::Move),
+            1 => Ok(MouseEventType::Down),
+            2 => Ok(MouseEventType::Up),
+            3 => Ok(MouseEventType::Wheel),
+            4 => Ok(MouseEventType::Trackpad),
+            5 => Ok(MouseEventType::MoveRelative),
+            _ => Err(ProtocolError::InvalidPacket),
         }
+    }
+}
 
-        Ok(())
+/// 鼠标按键（RustDesk MouseEvent.mask 的高位，左移 3 位后与事件类型组合）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MouseButton {
+    Left = 0x01,
+    Right = 0x02,
+    Middle = 0x04,
+    Back = 0x08,
+    Forward = 0x10,
+}
+
+impl MouseButton {
+    /// 从 ArkTS 传入的按键编号解析（0 左键，1 中键，2 右键，3 后退，4 前进）
+    pub fn from_index(index: u32) -> Option<Self> {
+        match index {
+            0 => Some(MouseButton::Left),
+            1 => Some(MouseButton::Middle),
+            2 => Some(MouseButton::Right),
+            3 => Some(MouseButton::Back),
+            4 => Some(MouseButton::Forward),
+            _ => None,
+        }
     }
 }
 
-/// 输入事件发送器
-pub struct InputEventSender {
-    socket: Arc<UdpSocket>,
-    peer_addr: SocketAddr,
+/// mask 中事件类型占用的位数
+const MOUSE_TYPE_BITS: i32 = 3;
+const MOUSE_TYPE_MASK: i32 = (1 << MOUSE_TYPE_BITS) - 1;
+
+/// 鼠标事件
+///
+/// mask = 按键位 << 3 | 事件类型；滚轮事件的 x / y 为水平 / 垂直滚动量
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MouseEvent {
+    pub mask: i32,
+    pub x: i32,
+    pub y: i32,
+    /// 事件发生时按住的修饰键
+    pub modifiers: Vec<ControlKey>,
 }
 
-impl InputEventSender {
-    pub fn new(socket: Arc<UdpSocket>, peer_addr: SocketAddr) -> Self {
-        Self { socket, peer_addr }
+impl MouseEvent {
+    pub fn new(event_type: MouseEventType, buttons: u8, x: i32, y: i32) -> Self {
+        Self {
+            mask: ((buttons as i32) << MOUSE_TYPE_BITS) | event_type as i32,
+            x,
+            y,
+            modifiers: Vec::new(),
+        }
     }
 
-    /// 发送键盘事件
-    pub async fn send_key_event(&self, key: u32, pressed: bool) -> Result<(), ProtocolError> {
-        let mut payload = BytesMut::new();
-        payload.put_u32(key);
-        payload.put_u8(pressed as u8);
+    /// 事件类型
+    pub fn event_type(&self) -> Result<MouseEventType, ProtocolError> {
+        MouseEventType::from_u8((self.mask & MOUSE_TYPE_MASK) as u8)
+    }
 
-        let packet = Packet::new(MessageType::KeyEvent, payload.to_vec());
-        let data = packet.serialize();
+    /// 按键位（MouseButton 的组合）
+    pub fn buttons(&self) -> u8 {
+        (self.mask >> MOUSE_TYPE_BITS) as u8
+    }
 
-        self.socket.send_to(&data, self.peer_addr).await?;
-        Ok(())
+    /// 序列化为数据包负载
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut buf = BytesMut::with_capacity(13 + self.modifiers.len());
+        buf.put_i32(self.mask);
+        buf.put_i32(self.x);
+        buf.put_i32(self.y);
+        buf.put_u8(self.modifiers.len() as u8);
+        for modifier in &self.modifiers {
+            buf.put_u8(*modifier as u8);
+        }
+        buf.to_vec()
     }
 
-    /// 发送鼠标移动
-    pub async fn send_mouse_move(&self, x: i32, y: i32) -> Result<(), ProtocolError> {
-        let mut payload = BytesMut::new();
-        payload.put_i32(x);
-        payload.put_i32(y);
+    /// 从数据包负载解析
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        let mut data = payload;
+        if data.remaining() < 13 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let mask = data.get_i32();
+        let x = data.get_i32();
+        let y = data.get_i32();
+        let count = data.get_u8() as usize;
+        if data.remaining() < count {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let modifiers = (0..count).map(|_| ControlKey::from_u8(data.get_u8())).collect();
 
-        let packet = Packet::new(MessageType::MouseEvent, payload.to_vec());
-        let data = packet.serialize();
+        let event = Self { mask, x, y, modifiers };
+        event.event_type()?;
+        Ok(event)
+    }
+}
 
-        self.socket.send_to(&data, self.peer_addr).await?;
-        Ok(())
+/// 剪贴板内容超过该长度时尝试 zstd 压缩
+const CLIPBOARD_COMPRESS_THRESHOLD: usize = 1024;
+/// zstd 压缩级别
+const CLIPBOARD_COMPRESS_LEVEL: i32 = 3;
+/// 文本类剪贴板内容（解压后）的最大长度
+pub const MAX_CLIPBOARD_LEN: usize = 4 * 1024 * 1024;
+/// 图片剪贴板内容的最大长度
+pub const MAX_CLIPBOARD_IMAGE_LEN: usize = 16 * 1024 * 1024;
+/// 超过该长度的内容只通告格式和长度，对端请求时再分块传输
+/// （每种格式最多内联该长度，全部格式的通告在一个 UDP 数据报内）
+pub const CLIPBOARD_LAZY_THRESHOLD: usize = 8 * 1024;
+
+/// 剪贴板格式标志：内容已压缩
+const CLIPBOARD_FLAG_COMPRESS: u8 = 0x01;
+/// 剪贴板格式标志：内容延迟传输（只有长度）
+const CLIPBOARD_FLAG_LAZY: u8 = 0x02;
+
+/// 剪贴板格式（与 RustDesk ClipboardFormat 对应）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ClipboardFormat {
+    Text = 0,
+    Rtf = 1,
+    Html = 2,
+    ImagePng = 3,
+    /// 复制的文件（每行一个绝对路径，粘贴时通过文件传输连接获取）
+    FileList = 4,
+}
+
+impl ClipboardFormat {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(ClipboardFormat::Text),
+            1 => Some(ClipboardFormat::Rtf),
+            2 => Some(ClipboardFormat::Html),
+            3 => Some(ClipboardFormat::ImagePng),
+            4 => Some(ClipboardFormat::FileList),
+            _ => None,
+        }
     }
 
-    /// 发送鼠标点击
-    pub async fn send_mouse_click(&self, button: u32, pressed: bool) -> Result<(), ProtocolError> {
-        let mut payload = BytesMut::new();
-        payload.put_u32(button);
-        payload.put_u8(pressed as u8);
+    /// 从 MIME 类型解析
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        match mime {
+            "text/plain" => Some(ClipboardFormat::Text),
+            "text/rtf" | "application/rtf" => Some(ClipboardFormat::Rtf),
+            "text/html" => Some(ClipboardFormat::Html),
+            "image/png" => Some(ClipboardFormat::ImagePng),
+            "application/x-file-list" => Some(ClipboardFormat::FileList),
+            _ => None,
+        }
+    }
 
-        let packet = Packet::new(MessageType::MouseEvent, payload.to_vec());
-        let data = packet.serialize();
+    pub fn mime(self) -> &'static str {
+        match self {
+            ClipboardFormat::Text => "text/plain",
+            ClipboardFormat::Rtf => "text/rtf",
+            ClipboardFormat::Html => "text/html",
+            ClipboardFormat::ImagePng => "image/png",
+            ClipboardFormat::FileList => "application/x-file-list",
+        }
+    }
 
-        self.socket.send_to(&data, self.peer_addr).await?;
-        Ok(())
+    /// 是否为文本（内容为 UTF-8）
+    pub fn is_text(self) -> bool {
+        self != ClipboardFormat::ImagePng
+    }
+
+    /// 内容的最大长度
+    pub fn max_len(self) -> usize {
+        if self.is_text() {
+            MAX_CLIPBOARD_LEN
+        } else {
+            MAX_CLIPBOARD_IMAGE_LEN
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// 剪贴板中一种格式的内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardItem {
+    pub format: ClipboardFormat,
+    /// 内容长度（未压缩）
+    pub size: usize,
+    /// 内容，延迟传输时为 None
+    pub content: Option<Vec<u8>>,
+}
 
-    #[test]
-    fn test_packet_serialization() {
-        let packet = Packet::new(MessageType::Ping, vec![1, 2, 3, 4]);
-        let data = packet.serialize();
-        let decoded = Packet::deserialize(&data).unwrap();
+impl ClipboardItem {
+    pub fn new(format: ClipboardFormat, content: Vec<u8>) -> Self {
+        Self { format, size: content.len(), content: Some(content) }
+    }
 
-        assert_eq!(packet.msg_type, decoded.msg_type);
-        assert_eq!(packet.payload, decoded.payload);
+    /// 是否需要延迟传输
+    pub fn is_large(&self) -> bool {
+        self.size > CLIPBOARD_LAZY_THRESHOLD
+    }
+
+    /// 只保留格式和长度（通告）
+    pub fn announce(&self) -> Self {
+        Self { format: self.format, size: self.size, content: None }
+    }
+
+    /// 文件列表
+    pub fn file_list(paths: &[String]) -> Self {
+        Self::new(ClipboardFormat::FileList, paths.join("\n").into_bytes())
+    }
+
+    /// 文件列表中的路径（忽略空行）
+    pub fn file_paths(&self) -> Option<Vec<String>> {
+        if self.format != ClipboardFormat::FileList {
+            return None;
+        }
+        let paths = self.text()?.lines().map(str::trim_end).filter(|path| !path.is_empty());
+        Some(paths.map(str::to_string).collect())
+    }
+
+    /// 文本内容
+    pub fn text(&self) -> Option<&str> {
+        if !self.format.is_text() {
+            return None;
+        }
+        std::str::from_utf8(self.content.as_deref()?).ok()
+    }
+
+    /// 写入：格式、标志、原始长度、内容长度、内容（较长时压缩，压缩后不变小时发送原文）
+    fn write(&self, buf: &mut BytesMut) {
+        let raw = match &self.content {
+            Some(raw) => raw.as_slice(),
+            None => {
+                buf.put_u8(self.format as u8);
+                buf.put_u8(CLIPBOARD_FLAG_LAZY);
+                buf.put_u32(self.size as u32);
+                buf.put_u32(0);
+                return;
+            }
+        };
+        let compressed = if raw.len() > CLIPBOARD_COMPRESS_THRESHOLD {
+            zstd::bulk::compress(raw, CLIPBOARD_COMPRESS_LEVEL)
+                .ok()
+                .filter(|data| data.len() < raw.len())
+        } else {
+            None
+        };
+
+        let (flags, content) = match &compressed {
+            Some(data) => (CLIPBOARD_FLAG_COMPRESS, data.as_slice()),
+            None => (0, raw),
+        };
+        buf.reserve(10 + content.len());
+        buf.put_u8(self.format as u8);
+        buf.put_u8(flags);
+        buf.put_u32(raw.len() as u32);
+        buf.put_u32(content.len() as u32);
+        buf.put_slice(content);
+    }
+
+    fn read(data: &mut &[u8]) -> Result<Self, ProtocolError> {
+        if data.remaining() < 10 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let format = ClipboardFormat::from_u8(data.get_u8()).ok_or(ProtocolError::InvalidPacket)?;
+        let flags = data.get_u8();
+        let size = data.get_u32() as usize;
+        let len = data.get_u32() as usize;
+        if size > format.max_len() || data.remaining() < len {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let content = &data[..len];
+        data.advance(len);
+
+        if flags & CLIPBOARD_FLAG_LAZY != 0 {
+            return Ok(Self { format, size, content: None });
+        }
+        let raw = if flags & CLIPBOARD_FLAG_COMPRESS != 0 {
+            zstd::bulk::decompress(content, format.max_len())
+                .map_err(|_| ProtocolError::InvalidPacket)?
+        } else {
+            content.to_vec()
+        };
+        if raw.len() != size || (format.is_text() && std::str::from_utf8(&raw).is_err()) {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        Ok(Self::new(format, raw))
+    }
+}
+
+/// 剪贴板同步消息（多格式，对应 RustDesk MultiClipboards）
+///
+/// 剪贴板变化时发送全部格式，较大的内容只通告长度，
+/// 对端通过 ClipboardRequest 请求后以 ClipboardResponse 分块传输
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClipboardData {
+    pub items: Vec<ClipboardItem>,
+}
+
+impl ClipboardData {
+    /// 纯文本剪贴板
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { items: vec![ClipboardItem::new(ClipboardFormat::Text, text.into().into_bytes())] }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 查找指定格式
+    pub fn item(&self, format: ClipboardFormat) -> Option<&ClipboardItem> {
+        self.items.iter().find(|item| item.format == format)
+    }
+
+    /// 纯文本内容
+    pub fn text(&self) -> Option<&str> {
+        self.item(ClipboardFormat::Text)?.text()
+    }
+
+    /// 剪贴板变化时发送的消息：较大的内容只通告长度
+    pub fn announce(&self) -> Self {
+        let items = self
+            .items
+            .iter()
+            .map(|item| if item.is_large() { item.announce() } else { item.clone() })
+            .collect();
+        Self { items }
+    }
+
+    /// 序列化为数据包负载（格式数量 + 各格式内容）
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        buf.put_u8(self.items.len() as u8);
+        for item in &self.items {
+            item.write(&mut buf);
+        }
+        buf.to_vec()
+    }
+
+    /// 从数据包负载解析
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        let mut data = payload;
+        if data.remaining() < 1 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let count = data.get_u8() as usize;
+        let mut items: Vec<ClipboardItem> = Vec::with_capacity(count);
+        for _ in 0..count {
+            let item = ClipboardItem::read(&mut data)?;
+            if items.iter().any(|other| other.format == item.format) {
+                return Err(ProtocolError::InvalidPacket);
+            }
+            items.push(item);
+        }
+        Ok(Self { items })
+    }
+}
+
+/// 延迟传输的剪贴板内容（回应 ClipboardRequest）
+///
+/// 与文件传输相同，内容按 FILE_BLOCK_SIZE 分块、每块较大时单独压缩，
+/// 最后发送完成标记（内容长度），接收端按块序号拼接
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardResponse {
+    Block { format: ClipboardFormat, blk_id: u32, data: Vec<u8> },
+    Done { format: ClipboardFormat, size: usize },
+}
+
+impl ClipboardResponse {
+    /// 把剪贴板内容拆分为文件块和完成标记
+    pub fn split(item: &ClipboardItem) -> Vec<Self> {
+        let content = item.content.as_deref().unwrap_or_default();
+        let mut responses: Vec<Self> = content
+            .chunks(FILE_BLOCK_SIZE)
+            .enumerate()
+            .map(|(blk_id, data)| ClipboardResponse::Block {
+                format: item.format,
+                blk_id: blk_id as u32,
+                data: data.to_vec(),
+            })
+            .collect();
+        responses.push(ClipboardResponse::Done { format: item.format, size: content.len() });
+        responses
+    }
+
+    pub fn format(&self) -> ClipboardFormat {
+        match self {
+            ClipboardResponse::Block { format, .. } | ClipboardResponse::Done { format, .. } => *format,
+        }
+    }
+
+    /// 序列化为数据包负载
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        match self {
+            ClipboardResponse::Block { format, blk_id, data } => {
+                let compressed = zstd::bulk::compress(data, FILE_COMPRESS_LEVEL)
+                    .ok()
+                    .filter(|compressed| compressed.len() < data.len());
+                buf.put_u8(0);
+                buf.put_u8(*format as u8);
+                buf.put_u32(*blk_id);
+                buf.put_u8(compressed.is_some() as u8);
+                buf.put_slice(compressed.as_deref().unwrap_or(data));
+            }
+            ClipboardResponse::Done { format, size } => {
+                buf.put_u8(1);
+                buf.put_u8(*format as u8);
+                buf.put_u32(*size as u32);
+            }
+        }
+        buf.to_vec()
+    }
+
+    /// 从数据包负载解析
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        let mut data = payload;
+        if data.remaining() < 2 + 4 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let kind = data.get_u8();
+        let format = ClipboardFormat::from_u8(data.get_u8()).ok_or(ProtocolError::InvalidPacket)?;
+        match kind {
+            0 => {
+                let blk_id = data.get_u32();
+                if data.remaining() < 1 {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                let block = if data.get_u8() != 0 {
+                    zstd::bulk::decompress(data, FILE_BLOCK_SIZE)
+                        .map_err(|_| ProtocolError::InvalidPacket)?
+                } else {
+                    data.to_vec()
+                };
+                if block.len() > FILE_BLOCK_SIZE {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                Ok(ClipboardResponse::Block { format, blk_id, data: block })
+            }
+            1 => {
+                let size = data.get_u32() as usize;
+                if size > format.max_len() {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                Ok(ClipboardResponse::Done { format, size })
+            }
+            _ => Err(ProtocolError::InvalidPacket),
+        }
+    }
+}
+
+/// 触摸事件（触摸直通：被控端支持触摸时代替鼠标事件，坐标为远程虚拟桌面坐标）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchEvent {
+    Down { id: i32, x: i32, y: i32 },
+    Move { id: i32, x: i32, y: i32 },
+    Up { id: i32, x: i32, y: i32 },
+    /// 双指缩放比例（相对手势开始时，千分比）
+    ScaleUpdate { scale: i32 },
+    /// 双指平移（双指中点）
+    PanStart { x: i32, y: i32 },
+    PanUpdate { x: i32, y: i32 },
+    PanEnd { x: i32, y: i32 },
+}
+
+impl TouchEvent {
+    /// 负载长度（类型 + id + x + y）
+    const PAYLOAD_SIZE: usize = 1 + 4 + 4 + 4;
+
+    /// 是否为移动类事件（可按发送频率批量发送）
+    pub fn is_update(self) -> bool {
+        matches!(
+            self,
+            TouchEvent::Move { .. } | TouchEvent::ScaleUpdate { .. } | TouchEvent::PanUpdate { .. }
+        )
+    }
+
+    /// 序列化为数据包负载（缩放事件的比例放在 x，平移事件的 id 为 0）
+    pub fn to_payload(self) -> Vec<u8> {
+        let (kind, id, x, y) = match self {
+            TouchEvent::Down { id, x, y } => (0, id, x, y),
+            TouchEvent::Move { id, x, y } => (1, id, x, y),
+            TouchEvent::Up { id, x, y } => (2, id, x, y),
+            TouchEvent::ScaleUpdate { scale } => (3, 0, scale, 0),
+            TouchEvent::PanStart { x, y } => (4, 0, x, y),
+            TouchEvent::PanUpdate { x, y } => (5, 0, x, y),
+            TouchEvent::PanEnd { x, y } => (6, 0, x, y),
+        };
+        let mut buf = BytesMut::with_capacity(Self::PAYLOAD_SIZE);
+        buf.put_u8(kind);
+        buf.put_i32(id);
+        buf.put_i32(x);
+        buf.put_i32(y);
+        buf.to_vec()
+    }
+
+    /// 从数据包负载解析
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        let mut data = payload;
+        if data.remaining() < Self::PAYLOAD_SIZE {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let kind = data.get_u8();
+        let id = data.get_i32();
+        let x = data.get_i32();
+        let y = data.get_i32();
+        match kind {
+            0 => Ok(TouchEvent::Down { id, x, y }),
+            1 => Ok(TouchEvent::Move { id, x, y }),
+            2 => Ok(TouchEvent::Up { id, x, y }),
+            3 => Ok(TouchEvent::ScaleUpdate { scale: x }),
+            4 => Ok(TouchEvent::PanStart { x, y }),
+            5 => Ok(TouchEvent::PanUpdate { x, y }),
+            6 => Ok(TouchEvent::PanEnd { x, y }),
+            _ => Err(ProtocolError::InvalidPacket),
+        }
+    }
+}
+
+/// 文件传输块大小（单个 UDP 数据报内）
+pub const FILE_BLOCK_SIZE: usize = 32 * 1024;
+/// 文件块的 zstd 压缩级别（与 RustDesk 一致，压缩后不变小时发送原文）
+const FILE_COMPRESS_LEVEL: i32 = 3;
+
+/// 文件类型（取值与 RustDesk FileType 一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FileType {
+    Dir = 0,
+    DirLink = 2,
+    DirDrive = 3,
+    File = 4,
+    FileLink = 5,
+}
+
+impl FileType {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(FileType::Dir),
+            2 => Some(FileType::DirLink),
+            3 => Some(FileType::DirDrive),
+            4 => Some(FileType::File),
+            5 => Some(FileType::FileLink),
+            _ => None,
+        }
+    }
+
+    pub fn is_dir(self) -> bool {
+        matches!(self, FileType::Dir | FileType::DirLink | FileType::DirDrive)
+    }
+}
+
+/// 文件条目（目录列表、传输的文件列表）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    /// 名称（传输列表中为相对路径，以 / 分隔；单个文件时为空）
+    pub name: String,
+    pub entry_type: FileType,
+    pub is_hidden: bool,
+    pub size: u64,
+    /// 修改时间（Unix 秒）
+    pub modified: u64,
+}
+
+impl FileEntry {
+    /// 固定字段长度（类型 + 隐藏 + 大小 + 修改时间，不含名称）
+    const FIXED_SIZE: usize = 1 + 1 + 8 + 8;
+
+    fn encode(&self, buf: &mut BytesMut) {
+        put_string(buf, &self.name);
+        buf.put_u8(self.entry_type as u8);
+        buf.put_u8(self.is_hidden as u8);
+        buf.put_u64(self.size);
+        buf.put_u64(self.modified);
+    }
+
+    fn decode(data: &mut &[u8]) -> Result<Self, ProtocolError> {
+        let name = get_string(data)?;
+        if data.remaining() < Self::FIXED_SIZE {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let entry_type = FileType::from_u8(data.get_u8()).ok_or(ProtocolError::InvalidPacket)?;
+        Ok(Self {
+            name,
+            entry_type,
+            is_hidden: data.get_u8() != 0,
+            size: data.get_u64(),
+            modified: data.get_u64(),
+        })
+    }
+}
+
+fn put_entries(buf: &mut BytesMut, entries: &[FileEntry]) {
+    buf.put_u32(entries.len() as u32);
+    for entry in entries {
+        entry.encode(buf);
+    }
+}
+
+fn get_entries(data: &mut &[u8]) -> Result<Vec<FileEntry>, ProtocolError> {
+    if data.remaining() < 4 {
+        return Err(ProtocolError::InvalidPacket);
+    }
+    let count = data.get_u32() as usize;
+    // 每个条目至少包含名称长度和固定字段
+    if count > data.remaining() / (2 + FileEntry::FIXED_SIZE) {
+        return Err(ProtocolError::InvalidPacket);
+    }
+    (0..count).map(|_| FileEntry::decode(data)).collect()
+}
+
+/// 文件摘要（发送每个文件前先发送，接收端据此跳过相同文件或断点续传）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDigest {
+    pub size: u64,
+    pub modified: u64,
+    /// 文件内容的 SHA-256
+    pub hash: [u8; 32],
+}
+
+/// 文件操作请求（控制端发出；上传时的确认由被控端发出）
+///
+/// 文件管理操作（ReadDir / Search 回应 Dir，其余成功时回应 Done，失败时回应 Error）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileAction {
+    /// 请求对端发送文件或目录（下载），rate 为限速（字节/秒，0 不限速）
+    Send { id: i32, path: String, include_hidden: bool, rate: u32 },
+    /// 通知对端接收文件（上传）
+    Receive { id: i32, path: String, files: Vec<FileEntry> },
+    /// 确认摘要：None 跳过该文件（内容相同），否则从指定块开始传输；
+    /// 回应完成标记时 None 表示文件已校验通过
+    Confirm { id: i32, file_num: i32, offset_blk: Option<u32> },
+    /// 回应完成标记：接收端缺少的块，发送端补发后再次发送完成标记
+    Missing { id: i32, file_num: i32, blocks: Vec<u32> },
+    /// 取消传输
+    Cancel { id: i32 },
+    /// 读取目录（只列出一层）
+    ReadDir { id: i32, path: String, include_hidden: bool },
+    /// 创建目录（包括不存在的上级目录）
+    CreateDir { id: i32, path: String },
+    /// 删除文件或目录（目录递归删除）
+    Remove { id: i32, path: String },
+    /// 重命名（new_name 为同一目录下的新名称）
+    Rename { id: i32, path: String, new_name: String },
+    /// 在目录下递归搜索名称匹配通配符（* 和 ?）的条目，结果为相对路径
+    Search { id: i32, path: String, pattern: String, include_hidden: bool },
+}
+
+impl FileAction {
+    /// 序列化为数据包负载
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        match self {
+            FileAction::Send { id, path, include_hidden, rate } => {
+                buf.put_u8(0);
+                buf.put_i32(*id);
+                put_string(&mut buf, path);
+                buf.put_u8(*include_hidden as u8);
+                buf.put_u32(*rate);
+            }
+            FileAction::Receive { id, path, files } => {
+                buf.put_u8(1);
+                buf.put_i32(*id);
+                put_string(&mut buf, path);
+                put_entries(&mut buf, files);
+            }
+            FileAction::Confirm { id, file_num, offset_blk } => {
+                buf.put_u8(2);
+                buf.put_i32(*id);
+                buf.put_i32(*file_num);
+                // 跳过用 u32::MAX 表示
+                buf.put_u32(offset_blk.unwrap_or(u32::MAX));
+            }
+            FileAction::Cancel { id } => {
+                buf.put_u8(3);
+                buf.put_i32(*id);
+            }
+            FileAction::ReadDir { id, path, include_hidden } => {
+                buf.put_u8(4);
+                buf.put_i32(*id);
+                put_string(&mut buf, path);
+                buf.put_u8(*include_hidden as u8);
+            }
+            FileAction::CreateDir { id, path } => {
+                buf.put_u8(5);
+                buf.put_i32(*id);
+                put_string(&mut buf, path);
+            }
+            FileAction::Remove { id, path } => {
+                buf.put_u8(6);
+                buf.put_i32(*id);
+                put_string(&mut buf, path);
+            }
+            FileAction::Rename { id, path, new_name } => {
+                buf.put_u8(7);
+                buf.put_i32(*id);
+                put_string(&mut buf, path);
+                put_string(&mut buf, new_name);
+            }
+            FileAction::Search { id, path, pattern, include_hidden } => {
+                buf.put_u8(8);
+                buf.put_i32(*id);
+                put_string(&mut buf, path);
+                put_string(&mut buf, pattern);
+                buf.put_u8(*include_hidden as u8);
+            }
+            FileAction::Missing { id, file_num, blocks } => {
+                buf.put_u8(9);
+                buf.put_i32(*id);
+                buf.put_i32(*file_num);
+                buf.put_u32(blocks.len() as u32);
+                for blk_id in blocks {
+                    buf.put_u32(*blk_id);
+                }
+            }
+        }
+        buf.to_vec()
+    }
+
+    /// 从数据包负载解析
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        let mut data = payload;
+        if data.remaining() < 5 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let kind = data.get_u8();
+        let id = data.get_i32();
+        match kind {
+            0 => {
+                let path = get_string(&mut data)?;
+                if data.remaining() < 5 {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                let include_hidden = data.get_u8() != 0;
+                Ok(FileAction::Send { id, path, include_hidden, rate: data.get_u32() })
+            }
+            1 => {
+                let path = get_string(&mut data)?;
+                Ok(FileAction::Receive { id, path, files: get_entries(&mut data)? })
+            }
+            2 => {
+                if data.remaining() < 8 {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                let file_num = data.get_i32();
+                let offset_blk = Some(data.get_u32()).filter(|blk| *blk != u32::MAX);
+                Ok(FileAction::Confirm { id, file_num, offset_blk })
+            }
+            3 => Ok(FileAction::Cancel { id }),
+            4 => {
+                let path = get_string(&mut data)?;
+                if data.remaining() < 1 {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                Ok(FileAction::ReadDir { id, path, include_hidden: data.get_u8() != 0 })
+            }
+            5 => Ok(FileAction::CreateDir { id, path: get_string(&mut data)? }),
+            6 => Ok(FileAction::Remove { id, path: get_string(&mut data)? }),
+            7 => {
+                let path = get_string(&mut data)?;
+                Ok(FileAction::Rename { id, path, new_name: get_string(&mut data)? })
+            }
+            8 => {
+                let path = get_string(&mut data)?;
+                let pattern = get_string(&mut data)?;
+                if data.remaining() < 1 {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                Ok(FileAction::Search { id, path, pattern, include_hidden: data.get_u8() != 0 })
+            }
+            9 => {
+                if data.remaining() < 8 {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                let file_num = data.get_i32();
+                let count = data.get_u32() as usize;
+                if data.remaining() < count * 4 {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                let blocks = (0..count).map(|_| data.get_u32()).collect();
+                Ok(FileAction::Missing { id, file_num, blocks })
+            }
+            _ => Err(ProtocolError::InvalidPacket),
+        }
+    }
+}
+
+/// 文件传输数据（下载时由被控端发出，上传时由控制端发出）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileResponse {
+    /// 传输的文件列表（回应 Send），或目录列表、搜索结果（回应 ReadDir / Search）
+    Dir { id: i32, path: String, entries: Vec<FileEntry> },
+    /// 文件摘要
+    Digest { id: i32, file_num: i32, digest: FileDigest },
+    /// 文件块（较大时 zstd 压缩）
+    Block { id: i32, file_num: i32, blk_id: u32, data: Vec<u8> },
+    /// 单个文件传输完成，或文件管理操作成功（file_num 为 0）
+    Done { id: i32, file_num: i32 },
+    /// 传输出错
+    Error { id: i32, file_num: i32, message: String },
+}
+
+impl FileResponse {
+    /// 所属的传输任务
+    pub fn id(&self) -> i32 {
+        match self {
+            FileResponse::Dir { id, .. }
+            | FileResponse::Digest { id, .. }
+            | FileResponse::Block { id, .. }
+            | FileResponse::Done { id, .. }
+            | FileResponse::Error { id, .. } => *id,
+        }
+    }
+
+    /// 序列化为数据包负载
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        match self {
+            FileResponse::Dir { id, path, entries } => {
+                buf.put_u8(0);
+                buf.put_i32(*id);
+                put_string(&mut buf, path);
+                put_entries(&mut buf, entries);
+            }
+            FileResponse::Digest { id, file_num, digest } => {
+                buf.put_u8(1);
+                buf.put_i32(*id);
+                buf.put_i32(*file_num);
+                buf.put_u64(digest.size);
+                buf.put_u64(digest.modified);
+                buf.put_slice(&digest.hash);
+            }
+            FileResponse::Block { id, file_num, blk_id, data } => {
+                let compressed = zstd::bulk::compress(data, FILE_COMPRESS_LEVEL)
+                    .ok()
+                    .filter(|compressed| compressed.len() < data.len());
+                buf.put_u8(2);
+                buf.put_i32(*id);
+                buf.put_i32(*file_num);
+                buf.put_u32(*blk_id);
+                buf.put_u8(compressed.is_some() as u8);
+                buf.put_slice(compressed.as_deref().unwrap_or(data));
+            }
+            FileResponse::Done { id, file_num } => {
+                buf.put_u8(3);
+                buf.put_i32(*id);
+                buf.put_i32(*file_num);
+            }
+            FileResponse::Error { id, file_num, message } => {
+                buf.put_u8(4);
+                buf.put_i32(*id);
+                buf.put_i32(*file_num);
+                put_string(&mut buf, message);
+            }
+        }
+        buf.to_vec()
+    }
+
+    /// 从数据包负载解析
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        let mut data = payload;
+        if data.remaining() < 5 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let kind = data.get_u8();
+        let id = data.get_i32();
+        if kind == 0 {
+            let path = get_string(&mut data)?;
+            return Ok(FileResponse::Dir { id, path, entries: get_entries(&mut data)? });
+        }
+
+        if data.remaining() < 4 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let file_num = data.get_i32();
+        match kind {
+            1 => {
+                if data.remaining() < 8 + 8 + 32 {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                let size = data.get_u64();
+                let modified = data.get_u64();
+                let mut hash = [0u8; 32];
+                data.copy_to_slice(&mut hash);
+                Ok(FileResponse::Digest { id, file_num, digest: FileDigest { size, modified, hash } })
+            }
+            2 => {
+                if data.remaining() < 5 {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                let blk_id = data.get_u32();
+                let block = if data.get_u8() != 0 {
+                    zstd::bulk::decompress(data, FILE_BLOCK_SIZE)
+                        .map_err(|_| ProtocolError::InvalidPacket)?
+                } else {
+                    data.to_vec()
+                };
+                if block.len() > FILE_BLOCK_SIZE {
+                    return Err(ProtocolError::InvalidPacket);
+                }
+                Ok(FileResponse::Block { id, file_num, blk_id, data: block })
+            }
+            3 => Ok(FileResponse::Done { id, file_num }),
+            4 => Ok(FileResponse::Error { id, file_num, message: get_string(&mut data)? }),
+            _ => Err(ProtocolError::InvalidPacket),
+        }
+    }
+}
+
+/// 光标图像（被控端首次使用某个光标时发送，之后只发送光标 ID）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorData {
+    pub id: u64,
+    /// 热点（点击位置）相对于图像左上角的偏移
+    pub hot_x: i32,
+    pub hot_y: i32,
+    pub width: u32,
+    pub height: u32,
+    /// RGBA 像素数据
+    pub colors: Vec<u8>,
+}
+
+impl CursorData {
+    /// 固定字段长度（ID + 热点 + 宽高）
+    const HEADER_SIZE: usize = 8 + 4 + 4 + 4 + 4;
+
+    /// 序列化为数据包负载
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut buf = BytesMut::with_capacity(Self::HEADER_SIZE + self.colors.len());
+        buf.put_u64(self.id);
+        buf.put_i32(self.hot_x);
+        buf.put_i32(self.hot_y);
+        buf.put_u32(self.width);
+        buf.put_u32(self.height);
+        buf.extend_from_slice(&self.colors);
+        buf.to_vec()
+    }
+
+    /// 从数据包负载解析
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        if payload.len() < Self::HEADER_SIZE {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let mut data = payload;
+        let id = data.get_u64();
+        let hot_x = data.get_i32();
+        let hot_y = data.get_i32();
+        let width = data.get_u32();
+        let height = data.get_u32();
+
+        if data.len() != (width as usize) * (height as usize) * 4 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+
+        Ok(Self {
+            id,
+            hot_x,
+            hot_y,
+            width,
+            height,
+            colors: data.to_vec(),
+        })
+    }
+}
+
+/// 解析光标 ID 消息
+pub fn parse_cursor_id(payload: &[u8]) -> Result<u64, ProtocolError> {
+    if payload.len() < 8 {
+        return Err(ProtocolError::InvalidPacket);
+    }
+    let mut data = payload;
+    Ok(data.get_u64())
+}
+
+/// 解析光标位置消息（远程虚拟桌面坐标）
+pub fn parse_cursor_position(payload: &[u8]) -> Result<(i32, i32), ProtocolError> {
+    if payload.len() < 8 {
+        return Err(ProtocolError::InvalidPacket);
+    }
+    let mut data = payload;
+    Ok((data.get_i32(), data.get_i32()))
+}
+
+/// 解析剪贴板请求消息（请求的格式）
+pub fn parse_clipboard_request(payload: &[u8]) -> Result<ClipboardFormat, ProtocolError> {
+    payload
+        .first()
+        .and_then(|format| ClipboardFormat::from_u8(*format))
+        .ok_or(ProtocolError::InvalidPacket)
+}
+
+/// 视频流接收器
+pub struct VideoStreamReceiver {
+    event_sender: mpsc::Sender<VideoStreamEvent>,
+}
+
+/// 视频帧
+#[derive(Debug, Clone)]
+pub struct VideoFrame {
+    /// 所属显示器索引（同时查看多个显示器时每个显示器一路视频流）
+    pub display: usize,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+    pub timestamp: u64,
+}
+
+/// 视频流配置（新视频流开始时由被控端发送）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoConfig {
+    pub display: usize,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl VideoConfig {
+    /// 负载长度（显示器索引 + 宽 + 高）
+    const PAYLOAD_SIZE: usize = 1 + 4 + 4;
+
+    /// 序列化为数据包负载
+    pub fn to_payload(self) -> Vec<u8> {
+        let mut buf = BytesMut::with_capacity(Self::PAYLOAD_SIZE);
+        buf.put_u8(self.display as u8);
+        buf.put_u32(self.width);
+        buf.put_u32(self.height);
+        buf.to_vec()
+    }
+
+    /// 从数据包负载解析
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        if payload.len() < Self::PAYLOAD_SIZE {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let mut data = payload;
+        Ok(Self {
+            display: data.get_u8() as usize,
+            width: data.get_u32(),
+            height: data.get_u32(),
+        })
+    }
+}
+
+/// 视频流事件
+#[derive(Debug, Clone)]
+pub enum VideoStreamEvent {
+    /// 新视频流开始（切换显示器或分辨率变化），解码器需要重置
+    Config(VideoConfig),
+    /// 编码后的视频帧
+    Frame(VideoFrame),
+}
+
+impl VideoStreamReceiver {
+    pub fn new() -> (Self, mpsc::Receiver<VideoStreamEvent>) {
+        let (sender, receiver) = mpsc::channel(100);
+        (Self { event_sender: sender }, receiver)
+    }
+
+    /// 处理视频数据包
+    pub fn handle_packet(&self, packet: &Packet) -> Result<(), ProtocolError> {
+        if packet.msg_type == MessageType::VideoConfig {
+            let config = VideoConfig::from_payload(&packet.payload)?;
+            log::info!(
+                "New video stream: display={}, {}x{}",
+                config.display,
+                config.width,
+                config.height
+            );
+
+            // 配置变化不能丢弃，否则解码器无法重置
+            if let Err(e) = self.event_sender.try_send(VideoStreamEvent::Config(config)) {
+                log::warn!("Failed to queue video config: {}", e);
+            }
+        } else if packet.msg_type == MessageType::VideoFrame {
+            // 简化的视频帧解析
+            let mut data = BytesMut::from(&packet.payload[..]);
+
+            // 显示器索引 + 宽 + 高 + 时间戳
+            if data.len() < 1 + 4 + 4 + 8 {
+                return Err(ProtocolError::InvalidPacket);
+            }
+
+            let display = data.get_u8() as usize;
+            let width = data.get_u32();
+            let height = data.get_u32();
+            let timestamp = data.get_u64();
+            let frame_data = data.to_vec();
+
+            let frame = VideoFrame {
+                display,
+                width,
+                height,
+                data: frame_data,
+                timestamp,
+            };
+
+            // 发送到接收通道
+            let _ = self.event_sender.try_send(VideoStreamEvent::Frame(frame));
+        }
+
+        Ok(())
+    }
+}
+
+/// Opus 支持的采样率
+const OPUS_SAMPLE_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+
+/// 音频格式（被控端开始发送音频前发送，之后的 AudioFrame 为该格式的 Opus 数据）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioFormat {
+    pub sample_rate: u32,
+    pub channels: u8,
+}
+
+impl AudioFormat {
+    /// 负载长度（采样率 + 声道数）
+    const PAYLOAD_SIZE: usize = 4 + 1;
+
+    pub fn to_payload(self) -> Vec<u8> {
+        let mut buf = BytesMut::with_capacity(Self::PAYLOAD_SIZE);
+        buf.put_u32(self.sample_rate);
+        buf.put_u8(self.channels);
+        buf.to_vec()
+    }
+
+    /// 从数据包负载解析（只接受 Opus 支持的采样率和单 / 双声道）
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        if payload.len() < Self::PAYLOAD_SIZE {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let mut data = payload;
+        let format = Self { sample_rate: data.get_u32(), channels: data.get_u8() };
+        if !OPUS_SAMPLE_RATES.contains(&format.sample_rate) || !(1..=2).contains(&format.channels) {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        Ok(format)
+    }
+}
+
+/// 语音通话请求（双向，is_connect 为 false 表示取消或挂断）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoiceCallRequest {
+    /// 发起时间（毫秒），用于匹配回应
+    pub req_timestamp: u64,
+    pub is_connect: bool,
+}
+
+impl VoiceCallRequest {
+    /// 负载长度（发起时间 + 标志）
+    const PAYLOAD_SIZE: usize = 8 + 1;
+
+    pub fn to_payload(self) -> Vec<u8> {
+        let mut buf = BytesMut::with_capacity(Self::PAYLOAD_SIZE);
+        buf.put_u64(self.req_timestamp);
+        buf.put_u8(self.is_connect as u8);
+        buf.to_vec()
+    }
+
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        if payload.len() < Self::PAYLOAD_SIZE {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let mut data = payload;
+        Ok(Self { req_timestamp: data.get_u64(), is_connect: data.get_u8() != 0 })
+    }
+}
+
+/// 语音通话回应（接听或拒绝）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoiceCallResponse {
+    /// 对应请求的发起时间
+    pub req_timestamp: u64,
+    pub accepted: bool,
+}
+
+impl VoiceCallResponse {
+    /// 负载长度（发起时间 + 标志）
+    const PAYLOAD_SIZE: usize = 8 + 1;
+
+    pub fn to_payload(self) -> Vec<u8> {
+        let mut buf = BytesMut::with_capacity(Self::PAYLOAD_SIZE);
+        buf.put_u64(self.req_timestamp);
+        buf.put_u8(self.accepted as u8);
+        buf.to_vec()
+    }
+
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        if payload.len() < Self::PAYLOAD_SIZE {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let mut data = payload;
+        Ok(Self { req_timestamp: data.get_u64(), accepted: data.get_u8() != 0 })
+    }
+}
+
+/// 序列化聊天消息（UTF-8 文本）
+pub fn chat_message_payload(text: &str) -> Vec<u8> {
+    let mut buf = BytesMut::with_capacity(2 + text.len());
+    put_string(&mut buf, text);
+    buf.to_vec()
+}
+
+/// 解析聊天消息
+pub fn parse_chat_message(payload: &[u8]) -> Result<String, ProtocolError> {
+    let mut data = payload;
+    get_string(&mut data)
+}
+
+/// 画质（预设或自定义码率百分比）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageQuality {
+    Best,
+    Balanced,
+    Low,
+    /// 自定义码率（相对默认码率的百分比）
+    Custom(u32),
+}
+
+impl ImageQuality {
+    /// 自定义码率的范围（百分比）
+    pub const CUSTOM_RANGE: std::ops::RangeInclusive<u32> = 10..=100;
+
+    /// 解析画质设置：best / balanced / low，或码率百分比
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "best" => Some(ImageQuality::Best),
+            "balanced" => Some(ImageQuality::Balanced),
+            "low" => Some(ImageQuality::Low),
+            _ => value
+                .parse()
+                .ok()
+                .filter(|percent| Self::CUSTOM_RANGE.contains(percent))
+                .map(ImageQuality::Custom),
+        }
+    }
+}
+
+/// 会话选项（与 RustDesk OptionMessage 对应，只发送设置了的选项）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptionMessage {
+    /// 关闭音频（被控端停止发送音频）
+    pub disable_audio: Option<bool>,
+    pub image_quality: Option<ImageQuality>,
+    /// 自定义帧率
+    pub custom_fps: Option<u32>,
+    /// 显示远程光标
+    pub show_remote_cursor: Option<bool>,
+    /// 关闭剪贴板（双方停止同步剪贴板）
+    pub disable_clipboard: Option<bool>,
+    /// 会话结束后锁定被控端
+    pub lock_after_session_end: Option<bool>,
+}
+
+impl OptionMessage {
+    const KEY_DISABLE_AUDIO: u8 = 1;
+    const KEY_IMAGE_QUALITY: u8 = 2;
+    const KEY_CUSTOM_IMAGE_QUALITY: u8 = 3;
+    const KEY_CUSTOM_FPS: u8 = 4;
+    const KEY_SHOW_REMOTE_CURSOR: u8 = 5;
+    const KEY_DISABLE_CLIPBOARD: u8 = 6;
+    const KEY_LOCK_AFTER_SESSION_END: u8 = 7;
+
+    /// 画质预设的取值
+    const QUALITY_BEST: u32 = 1;
+    const QUALITY_BALANCED: u32 = 2;
+    const QUALITY_LOW: u32 = 3;
+
+    /// 自定义帧率的范围
+    pub const FPS_RANGE: std::ops::RangeInclusive<u32> = 1..=120;
+
+    /// 解析 ArkTS 的选项设置（键与 RustDesk 的选项名一致），返回只含该选项的消息
+    pub fn parse(key: &str, value: &str) -> Result<Self, String> {
+        let invalid = || format!("无效的会话选项: {} = {}", key, value);
+        let flag = || match value {
+            "true" | "Y" => Ok(true),
+            "false" | "N" => Ok(false),
+            _ => Err(invalid()),
+        };
+        let mut option = Self::default();
+        match key {
+            "image-quality" => option.image_quality = Some(ImageQuality::parse(value).ok_or_else(invalid)?),
+            "custom-fps" => {
+                let fps = value.parse().ok().filter(|fps| Self::FPS_RANGE.contains(fps));
+                option.custom_fps = Some(fps.ok_or_else(invalid)?);
+            }
+            "show-remote-cursor" => option.show_remote_cursor = Some(flag()?),
+            "disable-audio" => option.disable_audio = Some(flag()?),
+            "disable-clipboard" => option.disable_clipboard = Some(flag()?),
+            "lock-after-session-end" => option.lock_after_session_end = Some(flag()?),
+            _ => return Err(format!("未知的会话选项: {}", key)),
+        }
+        Ok(option)
+    }
+
+    /// 是否没有设置任何选项
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 用另一组选项中设置了的值覆盖
+    pub fn merge(&mut self, other: &OptionMessage) {
+        self.disable_audio = other.disable_audio.or(self.disable_audio);
+        self.image_quality = other.image_quality.or(self.image_quality);
+        self.custom_fps = other.custom_fps.or(self.custom_fps);
+        self.show_remote_cursor = other.show_remote_cursor.or(self.show_remote_cursor);
+        self.disable_clipboard = other.disable_clipboard.or(self.disable_clipboard);
+        self.lock_after_session_end = other.lock_after_session_end.or(self.lock_after_session_end);
+    }
+
+    /// 序列化为数据包负载：选项数量，每个选项为键和值
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut options = Vec::new();
+        if let Some(disable_audio) = self.disable_audio {
+            options.push((Self::KEY_DISABLE_AUDIO, disable_audio as u32));
+        }
+        match self.image_quality {
+            Some(ImageQuality::Best) => options.push((Self::KEY_IMAGE_QUALITY, Self::QUALITY_BEST)),
+            Some(ImageQuality::Balanced) => options.push((Self::KEY_IMAGE_QUALITY, Self::QUALITY_BALANCED)),
+            Some(ImageQuality::Low) => options.push((Self::KEY_IMAGE_QUALITY, Self::QUALITY_LOW)),
+            Some(ImageQuality::Custom(percent)) => options.push((Self::KEY_CUSTOM_IMAGE_QUALITY, percent)),
+            None => {}
+        }
+        if let Some(fps) = self.custom_fps {
+            options.push((Self::KEY_CUSTOM_FPS, fps));
+        }
+        if let Some(show) = self.show_remote_cursor {
+            options.push((Self::KEY_SHOW_REMOTE_CURSOR, show as u32));
+        }
+        if let Some(disable_clipboard) = self.disable_clipboard {
+            options.push((Self::KEY_DISABLE_CLIPBOARD, disable_clipboard as u32));
+        }
+        if let Some(lock) = self.lock_after_session_end {
+            options.push((Self::KEY_LOCK_AFTER_SESSION_END, lock as u32));
+        }
+        let mut buf = BytesMut::with_capacity(1 + options.len() * 5);
+        buf.put_u8(options.len() as u8);
+        for (key, value) in options {
+            buf.put_u8(key);
+            buf.put_u32(value);
+        }
+        buf.to_vec()
+    }
+
+    /// 从数据包负载解析（忽略不认识的选项和取值）
+    pub fn from_payload(payload: &[u8]) -> Result<Self, ProtocolError> {
+        let mut data = payload;
+        if data.remaining() < 1 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let count = data.get_u8() as usize;
+        if data.remaining() < count * 5 {
+            return Err(ProtocolError::InvalidPacket);
+        }
+        let mut message = Self::default();
+        for _ in 0..count {
+            let key = data.get_u8();
+            let value = data.get_u32();
+            match (key, value) {
+                (Self::KEY_DISABLE_AUDIO, _) => message.disable_audio = Some(value != 0),
+                (Self::KEY_IMAGE_QUALITY, Self::QUALITY_BEST) => message.image_quality = Some(ImageQuality::Best),
+                (Self::KEY_IMAGE_QUALITY, Self::QUALITY_BALANCED) => {
+                    message.image_quality = Some(ImageQuality::Balanced)
+                }
+                (Self::KEY_IMAGE_QUALITY, Self::QUALITY_LOW) => message.image_quality = Some(ImageQuality::Low),
+                (Self::KEY_CUSTOM_IMAGE_QUALITY, _) => message.image_quality = Some(ImageQuality::Custom(value)),
+                (Self::KEY_CUSTOM_FPS, _) => message.custom_fps = Some(value),
+                (Self::KEY_SHOW_REMOTE_CURSOR, _) => message.show_remote_cursor = Some(value != 0),
+                (Self::KEY_DISABLE_CLIPBOARD, _) => message.disable_clipboard = Some(value != 0),
+                (Self::KEY_LOCK_AFTER_SESSION_END, _) => message.lock_after_session_end = Some(value != 0),
+                _ => log::debug!("忽略未知的会话选项: {} = {}", key, value),
+            }
+        }
+        Ok(message)
+    }
+}
+
+/// 输入事件发送器
+pub struct InputEventSender {
+    socket: Arc<UdpSocket>,
+    peer_addr: SocketAddr,
+}
+
+impl InputEventSender {
+    pub fn new(socket: Arc<UdpSocket>, peer_addr: SocketAddr) -> Self {
+        Self { socket, peer_addr }
+    }
+
+    /// 发送键盘事件
+    pub async fn send_key_event(&self, event: &KeyEvent) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::KeyEvent, event.to_payload());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 发送鼠标事件
+    pub async fn send_mouse_event(&self, event: &MouseEvent) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::MouseEvent, event.to_payload());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 发送触摸事件
+    pub async fn send_touch_event(&self, event: &TouchEvent) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::TouchEvent, event.to_payload());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 发送剪贴板内容
+    pub async fn send_clipboard(&self, clipboard: &ClipboardData) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::ClipboardEvent, clipboard.to_payload());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 请求对端传输延迟的剪贴板内容
+    pub async fn send_clipboard_request(&self, format: ClipboardFormat) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::ClipboardRequest, vec![format as u8]);
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 回应对端的剪贴板请求（一个内容块或完成标记）
+    pub async fn send_clipboard_response(&self, response: &ClipboardResponse) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::ClipboardResponse, response.to_payload());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 发送文件操作请求
+    pub async fn send_file_action(&self, action: &FileAction) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::FileAction, action.to_payload());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 发送文件传输数据
+    pub async fn send_file_response(&self, response: &FileResponse) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::FileResponse, response.to_payload());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 发送会话选项
+    pub async fn send_option(&self, option: &OptionMessage) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::OptionMessage, option.to_payload());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 发送音频格式（之后的音频帧按该格式编码）
+    pub async fn send_audio_format(&self, format: AudioFormat) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::AudioFormat, format.to_payload());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 发送一帧 Opus 音频
+    pub async fn send_audio_frame(&self, frame: &[u8]) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::AudioFrame, frame.to_vec());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 发送语音通话请求
+    pub async fn send_voice_call_request(&self, request: VoiceCallRequest) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::VoiceCallRequest, request.to_payload());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 回应对端的语音通话请求
+    pub async fn send_voice_call_response(&self, response: VoiceCallResponse) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::VoiceCallResponse, response.to_payload());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 发送聊天消息
+    pub async fn send_chat_message(&self, text: &str) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::ChatMessage, chat_message_payload(text));
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 请求被控端切换到指定显示器
+    pub async fn send_switch_display(&self, display: usize) -> Result<(), ProtocolError> {
+        let packet = Packet::new(MessageType::SwitchDisplay, vec![display as u8]);
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+
+    /// 请求被控端同时采集多个显示器（每个显示器一路视频流）
+    pub async fn send_capture_displays(&self, displays: &[usize]) -> Result<(), ProtocolError> {
+        let mut payload = BytesMut::with_capacity(1 + displays.len());
+        payload.put_u8(displays.len() as u8);
+        for display in displays {
+            payload.put_u8(*display as u8);
+        }
+
+        let packet = Packet::new(MessageType::CaptureDisplays, payload.to_vec());
+        let data = packet.serialize();
+
+        self.socket.send_to(&data, self.peer_addr).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packet_serialization() {
+        let packet = Packet::new(MessageType::Ping, vec![1, 2, 3, 4]);
+        let data = packet.serialize();
+        let decoded = Packet::deserialize(&data).unwrap();
+
+        assert_eq!(packet.msg_type, decoded.msg_type);
+        assert_eq!(packet.payload, decoded.payload);
+    }
+
+    #[test]
+    fn test_peer_info_roundtrip() {
+        let info = PeerInfo {
+            username: "alice".to_string(),
+            hostname: "office-pc".to_string(),
+            platform: "Windows".to_string(),
+            version: "1.2.3".to_string(),
+            displays: vec![
+                DisplayInfo {
+                    x: 0,
+                    y: 0,
+                    width: 2560,
+                    height: 1440,
+                    scale: 1.5,
+                    is_primary: true,
+                    name: "\\\\.\\DISPLAY1".to_string(),
+                },
+                DisplayInfo {
+                    x: -1920,
+                    y: 0,
+                    width: 1920,
+                    height: 1080,
+                    scale: 1.0,
+                    is_primary: false,
+                    name: "\\\\.\\DISPLAY2".to_string(),
+                },
+            ],
+            current_display: 1,
+            features: vec!["privacy_mode".to_string()],
+        };
+
+        let decoded = PeerInfo::from_payload(&info.to_payload()).unwrap();
+        assert_eq!(decoded, info);
+        assert_eq!(decoded.current().unwrap().width, 1920);
+
+        // 截断的负载必须报错而不是 panic
+        let payload = info.to_payload();
+        assert!(PeerInfo::from_payload(&payload[..payload.len() - 3]).is_err());
+    }
+
+    #[test]
+    fn test_video_frame_carries_display() {
+        let (receiver, mut events) = VideoStreamReceiver::new();
+
+        let mut payload = BytesMut::new();
+        payload.put_u8(1);
+        payload.put_u32(1920);
+        payload.put_u32(1080);
+        payload.put_u64(42);
+        payload.extend_from_slice(&[0, 0, 0, 1, 0x65]);
+        receiver
+            .handle_packet(&Packet::new(MessageType::VideoFrame, payload.to_vec()))
+            .unwrap();
+
+        match events.try_recv().unwrap() {
+            VideoStreamEvent::Frame(frame) => {
+                assert_eq!(frame.display, 1);
+                assert_eq!(frame.timestamp, 42);
+                assert_eq!(frame.data.len(), 5);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        // 头部不完整
+        let short = Packet::new(MessageType::VideoFrame, vec![0; 16]);
+        assert!(receiver.handle_packet(&short).is_err());
+    }
+
+    #[test]
+    fn test_cursor_data_roundtrip() {
+        let cursor = CursorData {
+            id: 7,
+            hot_x: 1,
+            hot_y: 2,
+            width: 2,
+            height: 2,
+            colors: vec![255; 16],
+        };
+        assert_eq!(CursorData::from_payload(&cursor.to_payload()).unwrap(), cursor);
+
+        // 像素数据长度与宽高不符
+        let payload = cursor.to_payload();
+        assert!(CursorData::from_payload(&payload[..payload.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_key_event_roundtrip() {
+        let events = [
+            KeyEvent {
+                down: true,
+                press: false,
+                value: KeyValue::Control(ControlKey::F5),
+                modifiers: vec![ControlKey::Control, ControlKey::Shift],
+                mode: KeyboardMode::Legacy,
+            },
+            KeyEvent {
+                down: true,
+                press: true,
+                value: KeyValue::Seq("你好".to_string()),
+                modifiers: vec![],
+                mode: KeyboardMode::Translate,
+            },
+        ];
+        for event in events {
+            assert_eq!(KeyEvent::from_payload(&event.to_payload()).unwrap(), event);
+        }
+
+        assert_eq!(ControlKey::from_u8(72), ControlKey::NumpadEnter);
+        assert_eq!(ControlKey::from_u8(101), ControlKey::LockScreen);
+        assert_eq!(ControlKey::from_u8(90), ControlKey::Unknown);
+    }
+
+    #[test]
+    fn test_mouse_event_mask() {
+        let down = MouseEvent::new(MouseEventType::Down, MouseButton::Right as u8, 10, 20);
+        assert_eq!(down.mask, 0x02 << 3 | 1);
+        assert_eq!(down.event_type().unwrap(), MouseEventType::Down);
+        assert_eq!(down.buttons(), MouseButton::Right as u8);
+
+        let mut wheel = MouseEvent::new(MouseEventType::Wheel, 0, -1, 3);
+        wheel.modifiers = vec![ControlKey::Control];
+        let decoded = MouseEvent::from_payload(&wheel.to_payload()).unwrap();
+        assert_eq!(decoded, wheel);
+        assert_eq!(decoded.event_type().unwrap(), MouseEventType::Wheel);
+
+        let relative = MouseEvent::new(MouseEventType::MoveRelative, 0, -4, 2);
+        let decoded = MouseEvent::from_payload(&relative.to_payload()).unwrap();
+        assert_eq!(decoded.event_type().unwrap(), MouseEventType::MoveRelative);
+        assert_eq!((decoded.x, decoded.y), (-4, 2));
+
+        // 未知事件类型
+        let invalid = MouseEvent { mask: 7, x: 0, y: 0, modifiers: vec![] };
+        assert!(MouseEvent::from_payload(&invalid.to_payload()).is_err());
+        assert_eq!(MouseButton::from_index(1), Some(MouseButton::Middle));
+        assert_eq!(MouseButton::from_index(5), None);
+    }
+
+    #[test]
+    fn test_touch_event_roundtrip() {
+        let events = [
+            TouchEvent::Down { id: 1, x: 10, y: -20 },
+            TouchEvent::ScaleUpdate { scale: 1500 },
+            TouchEvent::PanEnd { x: 5, y: 6 },
+        ];
+        for event in events {
+            assert_eq!(TouchEvent::from_payload(&event.to_payload()).unwrap(), event);
+        }
+        assert!(TouchEvent::from_payload(&[9; 13]).is_err());
+
+        let mut info = PeerInfo { platform: "Windows".to_string(), ..Default::default() };
+        assert!(!info.supports_touch());
+        info.features.push("touch".to_string());
+        assert!(info.supports_touch());
+    }
+
+    #[test]
+    fn test_clipboard_roundtrip() {
+        // 短文本不压缩
+        let short = ClipboardData::new("你好, clipboard");
+        let payload = short.to_payload();
+        assert_eq!(payload[2], 0);
+        assert_eq!(ClipboardData::from_payload(&payload).unwrap(), short);
+        assert_eq!(short.text(), Some("你好, clipboard"));
+
+        // 长文本压缩后更短
+        let long = ClipboardData::new("a".repeat(10_000));
+        let payload = long.to_payload();
+        assert_eq!(payload[2], CLIPBOARD_FLAG_COMPRESS);
+        assert!(payload.len() < 10_000);
+        assert_eq!(ClipboardData::from_payload(&payload).unwrap(), long);
+
+        // 截断和无效的压缩数据
+        assert!(ClipboardData::from_payload(&payload[..payload.len() - 1]).is_err());
+        assert!(ClipboardData::from_payload(&[1, 0, 1, 0, 0, 0, 2, 0, 0, 0, 2, 0xAB, 0xCD]).is_err());
+    }
+
+    #[test]
+    fn test_clipboard_multi_format() {
+        let png = vec![0x89u8; CLIPBOARD_LAZY_THRESHOLD + 1];
+        let data = ClipboardData {
+            items: vec![
+                ClipboardItem::new(ClipboardFormat::Text, b"hello".to_vec()),
+                ClipboardItem::new(ClipboardFormat::Html, b"<b>hello</b>".to_vec()),
+                ClipboardItem::new(ClipboardFormat::ImagePng, png.clone()),
+            ],
+        };
+
+        // 较大的图片只通告长度
+        let announced = ClipboardData::from_payload(&data.announce().to_payload()).unwrap();
+        assert_eq!(announced.items.len(), 3);
+        assert_eq!(announced.item(ClipboardFormat::Html).unwrap().text(), Some("<b>hello</b>"));
+        let image = announced.item(ClipboardFormat::ImagePng).unwrap();
+        assert_eq!((image.size, image.content.as_ref()), (png.len(), None));
+
+        // 请求后分块传输
+        let item = data.item(ClipboardFormat::ImagePng).unwrap();
+        let responses = ClipboardResponse::split(item);
+        assert_eq!(responses.last(), Some(&ClipboardResponse::Done { format: ClipboardFormat::ImagePng, size: png.len() }));
+        for response in &responses {
+            assert_eq!(&ClipboardResponse::from_payload(&response.to_payload()).unwrap(), response);
+        }
+        assert_eq!(parse_clipboard_request(&[3]).unwrap(), ClipboardFormat::ImagePng);
+        assert!(parse_clipboard_request(&[9]).is_err());
+
+        // 超出长度限制、重复格式和无效的 UTF-8 文本
+        let mut oversized = ClipboardItem::new(ClipboardFormat::Html, vec![]).announce();
+        oversized.size = MAX_CLIPBOARD_LEN + 1;
+        assert!(ClipboardData::from_payload(&ClipboardData { items: vec![oversized] }.to_payload()).is_err());
+        let text = ClipboardItem::new(ClipboardFormat::Text, b"a".to_vec());
+        let duplicate = ClipboardData { items: vec![text.clone(), text] };
+        assert!(ClipboardData::from_payload(&duplicate.to_payload()).is_err());
+        let invalid = ClipboardData { items: vec![ClipboardItem::new(ClipboardFormat::Rtf, vec![0xFF])] };
+        assert!(ClipboardData::from_payload(&invalid.to_payload()).is_err());
+        assert_eq!(ClipboardFormat::from_mime("text/html"), Some(ClipboardFormat::Html));
+
+        // 文件列表
+        let files = ClipboardItem::file_list(&["/data/a.txt".to_string(), "C:\\Users\\报告".to_string()]);
+        let data = ClipboardData { items: vec![files] };
+        let parsed = ClipboardData::from_payload(&data.to_payload()).unwrap();
+        let paths = parsed.item(ClipboardFormat::FileList).unwrap().file_paths().unwrap();
+        assert_eq!(paths, vec!["/data/a.txt", "C:\\Users\\报告"]);
+        let crlf = ClipboardItem::new(ClipboardFormat::FileList, b"/a\r\n\r\n/b".to_vec());
+        assert_eq!(crlf.file_paths().unwrap(), vec!["/a", "/b"]);
+    }
+
+    #[test]
+    fn test_clipboard_fits_datagram() {
+        // 每种格式都在内联上限时，通告仍在一个数据报内
+        let formats = [
+            ClipboardFormat::Text,
+            ClipboardFormat::Rtf,
+            ClipboardFormat::Html,
+            ClipboardFormat::ImagePng,
+            ClipboardFormat::FileList,
+        ];
+        let items = formats
+            .iter()
+            .map(|format| ClipboardItem::new(*format, vec![b'a'; CLIPBOARD_LAZY_THRESHOLD]))
+            .collect();
+        let payload = ClipboardData { items }.announce().to_payload();
+        assert!(payload.len() < 60 * 1024);
+
+        // 超过 64 KiB 的内容（不可压缩）分块传输，每块一个数据报
+        let content: Vec<u8> = (0..100_000u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect();
+        let item = ClipboardItem::new(ClipboardFormat::ImagePng, content.clone());
+        let mut received = vec![0u8; content.len()];
+        let mut done = None;
+        for response in ClipboardResponse::split(&item) {
+            let payload = Packet::new(MessageType::ClipboardResponse, response.to_payload()).serialize();
+            assert!(payload.len() < 60 * 1024);
+            match ClipboardResponse::from_payload(&Packet::deserialize(&payload).unwrap().payload).unwrap() {
+                ClipboardResponse::Block { blk_id, data, .. } => {
+                    let offset = blk_id as usize * FILE_BLOCK_SIZE;
+                    received[offset..offset + data.len()].copy_from_slice(&data);
+                }
+                ClipboardResponse::Done { size, .. } => done = Some(size),
+            }
+        }
+        assert_eq!(done, Some(content.len()));
+        assert_eq!(received, content);
+        assert!(ClipboardResponse::from_payload(&[1, 3, 0xFF, 0xFF, 0xFF, 0xFF]).is_err());
+    }
+
+    #[test]
+    fn test_file_messages_roundtrip() {
+        let entry = FileEntry {
+            name: "docs/报告.pdf".to_string(),
+            entry_type: FileType::File,
+            is_hidden: false,
+            size: 70_000,
+            modified: 1_700_000_000,
+        };
+        let actions = [
+            FileAction::Send { id: 1, path: "C:\\data".to_string(), include_hidden: true, rate: 1024 },
+            FileAction::Receive { id: 2, path: "/tmp".to_string(), files: vec![entry.clone()] },
+            FileAction::Confirm { id: 2, file_num: 0, offset_blk: Some(3) },
+            FileAction::Confirm { id: 2, file_num: 1, offset_blk: None },
+            FileAction::Missing { id: 2, file_num: 1, blocks: vec![1, 5, 6] },
+            FileAction::Cancel { id: 1 },
+            FileAction::ReadDir { id: 3, path: "/home".to_string(), include_hidden: false },
+            FileAction::CreateDir { id: 4, path: "/home/新建文件夹".to_string() },
+            FileAction::Remove { id: 5, path: "D:\\old".to_string() },
+            FileAction::Rename { id: 6, path: "/tmp/a.txt".to_string(), new_name: "b.txt".to_string() },
+            FileAction::Search { id: 7, path: "/".to_string(), pattern: "*.log".to_string(), include_hidden: true },
+        ];
+        for action in &actions {
+            assert_eq!(&FileAction::from_payload(&action.to_payload()).unwrap(), action);
+        }
+
+        let responses = [
+            FileResponse::Dir { id: 1, path: "/tmp".to_string(), entries: vec![entry] },
+            FileResponse::Digest {
+                id: 1,
+                file_num: 0,
+                digest: FileDigest { size: 70_000, modified: 1, hash: [7; 32] },
+            },
+            FileResponse::Block { id: 1, file_num: 0, blk_id: 2, data: vec![0; FILE_BLOCK_SIZE] },
+            FileResponse::Block { id: 1, file_num: 0, blk_id: 3, data: vec![1, 2, 3] },
+            FileResponse::Done { id: 1, file_num: 0 },
+            FileResponse::Error { id: 1, file_num: 0, message: "权限不足".to_string() },
+        ];
+        for response in &responses {
+            assert_eq!(&FileResponse::from_payload(&response.to_payload()).unwrap(), response);
+        }
+
+        // 重复数据压缩传输
+        assert!(responses[2].to_payload().len() < FILE_BLOCK_SIZE);
+        // 截断和无效的条目数量
+        let payload = responses[0].to_payload();
+        assert!(FileResponse::from_payload(&payload[..payload.len() - 1]).is_err());
+        assert!(FileAction::from_payload(&[1, 0, 0, 0, 2, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]).is_err());
+    }
+
+    #[test]
+    fn test_audio_and_option_roundtrip() {
+        let format = AudioFormat { sample_rate: 48000, channels: 2 };
+        assert_eq!(AudioFormat::from_payload(&format.to_payload()).unwrap(), format);
+        let invalid = AudioFormat { sample_rate: 44100, channels: 2 };
+        assert!(AudioFormat::from_payload(&invalid.to_payload()).is_err());
+        assert!(AudioFormat::from_payload(&AudioFormat { sample_rate: 16000, channels: 3 }.to_payload()).is_err());
+        assert!(AudioFormat::from_payload(&[0, 0]).is_err());
+
+        let request = VoiceCallRequest { req_timestamp: 1_700_000_000_123, is_connect: true };
+        assert_eq!(VoiceCallRequest::from_payload(&request.to_payload()).unwrap(), request);
+        let response = VoiceCallResponse { req_timestamp: 1_700_000_000_123, accepted: false };
+        assert_eq!(VoiceCallResponse::from_payload(&response.to_payload()).unwrap(), response);
+        assert!(VoiceCallResponse::from_payload(&[0; 8]).is_err());
+
+        let option = OptionMessage { disable_audio: Some(true), ..Default::default() };
+        assert_eq!(OptionMessage::from_payload(&option.to_payload()).unwrap(), option);
+        assert!(OptionMessage::default().is_empty());
+        assert_eq!(OptionMessage::default().to_payload(), vec![0]);
+        // 未知选项被忽略，截断的负载无效
+        assert_eq!(OptionMessage::from_payload(&[1, 0xEE, 0, 0, 0, 1]).unwrap(), OptionMessage::default());
+        assert!(OptionMessage::from_payload(&[2, 1, 0, 0, 0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_session_options() {
+        let mut options = OptionMessage::parse("image-quality", "balanced").unwrap();
+        options.merge(&OptionMessage::parse("custom-fps", "30").unwrap());
+        options.merge(&OptionMessage::parse("show-remote-cursor", "true").unwrap());
+        options.merge(&OptionMessage::parse("disable-clipboard", "Y").unwrap());
+        options.merge(&OptionMessage::parse("lock-after-session-end", "true").unwrap());
+        assert_eq!(OptionMessage::from_payload(&options.to_payload()).unwrap(), options);
+
+        // 后设置的值覆盖之前的值，未设置的保留
+        options.merge(&OptionMessage::parse("image-quality", "50").unwrap());
+        options.merge(&OptionMessage::parse("disable-clipboard", "false").unwrap());
+        assert_eq!(options.image_quality, Some(ImageQuality::Custom(50)));
+        assert_eq!(options.disable_clipboard, Some(false));
+        assert_eq!(options.custom_fps, Some(30));
+        assert_eq!(OptionMessage::from_payload(&options.to_payload()).unwrap(), options);
+
+        assert!(OptionMessage::parse("image-quality", "5").is_err());
+        assert!(OptionMessage::parse("custom-fps", "0").is_err());
+        assert!(OptionMessage::parse("disable-audio", "yes").is_err());
+        assert!(OptionMessage::parse("view-only", "true").is_err());
+    }
+
+    #[test]
+    fn test_chat_message_roundtrip() {
+        assert_eq!(parse_chat_message(&chat_message_payload("马上重启")).unwrap(), "马上重启");
+        assert!(parse_chat_message(&[0, 5, b'a']).is_err());
+    }
+
+    #[test]
+    fn test_video_config_roundtrip() {
+        let config = VideoConfig { display: 2, width: 1280, height: 720 };
+        assert_eq!(VideoConfig::from_payload(&config.to_payload()).unwrap(), config);
+        assert!(VideoConfig::from_payload(&[0, 1]).is_err());
     }
 }
@@ -5,14 +5,31 @@
  * 实现了完整的远程桌面连接、视频流接收和输入转发
  */
 
+use crate::audio::{AudioPlayback, VoiceCall, VoiceCallMessage, VoiceCallState};
+use crate::chat::{ChatHistory, ChatMessage};
+use crate::clipboard::{ClipboardSync, RemoteRead};
+use crate::cursor::CursorState;
+use crate::file_manager::{DirListing, FileManager, FileOperation, OperationResult};
+use crate::file_transfer::{self, FileTransfers, TransferEvent, Upload};
+use crate::gesture::{GestureAction, GestureRecognizer, TouchAction, TouchMode, TouchPoint};
+use crate::input_queue::{InputCommand, InputQueue};
+use crate::keyboard::{self, KeyboardTranslator, SpecialAction};
+use crate::mouse::MouseState;
+use crate::touch::TouchPassthrough;
+use crate::viewport::{FitMode, Viewport, ViewportState};
 use crate::protocol::{
-    IdServerClient, NatTraversal, SecureHandshake,
-    VideoStreamReceiver, InputEventSender, VideoFrame, ProtocolError
+    self, AudioFormat, IdServerClient, NatTraversal, SecureHandshake, MessageType, Packet, PeerInfo, ClipboardData,
+    ClipboardFormat, ClipboardResponse, ConnType, CursorData, FileAction, FileResponse, KeyboardMode, OptionMessage, MouseButton, MouseEvent, MouseEventType, VideoStreamReceiver, VideoStreamEvent, InputEventSender, ProtocolError,
+    VoiceCallRequest, VoiceCallResponse
 };
+use crate::video::{DecodedFrame, DecoderConfig, FrameBuffer, H264Decoder};
 use std::sync::Arc;
-use tokio::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex, MutexGuard, Notify};
 use tokio::net::UdpSocket;
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 
 /// RustDesk 连接配置
@@ -28,6 +45,10 @@ pub struct RustDeskConfig {
     pub relay_server: Option<String>,
     /// 是否使用强制中继
     pub force_relay: bool,
+    /// 连接类型（文件传输使用独立的连接）
+    pub conn_type: ConnType,
+    /// 会话选项（登录后发送给对端）
+    pub options: OptionMessage,
 }
 
 impl Default for RustDeskConfig {
@@ -39,6 +60,8 @@ impl Default for RustDeskConfig {
             id_server: "router.rustdesk.com:21116".to_string(),
             relay_server: None,
             force_relay: false,
+            conn_type: ConnType::DefaultConn,
+            options: OptionMessage::default(),
         }
     }
 }
@@ -52,6 +75,9 @@ pub enum ConnectionState {
     Failed,
 }
 
+/// Android 最近任务需要长按中键的时长
+const MOBILE_LONG_PRESS: Duration = Duration::from_millis(500);
+
 /// RustDesk 连接管理器
 pub struct RustDeskConnection {
     config: RustDeskConfig,
@@ -59,7 +85,26 @@ pub struct RustDeskConnection {
     socket: Arc<Mutex<Option<Arc<UdpSocket>>>>,
     peer_addr: Arc<Mutex<Option<std::net::SocketAddr>>>,
     input_sender: Arc<Mutex<Option<InputEventSender>>>,
-    video_receiver: Arc<Mutex<Option<mpsc::Receiver<VideoFrame>>>>,
+    video_receiver: Arc<Mutex<Option<mpsc::Receiver<VideoStreamEvent>>>>,
+    peer_info: Arc<Mutex<Option<PeerInfo>>>,
+    cursor: Arc<Mutex<CursorState>>,
+    clipboard: Arc<Mutex<ClipboardSync>>,
+    files: Arc<Mutex<FileTransfers>>,
+    file_manager: Arc<Mutex<FileManager>>,
+    audio: Arc<Mutex<AudioPlayback>>,
+    voice_call: Arc<Mutex<VoiceCall>>,
+    chat: Arc<Mutex<ChatHistory>>,
+    /// 当前的会话选项（重新连接时再次发送）
+    options: Mutex<OptionMessage>,
+    keyboard: Mutex<KeyboardTranslator>,
+    mouse: Mutex<MouseState>,
+    gesture: Mutex<GestureRecognizer>,
+    touch: Mutex<TouchPassthrough>,
+    viewport: Mutex<Viewport>,
+    input_queue: Arc<Mutex<InputQueue>>,
+    input_notify: Arc<Notify>,
+    receive_task: Option<JoinHandle<()>>,
+    input_task: Option<JoinHandle<()>>,
     password: String,
 }
 
@@ -67,6 +112,7 @@ impl RustDeskConnection {
     /// 创建新的连接配置
     pub fn new(config: RustDeskConfig) -> Self {
         let password = config.password.clone().unwrap_or_default();
+        let options = config.options.clone();
 
         Self {
             config,
@@ -75,6 +121,24 @@ impl RustDeskConnection {
             peer_addr: Arc::new(Mutex::new(None)),
             input_sender: Arc::new(Mutex::new(None)),
             video_receiver: Arc::new(Mutex::new(None)),
+            peer_info: Arc::new(Mutex::new(None)),
+            cursor: Arc::new(Mutex::new(CursorState::new())),
+            clipboard: Arc::new(Mutex::new(ClipboardSync::new())),
+            files: Arc::new(Mutex::new(FileTransfers::new())),
+            file_manager: Arc::new(Mutex::new(FileManager::new())),
+            audio: Arc::new(Mutex::new(AudioPlayback::new())),
+            voice_call: Arc::new(Mutex::new(VoiceCall::new())),
+            chat: Arc::new(Mutex::new(ChatHistory::new())),
+            options: Mutex::new(options),
+            keyboard: Mutex::new(KeyboardTranslator::new()),
+            mouse: Mutex::new(MouseState::new()),
+            gesture: Mutex::new(GestureRecognizer::new()),
+            touch: Mutex::new(TouchPassthrough::new()),
+            viewport: Mutex::new(Viewport::new()),
+            input_queue: Arc::new(Mutex::new(InputQueue::new())),
+            input_notify: Arc::new(Notify::new()),
+            receive_task: None,
+            input_task: None,
             password,
         }
     }
@@ -105,7 +169,7 @@ impl RustDeskConnection {
 
         // 步骤 2: 请求对端信息
         log::info!("步骤 2/5: 请求对端信息...");
-        let peer_addr = match id_client.request_connection(&self.config.desk_id).await {
+        let peer_addr = match id_client.request_connection(&self.config.desk_id, self.config.conn_type).await {
             Ok(addr) => {
                 log::info!("获取到对端地址: {}", addr);
                 addr
@@ -145,7 +209,8 @@ impl RustDeskConnection {
         log::info!("步骤 4/5: 执行安全握手...");
         let mut handshake = SecureHandshake::new();
 
-        if let Err(e) = handshake.perform_handshake(&mut local_socket, peer_addr, &self.password).await {
+        let conn_type = self.config.conn_type;
+        if let Err(e) = handshake.perform_handshake(&mut local_socket, peer_addr, &self.password, conn_type).await {
             log::error!("握手失败: {}", e);
             *self.state.lock().await = ConnectionState::Failed;
             return Err(format!("握手失败: {}", e));
@@ -154,19 +219,64 @@ impl RustDeskConnection {
         // 步骤 5: 建立连接
         log::info!("步骤 5/5: 建立连接...");
 
+        // 登录成功后被控端会发送对端信息（显示器列表等）
+        match Self::wait_peer_info(&local_socket, peer_addr).await {
+            Ok(info) => {
+                log::info!(
+                    "收到对端信息: {}@{} ({}), {} 个显示器",
+                    info.username,
+                    info.hostname,
+                    info.platform,
+                    info.displays.len()
+                );
+                *self.peer_info.lock().await = Some(info);
+            }
+            Err(e) => {
+                log::warn!("未收到对端信息: {}", e);
+            }
+        }
+
         // 存储连接信息
         let socket = Arc::new(local_socket);
         *self.socket.lock().await = Some(socket.clone());
         *self.peer_addr.lock().await = Some(peer_addr);
 
         // 创建输入事件发送器（共享 socket）
-        let input_sender = InputEventSender::new(socket, peer_addr);
+        let input_sender = InputEventSender::new(socket.clone(), peer_addr);
+        // 登录后应用会话选项（重新连接时保持）
+        let options = self.options.lock().await.clone();
+        self.audio.lock().await.set_muted(options.disable_audio == Some(true));
+        if !options.is_empty() {
+            if let Err(e) = input_sender.send_option(&options).await {
+                log::warn!("发送会话选项失败: {}", e);
+            }
+        }
         *self.input_sender.lock().await = Some(input_sender);
+        self.input_task = Some(tokio::spawn(Self::input_loop(
+            self.input_queue.clone(),
+            self.input_notify.clone(),
+            self.input_sender.clone(),
+        )));
 
         // 创建视频流接收器
         let (video_receiver, receiver) = VideoStreamReceiver::new();
         *self.video_receiver.lock().await = Some(receiver);
 
+        // 启动接收任务
+        let dispatcher = PacketDispatcher {
+            video: video_receiver,
+            peer_info: self.peer_info.clone(),
+            cursor: self.cursor.clone(),
+            clipboard: self.clipboard.clone(),
+            files: self.files.clone(),
+            file_manager: self.file_manager.clone(),
+            audio: self.audio.clone(),
+            voice_call: self.voice_call.clone(),
+            chat: self.chat.clone(),
+            input_sender: self.input_sender.clone(),
+        };
+        self.receive_task = Some(tokio::spawn(Self::receive_loop(socket, peer_addr, dispatcher)));
+
         // 更新状态
         *self.state.lock().await = ConnectionState::Connected;
 
@@ -184,6 +294,35 @@ impl RustDeskConnection {
         // 更新状态
         *self.state.lock().await = ConnectionState::Disconnected;
 
+        // 停止接收任务
+        if let Some(task) = self.receive_task.take() {
+            task.abort();
+        }
+
+        // 取消文件传输（未完成的文件保留，下次继续），等待中的文件管理操作失败
+        self.cancel_all_transfers().await;
+        self.file_manager.lock().await.fail_all("连接已断开");
+
+        // 挂断语音通话
+        if let Err(e) = self.end_voice_call().await {
+            log::debug!("{}", e);
+        }
+
+        // 先停止发送任务并等待其结束，再直接发出队列中剩余的输入和抬起按住按键的事件
+        // （发送任务被中止时正在发送的一批事件可能丢失，抬起事件必须在之后发出）
+        if let Some(task) = self.input_task.take() {
+            task.abort();
+            let _ = task.await;
+        }
+        let releases = self.held_input_releases().await;
+        {
+            let mut queue = self.input_queue.lock().await;
+            for command in releases {
+                queue.push(command);
+            }
+        }
+        Self::flush_input(&self.input_queue, &self.input_sender).await;
+
         // 关闭 socket
         let mut socket = self.socket.lock().await;
         *socket = None;
@@ -192,45 +331,687 @@ impl RustDeskConnection {
         *self.peer_addr.lock().await = None;
         *self.input_sender.lock().await = None;
         *self.video_receiver.lock().await = None;
+        *self.peer_info.lock().await = None;
+        *self.cursor.lock().await = CursorState::new();
+        self.audio.lock().await.reset();
 
         log::info!("连接已断开");
         Ok(())
     }
 
-    /// 发送键盘输入
+    /// 发送键盘输入（HarmonyOS KeyCode，按当前键盘模式转换）
     pub async fn send_key_event(&self, key: u32, pressed: bool) -> Result<(), String> {
-        let sender = self.input_sender.lock().await;
-        if let Some(sender) = sender.as_ref() {
-            sender.send_key_event(key, pressed).await
-                .map_err(|e| format!("发送键盘事件失败: {}", e))?;
+        let event = match self.keyboard.lock().await.translate(key, pressed) {
+            Some(event) => event,
+            None => return Ok(()),
+        };
+
+        self.queue_input(InputCommand::Key(event)).await;
+        Ok(())
+    }
+
+    /// 发送文本输入（软键盘 / 输入法上屏内容）
+    pub async fn send_text(&self, text: &str) -> Result<(), String> {
+        let mode = self.keyboard.lock().await.mode();
+        for event in keyboard::text_events(text, mode) {
+            self.queue_input(InputCommand::Key(event)).await;
+        }
+        Ok(())
+    }
+
+    /// 发送特殊操作（Ctrl+Alt+Del、锁屏、Android 导航等）
+    pub async fn send_special_action(&self, action: SpecialAction) -> Result<(), String> {
+        let platform = self.peer_info.lock().await
+            .as_ref()
+            .map(|info| info.platform.clone())
+            .unwrap_or_default();
+        log::info!("特殊操作: {:?} (被控端: {})", action, platform);
+
+        if !action.is_mobile() {
+            let mode = self.keyboard.lock().await.mode();
+            if let Some(event) = action.key_event(&platform, mode) {
+                self.queue_input(InputCommand::Key(event)).await;
+            }
+            return Ok(());
+        }
+
+        // Android 被控端：右键为返回，中键单击为主页，长按中键为最近任务
+        if !platform.eq_ignore_ascii_case("android") {
+            return Err(format!("{:?} 仅支持 Android 被控端", action));
+        }
+        let button = match action {
+            SpecialAction::MobileBack => MouseButton::Right,
+            _ => MouseButton::Middle,
+        };
+        let (x, y) = self.mouse.lock().await.position();
+        let down = MouseEvent::new(MouseEventType::Down, button as u8, x, y);
+        let up = InputCommand::Mouse(MouseEvent::new(MouseEventType::Up, button as u8, x, y));
+        self.queue_input(InputCommand::Mouse(down)).await;
+
+        if action == SpecialAction::MobileRecents {
+            if self.input_task.is_none() {
+                return Ok(());
+            }
+            // 延迟抬起，不阻塞调用方
+            let queue = self.input_queue.clone();
+            let notify = self.input_notify.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(MOBILE_LONG_PRESS).await;
+                queue.lock().await.push(up);
+                notify.notify_one();
+            });
+        } else {
+            self.queue_input(up).await;
         }
         Ok(())
     }
 
-    /// 发送鼠标移动
+    /// 设置键盘模式
+    pub async fn set_keyboard_mode(&self, mode: KeyboardMode) {
+        log::info!("键盘模式: {:?}", mode);
+        self.keyboard.lock().await.set_mode(mode);
+    }
+
+    /// 发送鼠标移动（视图坐标，视口未设置时按远程坐标处理）
     pub async fn send_mouse_move(&self, x: i32, y: i32) -> Result<(), String> {
+        let (x, y) = self.viewport().await
+            .view_to_remote(x as f32, y as f32)
+            .unwrap_or((x, y));
+        self.send_mouse_move_remote(x, y).await
+    }
+
+    /// 发送鼠标移动（远程桌面坐标，不经过视口换算）
+    pub async fn send_mouse_move_remote(&self, x: i32, y: i32) -> Result<(), String> {
+        let event = self.mouse.lock().await.move_to(x, y);
+        self.send_mouse_event(event).await;
+        Ok(())
+    }
+
+    /// 发送相对鼠标移动（远程像素位移，需先开启相对鼠标模式）
+    pub async fn send_mouse_relative(&self, dx: i32, dy: i32) -> Result<(), String> {
+        let event = {
+            let mut mouse = self.mouse.lock().await;
+            if !mouse.is_relative() {
+                return Err("未开启相对鼠标模式".to_string());
+            }
+            mouse.move_by(dx as f32, dy as f32)
+        };
+        if let Some(event) = event {
+            self.send_mouse_event(event).await;
+        }
+        Ok(())
+    }
+
+    /// 开启 / 关闭相对鼠标模式
+    pub async fn set_relative_mouse(&self, enabled: bool) {
+        log::info!("相对鼠标模式: {}", enabled);
+        self.mouse.lock().await.set_relative(enabled);
+    }
+
+    /// 发送鼠标点击（0 左键，1 中键，2 右键，3 后退，4 前进）
+    pub async fn send_mouse_click(&self, button: u32, pressed: bool) -> Result<(), String> {
+        let button = MouseButton::from_index(button)
+            .ok_or_else(|| format!("未知鼠标按键: {}", button))?;
+        let event = self.mouse.lock().await.button(button, pressed);
+        self.send_mouse_event(event).await;
+        Ok(())
+    }
+
+    /// 发送鼠标滚轮（dx 水平，dy 垂直）
+    pub async fn send_mouse_wheel(&self, dx: i32, dy: i32) -> Result<(), String> {
+        let event = self.mouse.lock().await.wheel(dx, dy);
+        self.send_mouse_event(event).await;
+        Ok(())
+    }
+
+    /// 发送触摸事件（视图坐标，识别为鼠标手势），返回当前视口状态
+    pub async fn send_touch(
+        &self,
+        points: &[TouchPoint],
+        action: TouchAction,
+    ) -> Result<ViewportState, String> {
+        if self.touch_passthrough().await {
+            let events = {
+                let viewport = self.viewport().await;
+                self.touch.lock().await.handle(points, action, |x, y| {
+                    viewport.view_to_remote(x, y).unwrap_or((x as i32, y as i32))
+                })
+            };
+            for event in events {
+                self.queue_input(InputCommand::Touch(event)).await;
+            }
+        } else {
+            let actions = self.gesture.lock().await.handle(points, action);
+            self.apply_gestures(actions).await?;
+        }
+        Ok(self.viewport().await.state())
+    }
+
+    /// 是否直接转发触摸（被控端支持触摸，或上一次触摸尚未结束）
+    async fn touch_passthrough(&self) -> bool {
+        if self.touch.lock().await.is_active() {
+            return true;
+        }
+        self.peer_info.lock().await
+            .as_ref()
+            .is_some_and(PeerInfo::supports_touch)
+    }
+
+    /// 设置触摸模式
+    pub async fn set_touch_mode(&self, mode: TouchMode) -> Result<(), String> {
+        log::info!("触摸模式: {:?}", mode);
+        let actions = self.gesture.lock().await.set_mode(mode);
+        self.apply_gestures(actions).await
+    }
+
+    /// 把手势识别结果映射到远程坐标并转换为鼠标事件发送
+    async fn apply_gestures(&self, actions: Vec<GestureAction>) -> Result<(), String> {
+        for action in actions {
+            let event = {
+                let mut viewport = self.viewport().await;
+                let mut mouse = self.mouse.lock().await;
+                match action {
+                    GestureAction::Move { x, y } => {
+                        let (x, y) = viewport
+                            .view_to_remote(x, y)
+                            .unwrap_or((x as i32, y as i32));
+                        mouse.move_to(x, y)
+                    }
+                    // 相对鼠标模式下触控板移动直接作为位移发送
+                    GestureAction::MoveBy { dx, dy } if mouse.is_relative() => {
+                        let (dx, dy) = viewport.scale_delta(dx, dy);
+                        match mouse.move_by(dx, dy) {
+                            Some(event) => event,
+                            None => continue,
+                        }
+                    }
+                    GestureAction::MoveBy { dx, dy } => {
+                        let (x, y) = mouse.position();
+                        let (x, y) = viewport
+                            .move_remote_by((x, y), dx, dy)
+                            .unwrap_or((x + dx as i32, y + dy as i32));
+                        mouse.move_to(x, y)
+                    }
+                    GestureAction::Button { button, down } => mouse.button(button, down),
+                    GestureAction::Wheel { dx, dy } => mouse.wheel(dx, dy),
+                    // 缩放只影响本地显示
+                    GestureAction::Zoom { factor, center_x, center_y } => {
+                        viewport.zoom_by(factor, center_x, center_y);
+                        continue;
+                    }
+                }
+            };
+            self.send_mouse_event(event).await;
+        }
+        Ok(())
+    }
+
+    /// 设置视图尺寸和适配模式
+    pub async fn set_viewport(&self, width: f32, height: f32, fit: FitMode) {
+        let mut viewport = self.viewport().await;
+        viewport.set_view_size(width, height);
+        viewport.set_fit_mode(fit);
+    }
+
+    /// 以视图坐标为中心设置缩放倍数
+    pub async fn set_view_zoom(&self, zoom: f32, center_x: f32, center_y: f32) -> ViewportState {
+        let mut viewport = self.viewport().await;
+        viewport.set_zoom(zoom, center_x, center_y);
+        viewport.state()
+    }
+
+    /// 平移画面（视图像素）
+    pub async fn pan_view(&self, dx: f32, dy: f32) -> ViewportState {
+        let mut viewport = self.viewport().await;
+        viewport.pan_by(dx, dy);
+        viewport.state()
+    }
+
+    /// 当前视口状态
+    pub async fn viewport_state(&self) -> ViewportState {
+        self.viewport().await.state()
+    }
+
+    /// 获取视口，并同步当前显示器的位置和分辨率
+    async fn viewport(&self) -> MutexGuard<'_, Viewport> {
+        let mut viewport = self.viewport.lock().await;
+        if let Some(display) = self.peer_info.lock().await.as_ref().and_then(|info| info.current()) {
+            viewport.set_display(display);
+        }
+        viewport
+    }
+
+    /// 附加当前修饰键后发送鼠标事件（Ctrl + 点击等）
+    async fn send_mouse_event(&self, mut event: MouseEvent) {
+        event.modifiers = self.keyboard.lock().await.modifiers().to_control_keys();
+        self.queue_input(InputCommand::Mouse(event)).await;
+    }
+
+    /// 抬起所有按住的按键、修饰键和鼠标按键（应用失去焦点时调用）
+    pub async fn release_all_inputs(&self) {
+        for command in self.held_input_releases().await {
+            self.queue_input(command).await;
+        }
+    }
+
+    /// 清空按键、鼠标和触摸状态，返回抬起按住的输入的事件
+    async fn held_input_releases(&self) -> Vec<InputCommand> {
+        let keys = self.keyboard.lock().await.release_all();
+        // 取消进行中的拖拽手势，左键由鼠标状态统一抬起
+        self.gesture.lock().await.cancel();
+        let buttons = self.mouse.lock().await.release_all();
+        let touches = self.touch.lock().await.cancel();
+        if !keys.is_empty() || !buttons.is_empty() || !touches.is_empty() {
+            log::info!(
+                "释放按住的输入: {} 个按键, {} 个鼠标按键, {} 个触摸事件",
+                keys.len(),
+                buttons.len(),
+                touches.len()
+            );
+        }
+
+        let keys = keys.into_iter().map(InputCommand::Key);
+        let buttons = buttons.into_iter().map(InputCommand::Mouse);
+        let touches = touches.into_iter().map(InputCommand::Touch);
+        keys.chain(buttons).chain(touches).collect()
+    }
+
+    /// 本地剪贴板变化时同步到对端，返回是否已发送（同步关闭或回声时不发送）
+    pub async fn set_local_clipboard(&self, data: ClipboardData) -> Result<bool, String> {
+        for item in &data.items {
+            if item.size > item.format.max_len() {
+                return Err(format!("剪贴板内容过长: {} {} 字节", item.format.mime(), item.size));
+            }
+        }
+        let sender = self.input_sender.lock().await;
+        let sender = match sender.as_ref() {
+            Some(sender) => sender,
+            None => return Err("连接未建立".to_string()),
+        };
+        let announce = match self.clipboard.lock().await.local_changed(data) {
+            Some(announce) => announce,
+            None => return Ok(false),
+        };
+        sender
+            .send_clipboard(&announce)
+            .await
+            .map_err(|e| format!("发送剪贴板失败: {}", e))?;
+        Ok(true)
+    }
+
+    /// 取出对端剪贴板的最新变化（变化后的格式，没有变化时返回 None）
+    pub async fn take_remote_clipboard_formats(&self) -> Option<Vec<ClipboardFormat>> {
+        self.clipboard.lock().await.take_remote_formats()
+    }
+
+    /// 读取对端剪贴板的指定格式，延迟传输的内容尚未到达时返回 None（首次读取时向对端请求）
+    pub async fn read_remote_clipboard(&self, format: ClipboardFormat) -> Result<Option<Vec<u8>>, String> {
+        let read = self.clipboard.lock().await.read_remote(format);
+        match read {
+            RemoteRead::Ready(content) => Ok(Some(content)),
+            RemoteRead::Pending => Ok(None),
+            RemoteRead::Unavailable => Err(format!("远程剪贴板没有 {}", format.mime())),
+            RemoteRead::Fetch => {
+                let sender = self.input_sender.lock().await;
+                let result = match sender.as_ref() {
+                    Some(sender) => sender
+                        .send_clipboard_request(format)
+                        .await
+                        .map_err(|e| format!("请求剪贴板失败: {}", e)),
+                    None => Err("连接未建立".to_string()),
+                };
+                if result.is_err() {
+                    self.clipboard.lock().await.cancel_request(format);
+                }
+                result.map(|_| None)
+            }
+        }
+    }
+
+    /// 开启 / 关闭剪贴板同步
+    pub async fn set_clipboard_sync(&self, enabled: bool) {
+        self.clipboard.lock().await.set_enabled(enabled);
+    }
+
+    /// 上传本地文件或目录到对端路径，返回传输任务 ID
+    pub async fn start_upload(&self, local: &str, remote: &str) -> Result<i32, String> {
+        if self.input_sender.lock().await.is_none() {
+            return Err("连接未建立".to_string());
+        }
+        let local = std::path::PathBuf::from(local);
+        let files = file_transfer::scan(&local, true).map_err(|e| format!("读取本地文件失败: {}", e))?;
+
+        let (confirm_tx, confirm_rx) = mpsc::unbounded_channel();
+        let mut transfers = self.files.lock().await;
+        let id = transfers.start_upload(local.clone(), files.clone(), confirm_tx);
+        let upload = Upload { id, local, remote: Some(remote.to_string()), files, rate: transfers.rate() };
+        log::info!("开始上传: {} -> {} ({} 项)", upload.local.display(), remote, upload.files.len());
+        let task = tokio::spawn(file_transfer::run_upload(
+            upload,
+            confirm_rx,
+            self.files.clone(),
+            self.input_sender.clone(),
+        ));
+        transfers.set_task(id, task);
+        Ok(id)
+    }
+
+    /// 从对端下载文件或目录到本地路径，返回传输任务 ID
+    pub async fn start_download(&self, remote: &str, local: &str, include_hidden: bool) -> Result<i32, String> {
+        let sender = self.input_sender.lock().await;
+        let sender = match sender.as_ref() {
+            Some(sender) => sender,
+            None => return Err("连接未建立".to_string()),
+        };
+        let mut transfers = self.files.lock().await;
+        let id = transfers.start_download(std::path::PathBuf::from(local));
+        let action = FileAction::Send { id, path: remote.to_string(), include_hidden, rate: transfers.rate() };
+        if let Err(e) = sender.send_file_action(&action).await {
+            let error = format!("发送下载请求失败: {}", e);
+            transfers.finish(id, Err(error.clone()));
+            return Err(error);
+        }
+        log::info!("开始下载: {} -> {}", remote, local);
+        Ok(id)
+    }
+
+    /// 取消传输任务，返回任务是否存在
+    pub async fn cancel_transfer(&self, id: i32) -> bool {
+        if !self.files.lock().await.cancel(id) {
+            return false;
+        }
+        if let Some(sender) = self.input_sender.lock().await.as_ref() {
+            if let Err(e) = sender.send_file_action(&FileAction::Cancel { id }).await {
+                log::warn!("发送取消传输失败: {}", e);
+            }
+        }
+        true
+    }
+
+    /// 取消全部传输任务
+    pub async fn cancel_all_transfers(&self) {
+        let ids = self.files.lock().await.cancel_all();
+        for id in ids {
+            if let Some(sender) = self.input_sender.lock().await.as_ref() {
+                let _ = sender.send_file_action(&FileAction::Cancel { id }).await;
+            }
+        }
+    }
+
+    /// 设置本地剪贴板中的文件（对端粘贴时允许请求这些文件）
+    pub async fn set_shared_files(&self, paths: Vec<std::path::PathBuf>) {
+        self.files.lock().await.set_shared(paths);
+    }
+
+    /// 设置文件传输限速（字节/秒，0 不限速，对之后开始的任务生效）
+    pub async fn set_transfer_rate(&self, rate: u32) {
+        self.files.lock().await.set_rate(rate);
+    }
+
+    /// 取出文件传输事件
+    pub async fn take_transfer_events(&self) -> Vec<TransferEvent> {
+        self.files.lock().await.take_events()
+    }
+
+    /// 对端的音频格式（尚未收到音频时为 None）
+    pub async fn remote_audio_format(&self) -> Option<AudioFormat> {
+        self.audio.lock().await.format()
+    }
+
+    /// 拉取 frames 帧远程音频 PCM（16 位交错，不足部分为静音）
+    pub async fn read_remote_audio(&self, frames: usize) -> Option<Vec<i16>> {
+        self.audio.lock().await.read(frames)
+    }
+
+    /// 静音 / 取消静音，并通知对端停止 / 恢复发送音频
+    pub async fn set_audio_muted(&self, muted: bool) -> Result<(), String> {
+        let option = OptionMessage { disable_audio: Some(muted), ..Default::default() };
+        self.set_session_option(&option).await
+    }
+
+    /// 当前的会话选项
+    pub async fn session_options(&self) -> OptionMessage {
+        self.options.lock().await.clone()
+    }
+
+    /// 修改会话选项（未连接时在登录后发送）
+    pub async fn set_session_option(&self, option: &OptionMessage) -> Result<(), String> {
+        self.options.lock().await.merge(option);
+        if let Some(disable_audio) = option.disable_audio {
+            self.audio.lock().await.set_muted(disable_audio);
+        }
+        let sender = self.input_sender.lock().await;
+        let sender = match sender.as_ref() {
+            Some(sender) => sender,
+            None => return Ok(()),
+        };
+        sender
+            .send_option(option)
+            .await
+            .map_err(|e| format!("发送会话选项失败: {}", e))
+    }
+
+    /// 发起语音通话，或接听对端的来电
+    pub async fn start_voice_call(&self) -> Result<(), String> {
+        let sender = self.input_sender.lock().await;
+        let sender = sender.as_ref().ok_or("连接未建立")?;
+        let req_timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+        let messages = self.voice_call.lock().await.start(req_timestamp)?;
+        send_voice_call_messages(sender, messages)
+            .await
+            .map_err(|e| format!("发送语音通话请求失败: {}", e))
+    }
+
+    /// 挂断、取消发起或拒绝来电
+    pub async fn end_voice_call(&self) -> Result<(), String> {
+        let messages = self.voice_call.lock().await.end();
+        if messages.is_empty() {
+            return Ok(());
+        }
+        let sender = self.input_sender.lock().await;
+        let sender = sender.as_ref().ok_or("连接未建立")?;
+        send_voice_call_messages(sender, messages)
+            .await
+            .map_err(|e| format!("发送挂断请求失败: {}", e))
+    }
+
+    /// 语音通话状态
+    pub async fn voice_call_state(&self) -> VoiceCallState {
+        self.voice_call.lock().await.state()
+    }
+
+    /// 编码并发送麦克风 PCM（16 kHz 单声道，只在通话中）
+    pub async fn push_microphone_pcm(&self, pcm: &[i16]) -> Result<(), String> {
+        let frames = self.voice_call.lock().await.encode(pcm)?;
+        let sender = self.input_sender.lock().await;
+        let sender = sender.as_ref().ok_or("连接未建立")?;
+        for frame in frames {
+            sender
+                .send_audio_frame(&frame)
+                .await
+                .map_err(|e| format!("发送麦克风音频失败: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// 发送聊天消息，返回记入聊天记录的消息
+    pub async fn send_chat_message(&self, text: &str) -> Result<ChatMessage, String> {
+        let text = ChatHistory::prepare(text)?;
+        let sender = self.input_sender.lock().await;
+        let sender = sender.as_ref().ok_or("连接未建立")?;
+        sender
+            .send_chat_message(&text)
+            .await
+            .map_err(|e| format!("发送聊天消息失败: {}", e))?;
+        Ok(self.chat.lock().await.add_sent(text))
+    }
+
+    /// 聊天记录（按时间顺序）
+    pub async fn chat_history(&self) -> Vec<ChatMessage> {
+        self.chat.lock().await.messages()
+    }
+
+    /// 取出上次之后收到的聊天消息
+    pub async fn take_chat_messages(&self) -> Vec<ChatMessage> {
+        self.chat.lock().await.take_incoming()
+    }
+
+    /// 发起远程文件管理操作，返回操作 ID（结果由 take_file_results 取出）
+    pub async fn request_file_operation(&self, operation: FileOperation) -> Result<i32, String> {
+        let sender = self.input_sender.lock().await;
+        let sender = sender.as_ref().ok_or("连接未建立")?;
+        let id = self.files.lock().await.next_id();
+        let mut manager = self.file_manager.lock().await;
+        let action = manager.request(id, operation)?;
+        if let Err(e) = sender.send_file_action(&action).await {
+            manager.cancel(id);
+            return Err(format!("发送文件操作失败: {}", e));
+        }
+        Ok(id)
+    }
+
+    /// 取出远程文件管理操作的结果
+    pub async fn take_file_results(&self) -> Vec<OperationResult> {
+        self.file_manager.lock().await.take_results()
+    }
+
+    /// 最近一次读取的远程目录列表
+    pub async fn remote_dir_listing(&self) -> Option<DirListing> {
+        self.file_manager.lock().await.listing().cloned()
+    }
+
+    /// 设置输入发送频率（Hz，0 表示不合并、立即发送）
+    pub async fn set_input_rate(&self, rate: u32) {
+        let mut queue = self.input_queue.lock().await;
+        queue.set_rate(rate);
+        log::info!("输入发送频率: {} Hz", queue.rate());
+    }
+
+    /// 加入输入队列，由发送任务按频率合并发送
+    async fn queue_input(&self, command: InputCommand) {
+        if self.input_task.is_none() {
+            return;
+        }
+        self.input_queue.lock().await.push(command);
+        self.input_notify.notify_one();
+    }
+
+    /// 输入发送任务：按键立即发送，鼠标移动最多按设定频率发送
+    async fn input_loop(
+        queue: Arc<Mutex<InputQueue>>,
+        notify: Arc<Notify>,
+        sender: Arc<Mutex<Option<InputEventSender>>>,
+    ) {
+        let mut last_flush = Instant::now();
+        loop {
+            notify.notified().await;
+
+            // 只有鼠标移动时等到下一个发送时刻，期间的移动会被合并
+            loop {
+                let wait = {
+                    let queue = queue.lock().await;
+                    match queue.flush_interval() {
+                        Some(interval) if !queue.is_urgent() => {
+                            interval.saturating_sub(last_flush.elapsed())
+                        }
+                        _ => Duration::ZERO,
+                    }
+                };
+                if wait.is_zero() {
+                    break;
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => break,
+                    _ = notify.notified() => continue,
+                }
+            }
+
+            Self::flush_input(&queue, &sender).await;
+            last_flush = Instant::now();
+        }
+    }
+
+    /// 发送队列中的全部输入事件
+    async fn flush_input(queue: &Mutex<InputQueue>, sender: &Mutex<Option<InputEventSender>>) {
+        let commands = queue.lock().await.drain();
+        if commands.is_empty() {
+            return;
+        }
+
+        let sender = sender.lock().await;
+        let sender = match sender.as_ref() {
+            Some(sender) => sender,
+            None => return,
+        };
+        for command in &commands {
+            let result = match command {
+                InputCommand::Key(event) => sender.send_key_event(event).await,
+                InputCommand::Mouse(event) => sender.send_mouse_event(event).await,
+                InputCommand::Touch(event) => sender.send_touch_event(event).await,
+            };
+            if let Err(e) = result {
+                log::warn!("发送输入事件失败: {}", e);
+            }
+        }
+    }
+
+    /// 切换到指定显示器（新视频流开始时解码器会自动重置）
+    pub async fn switch_display(&self, display: usize) -> Result<(), String> {
+        let count = self.peer_info.lock().await
+            .as_ref()
+            .map(|info| info.displays.len())
+            .unwrap_or(0);
+        if display >= count {
+            return Err(format!("显示器索引无效: {} (共 {} 个)", display, count));
+        }
+
         let sender = self.input_sender.lock().await;
         if let Some(sender) = sender.as_ref() {
-            sender.send_mouse_move(x, y).await
-                .map_err(|e| format!("发送鼠标移动失败: {}", e))?;
+            sender.send_switch_display(display).await
+                .map_err(|e| format!("切换显示器失败: {}", e))?;
         }
+        log::info!("请求切换到显示器 {}", display);
         Ok(())
     }
 
-    /// 发送鼠标点击
-    pub async fn send_mouse_click(&self, button: u32, pressed: bool) -> Result<(), String> {
+    /// 同时采集多个显示器（被控端为每个显示器发送一路视频流）
+    pub async fn capture_displays(&self, displays: &[usize]) -> Result<(), String> {
+        let count = self.peer_info.lock().await
+            .as_ref()
+            .map(|info| info.displays.len())
+            .unwrap_or(0);
+        if displays.is_empty() {
+            return Err("至少需要采集一个显示器".to_string());
+        }
+        if let Some(invalid) = displays.iter().find(|&&display| display >= count) {
+            return Err(format!("显示器索引无效: {} (共 {} 个)", invalid, count));
+        }
+
         let sender = self.input_sender.lock().await;
         if let Some(sender) = sender.as_ref() {
-            sender.send_mouse_click(button, pressed).await
-                .map_err(|e| format!("发送鼠标点击失败: {}", e))?;
+            sender.send_capture_displays(displays).await
+                .map_err(|e| format!("采集多显示器失败: {}", e))?;
         }
+        log::info!("请求采集显示器: {:?}", displays);
         Ok(())
     }
 
-    /// 获取远程屏幕尺寸（简化实现，实际应从协议获取）
-    pub fn get_remote_screen_size(&self) -> Result<(u32, u32), String> {
-        // TODO: 从视频流配置中获取实际尺寸
-        Ok((1920, 1080))
+    /// 获取远程屏幕尺寸（当前显示器）
+    pub async fn get_remote_screen_size(&self) -> Result<(u32, u32), String> {
+        let peer_info = self.peer_info.lock().await;
+        peer_info
+            .as_ref()
+            .and_then(|info| info.current())
+            .map(|display| (display.width, display.height))
+            .ok_or_else(|| "尚未收到对端显示器信息".to_string())
+    }
+
+    /// 获取对端信息
+    pub async fn peer_info(&self) -> Option<PeerInfo> {
+        self.peer_info.lock().await.clone()
     }
 
     /// 获取连接状态
@@ -238,18 +1019,311 @@ impl RustDeskConnection {
         *self.state.lock().await
     }
 
-    /// 获取视频帧接收器
-    pub async fn get_video_receiver(&self) -> Option<mpsc::Receiver<VideoFrame>> {
-        // 注意：这里不能直接返回，因为 Receiver 不能 clone
-        // 实际实现需要不同的架构
-        None
+    /// 获取远程光标状态
+    pub fn cursor(&self) -> Arc<Mutex<CursorState>> {
+        self.cursor.clone()
+    }
+
+    /// 取出视频流事件接收器（Receiver 不能 clone，只能由一个视频流消费）
+    pub async fn take_video_receiver(&self) -> Option<mpsc::Receiver<VideoStreamEvent>> {
+        self.video_receiver.lock().await.take()
     }
+
+    /// 等待被控端发送对端信息
+    async fn wait_peer_info(
+        socket: &UdpSocket,
+        peer_addr: std::net::SocketAddr,
+    ) -> Result<PeerInfo, ProtocolError> {
+        let mut buf = vec![0u8; 4096];
+        let wait = async {
+            loop {
+                let (n, addr) = socket.recv_from(&mut buf).await?;
+                if addr != peer_addr {
+                    continue;
+                }
+                match Packet::deserialize(&buf[..n]) {
+                    Ok(packet) if packet.msg_type == MessageType::PeerInfo => {
+                        return PeerInfo::from_payload(&packet.payload);
+                    }
+                    Ok(packet) => {
+                        log::debug!("等待对端信息时忽略消息: {:?}", packet.msg_type);
+                    }
+                    Err(e) => {
+                        log::debug!("等待对端信息时忽略无效数据包: {}", e);
+                    }
+                }
+            }
+        };
+
+        tokio::time::timeout(Duration::from_secs(PEER_INFO_TIMEOUT_SECS), wait)
+            .await
+            .map_err(|_| ProtocolError::Timeout)?
+    }
+
+    /// 接收循环：读取对端数据包并分发
+    async fn receive_loop(
+        socket: Arc<UdpSocket>,
+        peer_addr: std::net::SocketAddr,
+        dispatcher: PacketDispatcher,
+    ) {
+        let mut buf = vec![0u8; 65536];
+        loop {
+            let (n, addr) = match socket.recv_from(&mut buf).await {
+                Ok(result) => result,
+                Err(e) => {
+                    log::error!("接收数据失败: {}", e);
+                    break;
+                }
+            };
+            if addr != peer_addr {
+                continue;
+            }
+
+            let packet = match Packet::deserialize(&buf[..n]) {
+                Ok(packet) => packet,
+                Err(e) => {
+                    log::debug!("忽略无效数据包: {}", e);
+                    continue;
+                }
+            };
+
+            if !dispatcher.dispatch(packet).await {
+                break;
+            }
+        }
+        log::info!("接收任务已结束");
+    }
+}
+
+/// 等待对端信息的超时时间（秒）
+const PEER_INFO_TIMEOUT_SECS: u64 = 5;
+
+/// 数据包分发器（接收任务持有的会话状态）
+struct PacketDispatcher {
+    video: VideoStreamReceiver,
+    peer_info: Arc<Mutex<Option<PeerInfo>>>,
+    cursor: Arc<Mutex<CursorState>>,
+    clipboard: Arc<Mutex<ClipboardSync>>,
+    files: Arc<Mutex<FileTransfers>>,
+    file_manager: Arc<Mutex<FileManager>>,
+    audio: Arc<Mutex<AudioPlayback>>,
+    voice_call: Arc<Mutex<VoiceCall>>,
+    chat: Arc<Mutex<ChatHistory>>,
+    input_sender: Arc<Mutex<Option<InputEventSender>>>,
+}
+
+/// 发送语音通话消息
+async fn send_voice_call_messages(
+    sender: &InputEventSender,
+    messages: Vec<VoiceCallMessage>,
+) -> Result<(), ProtocolError> {
+    for message in messages {
+        match message {
+            VoiceCallMessage::Request(request) => sender.send_voice_call_request(request).await?,
+            VoiceCallMessage::Response(response) => sender.send_voice_call_response(response).await?,
+            VoiceCallMessage::Format(format) => sender.send_audio_format(format).await?,
+        }
+    }
+    Ok(())
 }
 
+impl PacketDispatcher {
+    /// 分发单个数据包，返回 false 表示对端已断开
+    async fn dispatch(&self, packet: Packet) -> bool {
+        match packet.msg_type {
+            MessageType::PeerInfo => match PeerInfo::from_payload(&packet.payload) {
+                Ok(info) => {
+                    log::info!("对端信息更新: {} 个显示器", info.displays.len());
+                    *self.peer_info.lock().await = Some(info);
+                }
+                Err(e) => log::warn!("解析对端信息失败: {}", e),
+            },
+            MessageType::VideoConfig => {
+                // 新视频流开始，记录当前显示器
+                if let Ok(config) = protocol::VideoConfig::from_payload(&packet.payload) {
+                    if let Some(info) = self.peer_info.lock().await.as_mut() {
+                        info.current_display = config.display;
+                    }
+                }
+                if let Err(e) = self.video.handle_packet(&packet) {
+                    log::warn!("处理视频配置失败: {}", e);
+                }
+            }
+            MessageType::VideoFrame => {
+                if let Err(e) = self.video.handle_packet(&packet) {
+                    log::warn!("处理视频帧失败: {}", e);
+                }
+            }
+            MessageType::CursorData => match CursorData::from_payload(&packet.payload) {
+                Ok(data) => self.cursor.lock().await.update_data(data),
+                Err(e) => log::warn!("解析光标图像失败: {}", e),
+            },
+            MessageType::CursorId => match protocol::parse_cursor_id(&packet.payload) {
+                Ok(id) => self.cursor.lock().await.set_current(id),
+                Err(e) => log::warn!("解析光标 ID 失败: {}", e),
+            },
+            MessageType::CursorPosition => match protocol::parse_cursor_position(&packet.payload) {
+                Ok((x, y)) => self.cursor.lock().await.set_position(x, y),
+                Err(e) => log::warn!("解析光标位置失败: {}", e),
+            },
+            MessageType::ClipboardEvent => match ClipboardData::from_payload(&packet.payload) {
+                Ok(data) => {
+                    let formats: Vec<_> = data.items.iter().map(|item| item.format.mime()).collect();
+                    if self.clipboard.lock().await.remote_changed(data) {
+                        log::debug!("远程剪贴板已更新: {:?}", formats);
+                    }
+                }
+                Err(e) => log::warn!("解析剪贴板失败: {}", e),
+            },
+            MessageType::ClipboardRequest => match protocol::parse_clipboard_request(&packet.payload) {
+                Ok(format) => self.respond_clipboard(format).await,
+                Err(e) => log::warn!("解析剪贴板请求失败: {}", e),
+            },
+            MessageType::ClipboardResponse => match ClipboardResponse::from_payload(&packet.payload) {
+                Ok(response) => {
+                    let format = response.format();
+                    if self.clipboard.lock().await.remote_response_received(response) {
+                        log::debug!("远程剪贴板内容已到达: {}", format.mime());
+                    }
+                }
+                Err(e) => log::warn!("解析剪贴板内容失败: {}", e),
+            },
+            MessageType::FileAction => match FileAction::from_payload(&packet.payload) {
+                Ok(FileAction::Send { id, path, include_hidden, rate }) => {
+                    self.serve_files(id, path, include_hidden, rate).await
+                }
+                Ok(action) => self.files.lock().await.handle_action(action),
+                Err(e) => log::warn!("解析文件操作失败: {}", e),
+            },
+            MessageType::FileResponse => match FileResponse::from_payload(&packet.payload) {
+                Ok(response) => {
+                    // 文件管理操作的回应，其余属于传输任务
+                    let response = match self.file_manager.lock().await.handle_response(response) {
+                        Some(response) => response,
+                        None => return true,
+                    };
+                    let reply = file_transfer::handle_response(&self.files, response).await;
+                    if let (Some(action), Some(sender)) = (reply, self.input_sender.lock().await.as_ref()) {
+                        if let Err(e) = sender.send_file_action(&action).await {
+                            log::warn!("回复文件传输失败: {}", e);
+                        }
+                    }
+                }
+                Err(e) => log::warn!("解析文件传输数据失败: {}", e),
+            },
+            MessageType::AudioFormat => match AudioFormat::from_payload(&packet.payload) {
+                Ok(format) => {
+                    if let Err(e) = self.audio.lock().await.set_format(format) {
+                        log::warn!("{}", e);
+                    }
+                }
+                Err(e) => log::warn!("解析音频格式失败: {}", e),
+            },
+            MessageType::AudioFrame => {
+                if let Err(e) = self.audio.lock().await.push_frame(&packet.payload) {
+                    log::debug!("丢弃音频帧: {}", e);
+                }
+            }
+            MessageType::VoiceCallRequest => match VoiceCallRequest::from_payload(&packet.payload) {
+                Ok(request) => {
+                    let messages = self.voice_call.lock().await.handle_request(request);
+                    self.reply_voice_call(messages).await;
+                }
+                Err(e) => log::warn!("解析语音通话请求失败: {}", e),
+            },
+            MessageType::VoiceCallResponse => match VoiceCallResponse::from_payload(&packet.payload) {
+                Ok(response) => {
+                    let messages = self.voice_call.lock().await.handle_response(response);
+                    self.reply_voice_call(messages).await;
+                }
+                Err(e) => log::warn!("解析语音通话回应失败: {}", e),
+            },
+            MessageType::ChatMessage => match protocol::parse_chat_message(&packet.payload) {
+                Ok(text) => {
+                    log::debug!("收到聊天消息: {} 字节", text.len());
+                    self.chat.lock().await.add_received(text);
+                }
+                Err(e) => log::warn!("解析聊天消息失败: {}", e),
+            },
+            MessageType::Disconnect => {
+                log::info!("对端已断开连接");
+                return false;
+            }
+            other => {
+                log::debug!("未处理的消息类型: {:?}", other);
+            }
+        }
+        true
+    }
+
+    /// 回复语音通话消息
+    async fn reply_voice_call(&self, messages: Vec<VoiceCallMessage>) {
+        if messages.is_empty() {
+            return;
+        }
+        if let Some(sender) = self.input_sender.lock().await.as_ref() {
+            if let Err(e) = send_voice_call_messages(sender, messages).await {
+                log::warn!("回复语音通话失败: {}", e);
+            }
+        }
+    }
+
+    /// 回应对端的剪贴板请求（分块传输延迟的本地内容）
+    async fn respond_clipboard(&self, format: ClipboardFormat) {
+        let item = match self.clipboard.lock().await.local_item(format) {
+            Some(item) => item,
+            None => {
+                log::warn!("本地剪贴板没有 {}，忽略请求", format.mime());
+                return;
+            }
+        };
+        if let Some(sender) = self.input_sender.lock().await.as_ref() {
+            for response in ClipboardResponse::split(&item) {
+                if let Err(e) = sender.send_clipboard_response(&response).await {
+                    log::warn!("发送剪贴板内容失败: {}", e);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// 对端粘贴本地复制的文件：按对端的请求上传（只允许剪贴板中的文件）
+    async fn serve_files(&self, id: i32, path: String, include_hidden: bool, rate: u32) {
+        let (confirm_tx, confirm_rx) = mpsc::unbounded_channel();
+        let served = {
+            let mut transfers = self.files.lock().await;
+            transfers.serve(id, &path, include_hidden, rate, confirm_tx).map(|upload| {
+                log::info!("对端请求剪贴板文件: {} ({} 项)", path, upload.files.len());
+                let task = tokio::spawn(file_transfer::run_upload(
+                    upload,
+                    confirm_rx,
+                    self.files.clone(),
+                    self.input_sender.clone(),
+                ));
+                transfers.set_task(id, task);
+            })
+        };
+        if let Err(message) = served {
+            log::warn!("拒绝对端的文件请求: {}", message);
+            if let Some(sender) = self.input_sender.lock().await.as_ref() {
+                let _ = sender.send_file_response(&FileResponse::Error { id, file_num: 0, message }).await;
+            }
+        }
+    }
+}
+
+/// 每路视频流缓存的解码帧数量
+const FRAME_BUFFER_SIZE: usize = 3;
+
 /// RustDesk 视频流接收器（包装器）
+///
+/// 同时查看多个显示器时，被控端为每个显示器发送一路视频流，
+/// 每路视频流有独立的解码器和帧缓冲区（按显示器索引区分）
 pub struct RustDeskVideoStream {
     connection: Arc<Mutex<RustDeskConnection>>,
     is_running: Arc<Mutex<bool>>,
+    frame_buffers: Arc<Mutex<HashMap<usize, FrameBuffer>>>,
+    decode_task: Option<JoinHandle<()>>,
 }
 
 impl RustDeskVideoStream {
@@ -257,6 +1331,8 @@ impl RustDeskVideoStream {
         Self {
             connection,
             is_running: Arc::new(Mutex::new(false)),
+            frame_buffers: Arc::new(Mutex::new(HashMap::new())),
+            decode_task: None,
         }
     }
 
@@ -264,21 +1340,131 @@ impl RustDeskVideoStream {
     pub async fn start(&mut self) -> Result<(), String> {
         log::info!("启动视频流接收...");
 
+        let (receiver, peer_info) = {
+            let connection = self.connection.lock().await;
+            (
+                connection.take_video_receiver().await,
+                connection.peer_info().await,
+            )
+        };
+        let receiver = receiver.ok_or_else(|| "视频流接收器不可用".to_string())?;
+
+        // 已知显示器的分辨率；未知时按默认分辨率初始化，新视频流开始时会重新配置
+        let display_sizes: HashMap<usize, (u32, u32)> = peer_info
+            .map(|info| {
+                info.displays
+                    .iter()
+                    .enumerate()
+                    .map(|(index, display)| (index, (display.width, display.height)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
         *self.is_running.lock().await = true;
 
-        // TODO: 启动视频接收任务
-        // 这里应该创建一个后台任务来接收视频帧
+        self.decode_task = Some(tokio::spawn(Self::decode_loop(
+            receiver,
+            display_sizes,
+            self.frame_buffers.clone(),
+            self.is_running.clone(),
+        )));
 
         log::info!("视频流接收已启动");
         Ok(())
     }
 
+    /// 为指定显示器创建并初始化解码器
+    fn create_decoder(display: usize, size: Option<(u32, u32)>) -> Option<H264Decoder> {
+        let mut config = DecoderConfig::default();
+        if let Some((width, height)) = size {
+            config.width = width;
+            config.height = height;
+        }
+
+        let mut decoder = H264Decoder::new(config);
+        match decoder.initialize() {
+            Ok(()) => Some(decoder),
+            Err(e) => {
+                log::error!("初始化显示器 {} 的解码器失败: {}", display, e);
+                None
+            }
+        }
+    }
+
+    /// 解码循环：消费视频流事件，按显示器分别解码并写入帧缓冲区
+    async fn decode_loop(
+        mut receiver: mpsc::Receiver<VideoStreamEvent>,
+        display_sizes: HashMap<usize, (u32, u32)>,
+        frame_buffers: Arc<Mutex<HashMap<usize, FrameBuffer>>>,
+        is_running: Arc<Mutex<bool>>,
+    ) {
+        let mut decoders: HashMap<usize, H264Decoder> = HashMap::new();
+
+        while let Some(event) = receiver.recv().await {
+            match event {
+                VideoStreamEvent::Config(config) => {
+                    // 新视频流：旧的参考帧已失效，重置解码器并丢弃旧画面
+                    let result = match decoders.get_mut(&config.display) {
+                        Some(decoder) => decoder.reconfigure(config.width, config.height),
+                        None => {
+                            if let Some(decoder) = Self::create_decoder(
+                                config.display,
+                                Some((config.width, config.height)),
+                            ) {
+                                decoders.insert(config.display, decoder);
+                            }
+                            Ok(())
+                        }
+                    };
+                    if let Err(e) = result {
+                        log::error!("重置显示器 {} 的解码器失败: {}", config.display, e);
+                        continue;
+                    }
+                    if let Some(buffer) = frame_buffers.lock().await.get_mut(&config.display) {
+                        buffer.clear();
+                    }
+                }
+                VideoStreamEvent::Frame(frame) => {
+                    let decoder = match decoders.entry(frame.display) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => {
+                            let size = display_sizes.get(&frame.display).copied();
+                            match Self::create_decoder(frame.display, size) {
+                                Some(decoder) => entry.insert(decoder),
+                                None => continue,
+                            }
+                        }
+                    };
+
+                    match decoder.decode_frame(&frame.data) {
+                        Ok(mut decoded) => {
+                            decoded.timestamp = frame.timestamp;
+                            frame_buffers
+                                .lock()
+                                .await
+                                .entry(frame.display)
+                                .or_insert_with(|| FrameBuffer::new(FRAME_BUFFER_SIZE))
+                                .push(decoded);
+                        }
+                        Err(e) => log::warn!("解码显示器 {} 的视频帧失败: {}", frame.display, e),
+                    }
+                }
+            }
+        }
+
+        *is_running.lock().await = false;
+    }
+
     /// 停止视频流接收
     pub async fn stop(&mut self) -> Result<(), String> {
         log::info!("停止视频流接收...");
 
         *self.is_running.lock().await = false;
 
+        if let Some(task) = self.decode_task.take() {
+            task.abort();
+        }
+
         log::info!("视频流接收已停止");
         Ok(())
     }
@@ -287,6 +1473,23 @@ impl RustDeskVideoStream {
     pub async fn is_running(&self) -> bool {
         *self.is_running.lock().await
     }
+
+    /// 获取指定显示器最新解码帧的副本
+    pub async fn latest_frame(&self, display: usize) -> Option<DecodedFrame> {
+        self.frame_buffers
+            .lock()
+            .await
+            .get(&display)
+            .and_then(|buffer| buffer.get_latest().cloned())
+    }
+
+    /// 只保留指定显示器的画面（其余显示器已停止采集）
+    pub async fn retain_displays(&self, displays: &[usize]) {
+        self.frame_buffers
+            .lock()
+            .await
+            .retain(|display, _| displays.contains(display));
+    }
 }
 
 /// 辅助函数：生成随机 ID
@@ -320,4 +1523,39 @@ mod tests {
         // 注意：实际的连接测试需要 mock ID 服务器
         // 这里只测试状态转换逻辑
     }
+
+    #[tokio::test]
+    async fn test_oversized_clipboard_rejected() {
+        let conn = RustDeskConnection::new(RustDeskConfig::default());
+        let text = "a".repeat(protocol::MAX_CLIPBOARD_LEN + 1);
+        let error = conn.set_local_clipboard(ClipboardData::new(text)).await.unwrap_err();
+        assert!(error.contains("过长"));
+    }
+
+    #[tokio::test]
+    async fn test_decoded_frame_available_for_screenshot() {
+        let (sender, receiver) = mpsc::channel(4);
+        let frame_buffers = Arc::new(Mutex::new(HashMap::new()));
+        let sizes = HashMap::from([(0, (320, 240))]);
+        let task = tokio::spawn(RustDeskVideoStream::decode_loop(
+            receiver,
+            sizes,
+            frame_buffers.clone(),
+            Arc::new(Mutex::new(true)),
+        ));
+
+        let frame = protocol::VideoFrame { display: 0, width: 320, height: 240, data: vec![0; 16], timestamp: 7 };
+        sender.send(VideoStreamEvent::Frame(frame)).await.unwrap();
+        drop(sender);
+        task.await.unwrap();
+
+        // 解码后的帧写入帧缓冲区，截图取的就是这一帧
+        let latest = frame_buffers.lock().await.get(&0).and_then(|buffer| buffer.get_latest().cloned()).unwrap();
+        assert_eq!((latest.width, latest.height, latest.timestamp), (320, 240, 7));
+        let path = std::env::temp_dir().join("harmonydesk_stream_screenshot_test.png");
+        let shot = crate::screenshot::save_frame(&latest, path.to_str().unwrap(), crate::screenshot::ScreenshotFormat::Png)
+            .unwrap();
+        assert_eq!((shot.width, shot.height), (320, 240));
+        let _ = std::fs::remove_file(path);
+    }
 }
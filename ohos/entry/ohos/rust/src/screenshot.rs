@@ -0,0 +1,143 @@
+/**
+ * 截图导出模块
+ *
+ * 将会话中最新的解码帧转换为 RGBA，
+ * 并编码为 PNG 或 JPEG 文件保存到本地
+ */
+
+use crate::video::{DecodeError, DecodedFrame};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+use std::fs::File;
+use std::io::BufWriter;
+
+/// JPEG 编码质量
+const JPEG_QUALITY: u8 = 90;
+
+/// 截图错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum ScreenshotError {
+    #[error("No video frame available")]
+    NoFrame,
+
+    #[error("Unsupported image format: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("Frame conversion failed: {0}")]
+    Convert(#[from] DecodeError),
+
+    #[error("Encode failed: {0}")]
+    Encode(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 截图文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenshotFormat {
+    Png,
+    Jpeg,
+}
+
+impl ScreenshotFormat {
+    /// 从 ArkTS 传入的格式名解析（不区分大小写）
+    pub fn parse(name: &str) -> Result<Self, ScreenshotError> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Ok(ScreenshotFormat::Png),
+            "jpg" | "jpeg" => Ok(ScreenshotFormat::Jpeg),
+            _ => Err(ScreenshotError::UnsupportedFormat(name.to_string())),
+        }
+    }
+}
+
+/// 截图结果
+#[derive(Debug, Clone)]
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    pub path: String,
+}
+
+/// 将解码帧编码并写入文件
+pub fn save_frame(
+    frame: &DecodedFrame,
+    path: &str,
+    format: ScreenshotFormat,
+) -> Result<Screenshot, ScreenshotError> {
+    let rgba = frame.to_rgba()?;
+    let expected = (frame.width * frame.height * 4) as usize;
+    if rgba.len() < expected {
+        return Err(ScreenshotError::Convert(DecodeError::InvalidFrame(format!(
+            "RGBA buffer too small: {} < {}",
+            rgba.len(),
+            expected
+        ))));
+    }
+
+    let writer = BufWriter::new(File::create(path)?);
+
+    match format {
+        ScreenshotFormat::Png => {
+            PngEncoder::new(writer)
+                .write_image(&rgba[..expected], frame.width, frame.height, ColorType::Rgba8)
+                .map_err(|e| ScreenshotError::Encode(e.to_string()))?;
+        }
+        ScreenshotFormat::Jpeg => {
+            // JPEG 不支持透明通道，丢弃 Alpha
+            let rgb: Vec<u8> = rgba[..expected]
+                .chunks_exact(4)
+                .flat_map(|px| [px[0], px[1], px[2]])
+                .collect();
+            JpegEncoder::new_with_quality(writer, JPEG_QUALITY)
+                .write_image(&rgb, frame.width, frame.height, ColorType::Rgb8)
+                .map_err(|e| ScreenshotError::Encode(e.to_string()))?;
+        }
+    }
+
+    log::info!(
+        "Screenshot saved: {} ({}x{}, {:?})",
+        path,
+        frame.width,
+        frame.height,
+        format
+    );
+
+    Ok(Screenshot {
+        width: frame.width,
+        height: frame.height,
+        path: path.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video::PixelFormat;
+
+    #[test]
+    fn test_format_parse() {
+        assert_eq!(ScreenshotFormat::parse("PNG").unwrap(), ScreenshotFormat::Png);
+        assert_eq!(ScreenshotFormat::parse("jpg").unwrap(), ScreenshotFormat::Jpeg);
+        assert!(ScreenshotFormat::parse("bmp").is_err());
+    }
+
+    #[test]
+    fn test_save_png_and_jpeg() {
+        let frame = DecodedFrame::new(32, 16, PixelFormat::RGBA);
+        let dir = std::env::temp_dir();
+
+        let png_path = dir.join("harmonydesk_screenshot_test.png");
+        let shot = save_frame(&frame, png_path.to_str().unwrap(), ScreenshotFormat::Png).unwrap();
+        assert_eq!((shot.width, shot.height), (32, 16));
+        assert!(std::fs::metadata(&png_path).unwrap().len() > 0);
+
+        let jpg_path = dir.join("harmonydesk_screenshot_test.jpg");
+        save_frame(&frame, jpg_path.to_str().unwrap(), ScreenshotFormat::Jpeg).unwrap();
+        assert!(std::fs::metadata(&jpg_path).unwrap().len() > 0);
+
+        let _ = std::fs::remove_file(png_path);
+        let _ = std::fs::remove_file(jpg_path);
+    }
+}
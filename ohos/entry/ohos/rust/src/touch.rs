@@ -0,0 +1,259 @@
+/**
+ * 触摸直通模块
+ *
+ * 被控端支持触摸（Android 设备、触屏 Windows 设备）时，不再把触摸模拟成鼠标：
+ * - 每根手指的按下 / 移动 / 抬起作为触摸事件转发
+ * - 双指操作额外生成平移和缩放手势事件
+ *
+ * 输入与手势识别相同（sendTouch 的视图坐标），由调用方映射到远程坐标
+ */
+
+use crate::gesture::{TouchAction, TouchPoint};
+use crate::protocol::TouchEvent;
+
+/// 缩放比例的精度（千分比）
+const SCALE_UNIT: f32 = 1000.0;
+
+/// 按下的手指（远程坐标）
+#[derive(Debug, Clone, Copy)]
+struct Contact {
+    id: i32,
+    x: i32,
+    y: i32,
+}
+
+/// 进行中的双指手势
+#[derive(Debug, Clone, Copy)]
+struct TwoFinger {
+    start_distance: f32,
+    scale: i32,
+}
+
+/// 触摸直通状态（每个会话一个）
+#[derive(Debug, Default)]
+pub struct TouchPassthrough {
+    contacts: Vec<Contact>,
+    two_finger: Option<TwoFinger>,
+}
+
+impl TouchPassthrough {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 是否有按下的手指
+    pub fn is_active(&self) -> bool {
+        !self.contacts.is_empty()
+    }
+
+    /// 处理一次触摸事件（pointers 为本次变化的手指，map 将视图坐标映射到远程坐标）
+    pub fn handle(
+        &mut self,
+        pointers: &[TouchPoint],
+        action: TouchAction,
+        map: impl Fn(f32, f32) -> (i32, i32),
+    ) -> Vec<TouchEvent> {
+        match action {
+            TouchAction::Down => self.on_down(pointers, map),
+            TouchAction::Move => self.on_move(pointers, map),
+            TouchAction::Up => self.on_up(pointers, map),
+            TouchAction::Cancel => self.cancel(),
+        }
+    }
+
+    /// 抬起所有手指（触摸被系统取消、失去焦点时）
+    pub fn cancel(&mut self) -> Vec<TouchEvent> {
+        let mut events: Vec<_> = self.end_two_finger().into_iter().collect();
+        events.extend(
+            self.contacts
+                .drain(..)
+                .map(|contact| TouchEvent::Up { id: contact.id, x: contact.x, y: contact.y }),
+        );
+        events
+    }
+
+    fn on_down(
+        &mut self,
+        pointers: &[TouchPoint],
+        map: impl Fn(f32, f32) -> (i32, i32),
+    ) -> Vec<TouchEvent> {
+        let mut events = Vec::new();
+        for point in pointers {
+            let (x, y) = map(point.x, point.y);
+            // 重复的按下视为移动
+            match self.contacts.iter_mut().find(|contact| contact.id == point.id) {
+                Some(contact) => {
+                    (contact.x, contact.y) = (x, y);
+                    events.push(TouchEvent::Move { id: point.id, x, y });
+                }
+                None => {
+                    self.contacts.push(Contact { id: point.id, x, y });
+                    events.push(TouchEvent::Down { id: point.id, x, y });
+                }
+            }
+        }
+
+        if self.two_finger.is_none() && self.contacts.len() >= 2 {
+            let (x, y) = self.midpoint();
+            self.two_finger = Some(TwoFinger {
+                start_distance: self.distance().max(1.0),
+                scale: SCALE_UNIT as i32,
+            });
+            events.push(TouchEvent::PanStart { x, y });
+        }
+        events
+    }
+
+    fn on_move(
+        &mut self,
+        pointers: &[TouchPoint],
+        map: impl Fn(f32, f32) -> (i32, i32),
+    ) -> Vec<TouchEvent> {
+        let mut events = Vec::new();
+        for point in pointers {
+            let (x, y) = map(point.x, point.y);
+            let contact = match self.contacts.iter_mut().find(|contact| contact.id == point.id) {
+                Some(contact) => contact,
+                None => continue,
+            };
+            if (contact.x, contact.y) != (x, y) {
+                (contact.x, contact.y) = (x, y);
+                events.push(TouchEvent::Move { id: point.id, x, y });
+            }
+        }
+
+        if events.is_empty() {
+            return events;
+        }
+        let distance = self.distance();
+        let (x, y) = self.midpoint();
+        if let Some(two_finger) = self.two_finger.as_mut() {
+            events.push(TouchEvent::PanUpdate { x, y });
+            let scale = (distance / two_finger.start_distance * SCALE_UNIT).round() as i32;
+            if scale != two_finger.scale {
+                two_finger.scale = scale;
+                events.push(TouchEvent::ScaleUpdate { scale });
+            }
+        }
+        events
+    }
+
+    fn on_up(
+        &mut self,
+        pointers: &[TouchPoint],
+        map: impl Fn(f32, f32) -> (i32, i32),
+    ) -> Vec<TouchEvent> {
+        let mut events = Vec::new();
+        // 少于两根手指时先结束双指手势
+        let remaining = self
+            .contacts
+            .iter()
+            .filter(|contact| !pointers.iter().any(|point| point.id == contact.id))
+            .count();
+        if remaining < 2 {
+            events.extend(self.end_two_finger());
+        }
+
+        for point in pointers {
+            if let Some(index) = self.contacts.iter().position(|contact| contact.id == point.id) {
+                self.contacts.remove(index);
+                let (x, y) = map(point.x, point.y);
+                events.push(TouchEvent::Up { id: point.id, x, y });
+            }
+        }
+        events
+    }
+
+    fn end_two_finger(&mut self) -> Option<TouchEvent> {
+        self.two_finger.take()?;
+        let (x, y) = self.midpoint();
+        Some(TouchEvent::PanEnd { x, y })
+    }
+
+    /// 前两根手指的距离
+    fn distance(&self) -> f32 {
+        match self.contacts.as_slice() {
+            [a, b, ..] => {
+                let dx = (a.x - b.x) as f32;
+                let dy = (a.y - b.y) as f32;
+                (dx * dx + dy * dy).sqrt()
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// 前两根手指的中点
+    fn midpoint(&self) -> (i32, i32) {
+        match self.contacts.as_slice() {
+            [a, b, ..] => ((a.x + b.x) / 2, (a.y + b.y) / 2),
+            [a] => (a.x, a.y),
+            [] => (0, 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(id: i32, x: f32, y: f32) -> TouchPoint {
+        TouchPoint { id, x, y }
+    }
+
+    fn identity(x: f32, y: f32) -> (i32, i32) {
+        (x as i32, y as i32)
+    }
+
+    #[test]
+    fn test_single_finger_forwarded() {
+        let mut touch = TouchPassthrough::new();
+        let scaled = |x: f32, y: f32| ((x * 2.0) as i32, (y * 2.0) as i32);
+
+        let events = touch.handle(&[point(0, 10.0, 20.0)], TouchAction::Down, scaled);
+        assert_eq!(events, vec![TouchEvent::Down { id: 0, x: 20, y: 40 }]);
+        let events = touch.handle(&[point(0, 15.0, 20.0)], TouchAction::Move, scaled);
+        assert_eq!(events, vec![TouchEvent::Move { id: 0, x: 30, y: 40 }]);
+        let events = touch.handle(&[point(0, 15.0, 20.0)], TouchAction::Up, scaled);
+        assert_eq!(events, vec![TouchEvent::Up { id: 0, x: 30, y: 40 }]);
+        assert!(!touch.is_active());
+    }
+
+    #[test]
+    fn test_two_finger_pan_and_scale() {
+        let mut touch = TouchPassthrough::new();
+        touch.handle(&[point(0, 0.0, 0.0)], TouchAction::Down, identity);
+        let events = touch.handle(&[point(1, 100.0, 0.0)], TouchAction::Down, identity);
+        assert_eq!(
+            events,
+            vec![TouchEvent::Down { id: 1, x: 100, y: 0 }, TouchEvent::PanStart { x: 50, y: 0 }]
+        );
+
+        let events = touch.handle(&[point(1, 200.0, 0.0)], TouchAction::Move, identity);
+        assert_eq!(
+            events,
+            vec![
+                TouchEvent::Move { id: 1, x: 200, y: 0 },
+                TouchEvent::PanUpdate { x: 100, y: 0 },
+                TouchEvent::ScaleUpdate { scale: 2000 },
+            ]
+        );
+
+        let events = touch.handle(&[point(0, 0.0, 0.0)], TouchAction::Up, identity);
+        assert_eq!(
+            events,
+            vec![TouchEvent::PanEnd { x: 100, y: 0 }, TouchEvent::Up { id: 0, x: 0, y: 0 }]
+        );
+    }
+
+    #[test]
+    fn test_cancel_lifts_all_fingers() {
+        let mut touch = TouchPassthrough::new();
+        touch.handle(&[point(0, 0.0, 0.0), point(1, 10.0, 0.0)], TouchAction::Down, identity);
+
+        let events = touch.handle(&[], TouchAction::Cancel, identity);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0], TouchEvent::PanEnd { x: 5, y: 0 });
+        assert!(!touch.is_active());
+        assert!(touch.cancel().is_empty());
+    }
+}
@@ -300,6 +300,14 @@ impl H264Decoder {
         self.frame_count = 0;
         Ok(())
     }
+
+    /// 以新的分辨率重新初始化解码器（新视频流开始时调用）
+    pub fn reconfigure(&mut self, width: u32, height: u32) -> Result<(), DecodeError> {
+        self.reset()?;
+        self.config.width = width;
+        self.config.height = height;
+        self.initialize()
+    }
 }
 
 /// 视频帧缓冲区
@@ -0,0 +1,369 @@
+/**
+ * 视口模块
+ *
+ * 维护每个会话的显示视口，把视图坐标映射为远程桌面坐标：
+ * - 视图尺寸和适配模式（等比适配留黑边 / 填满裁剪 / 原始大小）
+ * - 缩放倍数和平移偏移
+ * - 当前显示器在远程虚拟桌面中的位置和分辨率
+ */
+
+use crate::protocol::DisplayInfo;
+
+/// 缩放范围（相对适配后的大小）
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 8.0;
+
+/// 画面适配模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitMode {
+    /// 等比缩放完整显示，多余部分留黑边
+    Fit,
+    /// 等比缩放填满视图，超出部分可平移查看
+    Fill,
+    /// 按远程分辨率 1:1 显示
+    Original,
+}
+
+impl FitMode {
+    /// 从 ArkTS 传入的模式名解析
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "fit" => Some(FitMode::Fit),
+            "fill" => Some(FitMode::Fill),
+            "original" => Some(FitMode::Original),
+            _ => None,
+        }
+    }
+}
+
+/// 视口状态（ArkTS 按此绘制画面）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewportState {
+    /// 远程像素到视图像素的缩放比例（含适配和缩放）
+    pub scale: f32,
+    /// 用户缩放倍数
+    pub zoom: f32,
+    /// 画面左上角在视图中的位置
+    pub offset_x: f32,
+    pub offset_y: f32,
+    /// 画面在视图中的大小
+    pub content_width: f32,
+    pub content_height: f32,
+}
+
+/// 会话视口
+#[derive(Debug, Clone)]
+pub struct Viewport {
+    view_width: f32,
+    view_height: f32,
+    fit: FitMode,
+    zoom: f32,
+    /// 相对居中位置的平移偏移（视图像素）
+    pan: (f32, f32),
+    /// 当前显示器（x, y, width, height）
+    display: Option<(i32, i32, u32, u32)>,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            view_width: 0.0,
+            view_height: 0.0,
+            fit: FitMode::Fit,
+            zoom: MIN_ZOOM,
+            pan: (0.0, 0.0),
+            display: None,
+        }
+    }
+}
+
+impl Viewport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 是否已设置视图尺寸和显示器，未就绪时坐标不做映射
+    pub fn is_ready(&self) -> bool {
+        self.view_width > 0.0
+            && self.view_height > 0.0
+            && matches!(self.display, Some((_, _, width, height)) if width > 0 && height > 0)
+    }
+
+    pub fn fit_mode(&self) -> FitMode {
+        self.fit
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// 设置视图尺寸（视图大小变化时平移范围随之调整）
+    pub fn set_view_size(&mut self, width: f32, height: f32) {
+        self.view_width = width.max(0.0);
+        self.view_height = height.max(0.0);
+        self.clamp_pan();
+    }
+
+    /// 设置适配模式（重置缩放和平移）
+    pub fn set_fit_mode(&mut self, fit: FitMode) {
+        if self.fit != fit {
+            self.fit = fit;
+            self.reset();
+        }
+    }
+
+    /// 设置当前显示器（显示器变化时重置缩放和平移）
+    pub fn set_display(&mut self, display: &DisplayInfo) {
+        let geometry = (display.x, display.y, display.width, display.height);
+        if self.display != Some(geometry) {
+            self.display = Some(geometry);
+            self.reset();
+        }
+    }
+
+    /// 重置缩放和平移
+    pub fn reset(&mut self) {
+        self.zoom = MIN_ZOOM;
+        self.pan = (0.0, 0.0);
+    }
+
+    /// 以视图坐标 (center_x, center_y) 为中心缩放到 zoom 倍，中心处的画面保持不动
+    pub fn set_zoom(&mut self, zoom: f32, center_x: f32, center_y: f32) {
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let (dw, dh) = match self.display_size() {
+            Some(size) if self.is_ready() => size,
+            _ => {
+                self.zoom = zoom;
+                return;
+            }
+        };
+
+        let old = self.state();
+        // 中心点对应的画面位置（远程像素）
+        let content_x = (center_x - old.offset_x) / old.scale;
+        let content_y = (center_y - old.offset_y) / old.scale;
+
+        self.zoom = zoom;
+        let scale = self.scale();
+        let centered = self.centered_origin(scale, dw, dh);
+        self.pan = (
+            center_x - content_x * scale - centered.0,
+            center_y - content_y * scale - centered.1,
+        );
+        self.clamp_pan();
+    }
+
+    /// 在当前缩放基础上再缩放 factor 倍
+    pub fn zoom_by(&mut self, factor: f32, center_x: f32, center_y: f32) {
+        self.set_zoom(self.zoom * factor, center_x, center_y);
+    }
+
+    /// 平移画面（视图像素）
+    pub fn pan_by(&mut self, dx: f32, dy: f32) {
+        self.pan.0 += dx;
+        self.pan.1 += dy;
+        self.clamp_pan();
+    }
+
+    /// 当前视口状态
+    pub fn state(&self) -> ViewportState {
+        let (dw, dh) = self.display_size().unwrap_or((0.0, 0.0));
+        let scale = self.scale();
+        let centered = self.centered_origin(scale, dw, dh);
+        ViewportState {
+            scale,
+            zoom: self.zoom,
+            offset_x: centered.0 + self.pan.0,
+            offset_y: centered.1 + self.pan.1,
+            content_width: dw * scale,
+            content_height: dh * scale,
+        }
+    }
+
+    /// 视图坐标 → 远程虚拟桌面坐标（黑边区域限制到显示器边缘）
+    pub fn view_to_remote(&self, x: f32, y: f32) -> Option<(i32, i32)> {
+        let (dx, dy, _, _) = self.display.filter(|_| self.is_ready())?;
+        let state = self.state();
+        let remote_x = dx as f32 + (x - state.offset_x) / state.scale;
+        let remote_y = dy as f32 + (y - state.offset_y) / state.scale;
+        Some(self.clamp_to_display(remote_x, remote_y))
+    }
+
+    /// 远程虚拟桌面坐标 → 视图坐标
+    pub fn remote_to_view(&self, x: i32, y: i32) -> Option<(f32, f32)> {
+        let (dx, dy, _, _) = self.display.filter(|_| self.is_ready())?;
+        let state = self.state();
+        Some((
+            state.offset_x + (x - dx) as f32 * state.scale,
+            state.offset_y + (y - dy) as f32 * state.scale,
+        ))
+    }
+
+    /// 按视图像素位移相对移动远程坐标（触控板模式）
+    pub fn move_remote_by(&self, from: (i32, i32), dx: f32, dy: f32) -> Option<(i32, i32)> {
+        if !self.is_ready() {
+            return None;
+        }
+        let scale = self.scale();
+        // 光标不在当前显示器时先移到显示器边缘
+        let (x, y) = self.clamp_to_display(from.0 as f32, from.1 as f32);
+        Some(self.clamp_to_display(x as f32 + dx / scale, y as f32 + dy / scale))
+    }
+
+    /// 视图中的位移 → 远程桌面中的位移（相对鼠标模式使用，未就绪时不缩放）
+    pub fn scale_delta(&self, dx: f32, dy: f32) -> (f32, f32) {
+        let scale = self.scale();
+        (dx / scale, dy / scale)
+    }
+
+    fn display_size(&self) -> Option<(f32, f32)> {
+        self.display.map(|(_, _, width, height)| (width as f32, height as f32))
+    }
+
+    /// 适配后的缩放比例
+    fn base_scale(&self) -> f32 {
+        let (dw, dh) = match self.display_size() {
+            Some(size) if self.is_ready() => size,
+            _ => return 1.0,
+        };
+        let scale_x = self.view_width / dw;
+        let scale_y = self.view_height / dh;
+        match self.fit {
+            FitMode::Fit => scale_x.min(scale_y),
+            FitMode::Fill => scale_x.max(scale_y),
+            FitMode::Original => 1.0,
+        }
+    }
+
+    fn scale(&self) -> f32 {
+        self.base_scale() * self.zoom
+    }
+
+    /// 画面居中时左上角的位置
+    fn centered_origin(&self, scale: f32, dw: f32, dh: f32) -> (f32, f32) {
+        (
+            (self.view_width - dw * scale) / 2.0,
+            (self.view_height - dh * scale) / 2.0,
+        )
+    }
+
+    /// 画面小于视图时居中，大于视图时不允许平移出边界
+    fn clamp_pan(&mut self) {
+        let state = self.state();
+        let slack_x = ((state.content_width - self.view_width) / 2.0).max(0.0);
+        let slack_y = ((state.content_height - self.view_height) / 2.0).max(0.0);
+        self.pan.0 = self.pan.0.clamp(-slack_x, slack_x);
+        self.pan.1 = self.pan.1.clamp(-slack_y, slack_y);
+    }
+
+    fn clamp_to_display(&self, x: f32, y: f32) -> (i32, i32) {
+        let (dx, dy, width, height) = match self.display {
+            Some(display) => display,
+            None => return (x as i32, y as i32),
+        };
+        let x = (x.floor() as i32).clamp(dx, dx + width as i32 - 1);
+        let y = (y.floor() as i32).clamp(dy, dy + height as i32 - 1);
+        (x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(x: i32, y: i32, width: u32, height: u32) -> DisplayInfo {
+        DisplayInfo {
+            x,
+            y,
+            width,
+            height,
+            scale: 1.0,
+            is_primary: x == 0 && y == 0,
+            name: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_letterbox_mapping() {
+        let mut viewport = Viewport::new();
+        assert!(viewport.view_to_remote(10.0, 10.0).is_none());
+
+        // 1920x1080 显示在 1000x1000 视图中：缩放 0.5，上下各留 230 像素黑边
+        viewport.set_view_size(1000.0, 1000.0);
+        viewport.set_display(&display(0, 0, 1920, 1080));
+        let state = viewport.state();
+        assert!((state.scale - 1000.0 / 1920.0).abs() < 1e-6);
+        assert!((state.offset_y - (1000.0 - 1080.0 * state.scale) / 2.0).abs() < 1e-3);
+
+        assert_eq!(viewport.view_to_remote(500.0, 500.0), Some((960, 540)));
+        // 黑边区域限制到显示器边缘
+        assert_eq!(viewport.view_to_remote(0.0, 0.0), Some((0, 0)));
+        assert_eq!(viewport.view_to_remote(1000.0, 1000.0), Some((1919, 1079)));
+    }
+
+    #[test]
+    fn test_secondary_display_origin() {
+        let mut viewport = Viewport::new();
+        viewport.set_view_size(960.0, 540.0);
+        // 副显示器位于主显示器左侧
+        viewport.set_display(&display(-1920, 0, 1920, 1080));
+
+        assert_eq!(viewport.view_to_remote(0.0, 0.0), Some((-1920, 0)));
+        assert_eq!(viewport.view_to_remote(480.0, 270.0), Some((-960, 540)));
+        assert_eq!(viewport.remote_to_view(-960, 540), Some((480.0, 270.0)));
+    }
+
+    #[test]
+    fn test_zoom_keeps_center_and_pan_is_clamped() {
+        let mut viewport = Viewport::new();
+        viewport.set_view_size(960.0, 540.0);
+        viewport.set_display(&display(0, 0, 1920, 1080));
+
+        let before = viewport.view_to_remote(240.0, 135.0);
+        viewport.set_zoom(2.0, 240.0, 135.0);
+        assert_eq!(viewport.view_to_remote(240.0, 135.0), before);
+        assert_eq!(viewport.state().scale, 1.0);
+
+        // 平移不能超出画面边界
+        viewport.pan_by(10_000.0, 10_000.0);
+        assert_eq!(viewport.view_to_remote(0.0, 0.0), Some((0, 0)));
+        viewport.pan_by(-10_000.0, -10_000.0);
+        assert_eq!(viewport.view_to_remote(959.0, 539.0), Some((1919, 1079)));
+
+        // 缩放范围
+        viewport.zoom_by(100.0, 0.0, 0.0);
+        assert_eq!(viewport.zoom(), MAX_ZOOM);
+
+        // 切换显示器重置缩放
+        viewport.set_display(&display(1920, 0, 1280, 720));
+        assert_eq!(viewport.zoom(), MIN_ZOOM);
+    }
+
+    #[test]
+    fn test_fill_and_original_modes() {
+        let mut viewport = Viewport::new();
+        viewport.set_view_size(1000.0, 1000.0);
+        viewport.set_display(&display(0, 0, 2000, 1000));
+
+        viewport.set_fit_mode(FitMode::Fill);
+        let state = viewport.state();
+        assert_eq!(state.scale, 1.0);
+        assert_eq!(state.offset_x, -500.0);
+        assert_eq!(viewport.view_to_remote(0.0, 0.0), Some((500, 0)));
+
+        viewport.set_fit_mode(FitMode::Original);
+        viewport.pan_by(500.0, 0.0);
+        assert_eq!(viewport.view_to_remote(0.0, 0.0), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_relative_move_scales_with_view() {
+        let mut viewport = Viewport::new();
+        viewport.set_view_size(960.0, 540.0);
+        viewport.set_display(&display(0, 0, 1920, 1080));
+
+        // 视图缩放 0.5，手指移动 10 像素对应远程 20 像素
+        assert_eq!(viewport.move_remote_by((100, 100), 10.0, -10.0), Some((120, 80)));
+        assert_eq!(viewport.move_remote_by((100, 100), -1000.0, 0.0), Some((0, 100)));
+    }
+}
@@ -10,6 +10,136 @@ export interface NativeVideoFrame {
   timestamp: number;
 }
 
+export interface NativeCursor {
+  x: number;
+  y: number;
+  id?: number;
+  hotX?: number;
+  hotY?: number;
+  width?: number;
+  height?: number;
+  data?: ArrayBuffer;
+}
+
+export interface NativeScreenshot {
+  width: number;
+  height: number;
+  path: string;
+}
+
+export interface NativeDisplayInfo {
+  index: number;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  scale: number;
+  isPrimary: boolean;
+  name: string;
+}
+
+export interface NativeDisplayList {
+  current: number;
+  displays: NativeDisplayInfo[];
+}
+
+export interface NativeSessionInfo {
+  id: string;
+  connected: boolean;
+  screenWidth: number;
+  screenHeight: number;
+  username: string;
+  hostname: string;
+  platform: string;
+  version: string;
+  currentDisplay: number;
+  displays: NativeDisplayInfo[];
+  features: string[];
+  supportsTouch: boolean;
+}
+
+export interface NativeTouchPointer {
+  id: number;
+  x: number;
+  y: number;
+}
+
+export interface NativeTransferEvent {
+  /** Transfer job ID returned by startUpload / startDownload */
+  id: number;
+  /** "progress" | "done" | "failed" | "cancelled" */
+  type: string;
+  /** Index of the file being transferred (progress only) */
+  fileNum?: number;
+  fileCount?: number;
+  /** Bytes transferred, including skipped and resumed files (progress only) */
+  transferred?: number;
+  total?: number;
+  /** Error message (failed only) */
+  error?: string;
+}
+
+export interface NativeFileEntry {
+  /** Base name (relative path for search results) */
+  name: string;
+  /** "dir" | "dirLink" | "drive" | "file" | "fileLink" */
+  type: string;
+  hidden: boolean;
+  /** Size in bytes (0 for directories) */
+  size: number;
+  /** Modification time in Unix seconds */
+  modified: number;
+}
+
+export interface NativeFileOperationResult {
+  /** Operation ID returned by readRemoteDir / createRemoteDir / removeRemoteFile / renameRemoteFile / searchRemoteFiles */
+  id: number;
+  /** Target path of the operation */
+  path: string;
+  success: boolean;
+  /** Directory entries or search matches (empty for other operations) */
+  entries: NativeFileEntry[];
+  /** Error message (failure only) */
+  error?: string;
+}
+
+export interface NativeDirListing {
+  path: string;
+  includeHidden: boolean;
+  entries: NativeFileEntry[];
+}
+
+export interface NativeAudioFormat {
+  /** 8000 | 12000 | 16000 | 24000 | 48000 */
+  sampleRate: number;
+  /** 1 (mono) or 2 (stereo) */
+  channels: number;
+}
+
+export interface NativeChatMessage {
+  text: string;
+  /** Sent or received time in milliseconds since the epoch */
+  timestamp: number;
+  /** true for messages from the remote side */
+  incoming: boolean;
+}
+
+export interface NativeClipboardRecord {
+  /** "text/plain" | "text/rtf" | "text/html" | "image/png" | "application/x-file-list" (one absolute path per line) */
+  mime: string;
+  /** String for text formats, ArrayBuffer (PNG bytes) for images */
+  data: string | ArrayBuffer;
+}
+
+export interface NativeViewportState {
+  scale: number;
+  zoom: number;
+  offsetX: number;
+  offsetY: number;
+  contentWidth: number;
+  contentHeight: number;
+}
+
 export interface HarmonyDeskNativeModule {
   /**
    * Initialize the native module
@@ -53,30 +183,530 @@ export interface HarmonyDeskNativeModule {
 
   /**
    * Send keyboard event
+   * @param session - Session (remote desktop) ID
    * @param keyCode - Key code
    * @param pressed - Whether key is pressed
+   * @returns 0 on success, 1 on failure
    */
-  sendKeyEvent(keyCode: number, pressed: boolean): void;
+  sendKeyEvent(session: string, keyCode: number, pressed: boolean): number;
+
+  /**
+   * Set how key events are sent to the remote side
+   * @param session - Session (remote desktop) ID
+   * @param mode - "map" (physical keys), "translate" (characters) or "legacy"
+   * @returns 0 on success, 1 on failure
+   */
+  setKeyboardMode(session: string, mode: string): number;
+
+  /**
+   * Send text typed on the soft keyboard or committed by the IME
+   * @param session - Session (remote desktop) ID
+   * @param text - Text to type on the remote side (newlines are sent as Enter)
+   * @returns 0 on success, 1 on failure
+   */
+  sendText(session: string, text: string): number;
 
   /**
    * Send mouse move event
-   * @param x - X coordinate
-   * @param y - Y coordinate
+   * @param session - Session (remote desktop) ID
+   * @param x - X coordinate (view coordinate once setViewport is called)
+   * @param y - Y coordinate (view coordinate once setViewport is called)
+   * @returns 0 on success, 1 on failure
    */
-  sendMouseMove(x: number, y: number): void;
+  sendMouseMove(session: string, x: number, y: number): number;
 
   /**
    * Send mouse click event
-   * @param button - Button number (0 = left, 1 = middle, 2 = right)
+   * @param session - Session (remote desktop) ID
+   * @param button - Button number (0 = left, 1 = middle, 2 = right, 3 = back, 4 = forward)
    * @param pressed - Whether button is pressed
+   * @returns 0 on success, 1 on failure
+   */
+  sendMouseClick(session: string, button: number, pressed: boolean): number;
+
+  /**
+   * Send mouse wheel event
+   * @param session - Session (remote desktop) ID
+   * @param dx - Horizontal scroll amount
+   * @param dy - Vertical scroll amount
+   * @returns 0 on success, 1 on failure
+   */
+  sendMouseWheel(session: string, dx: number, dy: number): number;
+
+  /**
+   * Send a relative mouse movement (requires relative mouse mode)
+   * @param session - Session (remote desktop) ID
+   * @param dx - Horizontal movement in remote pixels
+   * @param dy - Vertical movement in remote pixels
+   * @returns 0 on success, 1 on failure
+   */
+  sendMouseRelative(session: string, dx: number, dy: number): number;
+
+  /**
+   * Turn relative mouse mode on or off, for games and 3D/CAD tools that lock the pointer.
+   * While on, touchpad-mode finger movement is sent as relative movement too
+   * @param session - Session (remote desktop) ID
+   * @param enabled - Whether relative mouse mode is on
+   * @returns 0 on success, 1 on failure
+   */
+  setRelativeMouse(session: string, enabled: boolean): number;
+
+  /**
+   * Send a touch event. Recognised as mouse gestures (tap, two-finger tap, long-press drag, pan, pinch),
+   * or forwarded as native touch (plus two-finger pan / scale) when the peer supports touch
+   * @param session - Session (remote desktop) ID
+   * @param pointers - Pointers changed by this event (changedTouches), in view coordinates
+   * @param action - "down", "move", "up" or "cancel"
+   * @returns Current viewport state (pinch changes the zoom), or null on failure
+   */
+  sendTouch(session: string, pointers: NativeTouchPointer[], action: string): NativeViewportState | null;
+
+  /**
+   * Set how touches move the remote cursor
+   * @param session - Session (remote desktop) ID
+   * @param mode - "touchpad" (relative cursor) or "direct" (absolute)
+   * @returns 0 on success, 1 on failure
+   */
+  setTouchMode(session: string, mode: string): number;
+
+  /**
+   * Send a special action to the remote side
+   * @param session - Session (remote desktop) ID
+   * @param action - "ctrl_alt_del", "lock_screen", "switch_app" (Win/Cmd+Tab),
+   *   "close_window" (Alt+F4), "print_screen", or for Android peers "back" / "home" / "recents"
+   * @returns 0 on success, 1 on failure
+   */
+  sendSpecialAction(session: string, action: string): number;
+
+  /**
+   * Set how often queued input is sent; consecutive mouse moves in between are merged
+   * @param session - Session (remote desktop) ID
+   * @param rate - Sends per second (default 60, 0 = send every event immediately)
+   * @returns 0 on success, 1 on failure
+   */
+  setInputRate(session: string, rate: number): number;
+
+  /**
+   * Release every key, modifier and mouse button still held on the remote side
+   * (call when the app loses focus)
+   * @param session - Session (remote desktop) ID
+   * @returns 0 on success, 1 on failure
+   */
+  releaseAllInputs(session: string): number;
+
+  /**
+   * Set the clipboard sync policy for all sessions (PolicyConfig.clipboardSync)
+   * @param enabled - Whether text clipboard is synced in both directions
+   * @returns 0 on success
+   */
+  setClipboardSync(enabled: boolean): number;
+
+  /**
+   * Send the local clipboard text to the peer
+   * Text is limited to 4MB; text over 8KB is only announced and transferred in
+   * blocks when the peer reads it
+   * @param session - Session (remote desktop) ID
+   * @param text - Local clipboard text
+   * @returns 0 if sent, 1 on failure (e.g. too large), 2 if not sent (sync off, or the text just came from the peer)
+   */
+  setLocalClipboard(session: string, text: string): number;
+
+  /**
+   * Send the local clipboard with all its formats to the peer
+   * Text formats are limited to 4MB and images to 16MB; contents over 8KB are
+   * only announced and transferred in blocks when the peer reads them
+   * @param session - Session (remote desktop) ID
+   * @param records - One record per format; unsupported formats are ignored
+   * @returns 0 if sent, 1 on failure (e.g. too large), 2 if not sent (sync off, or the content just came from the peer)
+   */
+  setLocalClipboardData(session: string, records: NativeClipboardRecord[]): number;
+
+  /**
+   * Take the latest remote clipboard change
+   * @param session - Session (remote desktop) ID
+   * @returns MIME types available on the remote clipboard, or null if it has not changed
+   */
+  getRemoteClipboardFormats(session: string): string[] | null;
+
+  /**
+   * Read one format of the remote clipboard
+   * Large contents are requested from the peer on the first read; null is returned
+   * until they arrive, so callers should retry later
+   * @param session - Session (remote desktop) ID
+   * @param mime - One of the formats returned by getRemoteClipboardFormats
+   * @returns String for text formats, ArrayBuffer for images, or null if not (yet) available
+   */
+  readRemoteClipboard(session: string, mime: string): string | ArrayBuffer | null;
+
+  /**
+   * Set the file transfer policy for all sessions (PolicyConfig.fileTransfer)
+   * Disabling it closes all file transfer connections
+   * @param enabled - Whether file transfer is allowed
+   * @returns 0 on success
+   */
+  setFileTransferEnabled(enabled: boolean): number;
+
+  /**
+   * Open a file transfer connection (separate from the remote desktop connection)
+   * @param session - Session (remote desktop) ID
+   * @param password - Connection password
+   * @returns 0 on success, 1 on failure (including policy disabled)
+   */
+  connectFileTransfer(session: string, password: string): number;
+
+  /**
+   * Close the file transfer connection, cancelling running transfers
+   * @param session - Session (remote desktop) ID
+   * @returns 0 on success, 1 on failure
+   */
+  disconnectFileTransfer(session: string): number;
+
+  /**
+   * Upload a local file or directory
+   * @param session - Session (remote desktop) ID
+   * @param localPath - Local file or directory
+   * @param remotePath - Remote destination path
+   * @returns Transfer job ID, or -1 on failure
+   */
+  startUpload(session: string, localPath: string, remotePath: string): number;
+
+  /**
+   * Download a remote file or directory
+   * Files identical to local ones are skipped; interrupted files resume from the last block
+   * @param session - Session (remote desktop) ID
+   * @param remotePath - Remote file or directory
+   * @param localPath - Local destination path
+   * @param includeHidden - Whether hidden files in directories are included
+   * @returns Transfer job ID, or -1 on failure
+   */
+  startDownload(session: string, remotePath: string, localPath: string, includeHidden: boolean): number;
+
+  /**
+   * Cancel a transfer; partial files are kept so the next transfer resumes
+   * @param session - Session (remote desktop) ID
+   * @param id - Transfer job ID
+   * @returns 0 if cancelled, 1 if no such transfer
+   */
+  cancelFileTransfer(session: string, id: number): number;
+
+  /**
+   * Set the bandwidth cap for transfers started afterwards
+   * @param session - Session (remote desktop) ID
+   * @param bytesPerSecond - Cap in bytes per second (0 = unlimited)
+   * @returns 0 on success, 1 on failure
+   */
+  setFileTransferRate(session: string, bytesPerSecond: number): number;
+
+  /**
+   * Take pending file transfer events (progress is coalesced per job)
+   * @param session - Session (remote desktop) ID
+   * @returns Events since the last call
+   */
+  getFileTransferEvents(session: string): NativeTransferEvent[];
+
+  /**
+   * Read one level of a remote directory over the file transfer connection
+   * The result is also cached as the session's latest listing
+   * @param session - Session (remote desktop) ID
+   * @param path - Remote directory
+   * @param includeHidden - Whether hidden entries are listed
+   * @returns Operation ID, or -1 on failure
    */
-  sendMouseClick(button: number, pressed: boolean): void;
+  readRemoteDir(session: string, path: string, includeHidden: boolean): number;
+
+  /**
+   * Create a remote directory, including missing parents
+   * @param session - Session (remote desktop) ID
+   * @param path - Remote directory to create
+   * @returns Operation ID, or -1 on failure
+   */
+  createRemoteDir(session: string, path: string): number;
+
+  /**
+   * Delete a remote file, or a directory with all its contents
+   * @param session - Session (remote desktop) ID
+   * @param path - Remote file or directory
+   * @returns Operation ID, or -1 on failure
+   */
+  removeRemoteFile(session: string, path: string): number;
+
+  /**
+   * Rename a remote file or directory within its directory
+   * @param session - Session (remote desktop) ID
+   * @param path - Remote file or directory
+   * @param newName - New name (no path separators)
+   * @returns Operation ID, or -1 on failure (including an invalid name)
+   */
+  renameRemoteFile(session: string, path: string, newName: string): number;
+
+  /**
+   * Recursively search a remote directory for names matching a wildcard pattern
+   * @param session - Session (remote desktop) ID
+   * @param path - Remote directory to search
+   * @param pattern - Pattern with * and ? wildcards
+   * @param includeHidden - Whether hidden entries are searched
+   * @returns Operation ID, or -1 on failure
+   */
+  searchRemoteFiles(session: string, path: string, pattern: string, includeHidden: boolean): number;
+
+  /**
+   * Take results of finished remote file operations
+   * @param session - Session (remote desktop) ID
+   * @returns Results since the last call
+   */
+  getFileOperationResults(session: string): NativeFileOperationResult[];
+
+  /**
+   * Get the latest remote directory listing read in the session
+   * Creating, deleting and renaming entries in that directory update it
+   * @param session - Session (remote desktop) ID
+   * @returns Cached listing, or null if no directory has been read
+   */
+  getRemoteDirListing(session: string): NativeDirListing | null;
+
+  /**
+   * Announce locally copied files to the peer; the peer fetches them over the
+   * file transfer connection when it pastes (ignored while file transfer is disabled)
+   * @param session - Session (remote desktop) ID
+   * @param paths - Absolute local paths of files or directories
+   * @returns 0 sent, 1 failed, 2 not sent (sync disabled or content just received from the peer)
+   */
+  setLocalClipboardFiles(session: string, paths: string[]): number;
+
+  /**
+   * Read the files on the remote clipboard
+   * @param session - Session (remote desktop) ID
+   * @returns Remote paths, or null if there are none or the list has not arrived yet (retry later)
+   */
+  getRemoteClipboardFiles(session: string): string[] | null;
+
+  /**
+   * Paste the files on the remote clipboard by downloading them into a local directory
+   * Requires an open file transfer connection; progress is reported by getFileTransferEvents
+   * @param session - Session (remote desktop) ID
+   * @param localDir - Local destination directory
+   * @returns Transfer job IDs, empty on failure
+   */
+  pasteRemoteClipboardFiles(session: string, localDir: string): number[];
+
+  /**
+   * Get the format of the remote audio, used to configure the audio renderer
+   * @param session - Session (remote desktop) ID
+   * @returns Audio format, or null if no audio has been received
+   */
+  getRemoteAudioFormat(session: string): NativeAudioFormat | null;
+
+  /**
+   * Pull decoded remote audio for the audio renderer
+   * Playback starts after a short jitter buffer fills; missing samples are silence
+   * @param session - Session (remote desktop) ID
+   * @param frames - Number of frames (samples per channel) to read
+   * @returns Interleaved 16-bit little-endian PCM, or null if no audio has been received
+   */
+  readRemoteAudio(session: string, frames: number): ArrayBuffer | null;
+
+  /**
+   * Mute or unmute the session; the peer is told to stop or resume sending audio
+   * @param session - Session (remote desktop) ID
+   * @param muted - Whether audio is muted
+   * @returns 0 on success, 1 on failure
+   */
+  setAudioMuted(session: string, muted: boolean): number;
+
+  /**
+   * Start a voice call, or accept an incoming call from the peer
+   * Call audio from the peer plays through the remote audio path
+   * @param session - Session (remote desktop) ID
+   * @returns 0 on success, 1 on failure
+   */
+  startVoiceCall(session: string): number;
+
+  /**
+   * Hang up, cancel an outgoing call or reject an incoming call
+   * @param session - Session (remote desktop) ID
+   * @returns 0 on success, 1 on failure
+   */
+  endVoiceCall(session: string): number;
+
+  /**
+   * Get the voice call state
+   * @param session - Session (remote desktop) ID
+   * @returns 'idle' | 'calling' | 'incoming' | 'active'
+   */
+  getVoiceCallState(session: string): string;
+
+  /**
+   * Send microphone audio during a voice call (Opus-encoded natively)
+   * @param session - Session (remote desktop) ID
+   * @param samples - 16 kHz mono 16-bit little-endian PCM
+   * @returns 0 on success, 1 on failure (e.g. no active call)
+   */
+  pushMicrophonePcm(session: string, samples: ArrayBuffer): number;
+
+  /**
+   * Send a chat message to the user at the remote machine
+   * @param session - Session (remote desktop) ID
+   * @param text - Message text (trimmed, at most 4096 bytes)
+   * @returns The message as recorded in the chat history, or null on failure
+   */
+  sendChatMessage(session: string, text: string): NativeChatMessage | null;
+
+  /**
+   * Get the session's chat history, oldest first
+   * @param session - Session (remote desktop) ID
+   * @returns Sent and received messages
+   */
+  getChatHistory(session: string): NativeChatMessage[];
+
+  /**
+   * Take chat messages received since the last call
+   * @param session - Session (remote desktop) ID
+   * @returns Newly received messages
+   */
+  takeChatMessages(session: string): NativeChatMessage[];
+
+  /**
+   * Set a session option; options set before connecting are sent at login
+   * and kept across reconnects
+   * Keys and values:
+   * - 'image-quality': 'best' | 'balanced' | 'low' | custom bitrate percentage ('10'-'100')
+   * - 'custom-fps': '1'-'120'
+   * - 'show-remote-cursor' | 'disable-audio' | 'disable-clipboard' | 'lock-after-session-end': 'true' | 'false'
+   * @param session - Session (remote desktop) ID
+   * @param key - Option name
+   * @param value - Option value
+   * @returns 0 on success, 1 on failure (unknown key or invalid value)
+   */
+  setSessionOption(session: string, key: string, value: string): number;
+
+  /**
+   * Import input macros, replacing all existing ones
+   * @param json - JSON array of { name, steps }; each step is
+   *   { type: "key", code, down? } | { type: "text", text } |
+   *   { type: "click", button?, x?, y? } | { type: "delay", ms };
+   *   click x / y are remote desktop coordinates, independent of the viewport
+   * @returns 0 on success, 1 on failure (invalid JSON or macro)
+   */
+  setMacros(json: string): number;
+
+  /**
+   * Export all input macros
+   * @returns JSON array of macros
+   */
+  getMacros(): string;
+
+  /**
+   * Run a macro against a session in the background
+   * @param session - Session (remote desktop) ID
+   * @param name - Macro name
+   * @returns 0 if started, 1 on failure (unknown macro, or a macro is already running)
+   */
+  runMacro(session: string, name: string): number;
+
+  /**
+   * Cancel the macro running in a session; keys it holds are released
+   * @param session - Session (remote desktop) ID
+   * @returns 0 if a macro was cancelled, 1 if none was running
+   */
+  cancelMacro(session: string): number;
+
+  /**
+   * Set the view the remote display is drawn in; input coordinates are then view coordinates
+   * @param session - Session (remote desktop) ID
+   * @param width - View width
+   * @param height - View height
+   * @param fitMode - "fit" (letterbox), "fill" (crop) or "original" (1:1)
+   * @returns 0 on success, 1 on failure
+   */
+  setViewport(session: string, width: number, height: number, fitMode: string): number;
+
+  /**
+   * Zoom the remote display around a view point
+   * @param session - Session (remote desktop) ID
+   * @param zoom - Zoom factor (1 - 8)
+   * @param centerX - Zoom center X in view coordinates
+   * @param centerY - Zoom center Y in view coordinates
+   * @returns Viewport state, or null on failure
+   */
+  setViewZoom(session: string, zoom: number, centerX: number, centerY: number): NativeViewportState | null;
+
+  /**
+   * Pan the zoomed remote display
+   * @param session - Session (remote desktop) ID
+   * @param dx - Horizontal offset in view pixels
+   * @param dy - Vertical offset in view pixels
+   * @returns Viewport state, or null on failure
+   */
+  panView(session: string, dx: number, dy: number): NativeViewportState | null;
+
+  /**
+   * Get where the remote display is drawn in the view
+   * @param session - Session (remote desktop) ID
+   * @returns Viewport state, or null if the session does not exist
+   */
+  getViewport(session: string): NativeViewportState | null;
 
   /**
    * Get latest video frame
+   * @param session - Session (remote desktop) ID
+   * @param displayIndex - Display index (defaults to the current display)
    * @returns Video frame or null if no frame available
    */
-  getVideoFrame(): NativeVideoFrame | null;
+  getVideoFrame(session: string, displayIndex?: number): NativeVideoFrame | null;
+
+  /**
+   * Get the remote cursor image (RGBA) and position in remote desktop coordinates
+   * @param session - Session (remote desktop) ID
+   * @returns Cursor (image fields are absent until the peer sends one), or null if the session does not exist
+   */
+  getCursor(session: string): NativeCursor | null;
+
+  /**
+   * Enable or disable drawing the remote cursor into getVideoFrame() output
+   * @param session - Session (remote desktop) ID
+   * @param enabled - Whether to composite the cursor
+   * @returns 0 on success, 1 on failure
+   */
+  setCursorComposite(session: string, enabled: boolean): number;
+
+  /**
+   * Save the latest remote frame of a session to an image file
+   * @param session - Session (remote desktop) ID
+   * @param path - Output file path
+   * @param format - Image format ("png" or "jpeg")
+   * @returns Output dimensions and file path, or null on failure
+   */
+  takeScreenshot(session: string, path: string, format: string): NativeScreenshot | null;
+
+  /**
+   * Switch the remote display shown in a session
+   * @param session - Session (remote desktop) ID
+   * @param index - Display index from getDisplays()
+   * @returns 0 on success, 1 on failure
+   */
+  switchDisplay(session: string, index: number): number;
+
+  /**
+   * Get the remote display list of a session
+   * @param session - Session (remote desktop) ID
+   * @returns Display list and current display index, or null if the session does not exist
+   */
+  getDisplays(session: string): NativeDisplayList | null;
+
+  /**
+   * View several remote displays at once (one video stream per display)
+   * @param session - Session (remote desktop) ID
+   * @param indices - Display indices to capture
+   * @returns 0 on success, 1 on failure
+   */
+  captureDisplays(session: string, indices: number[]): number;
+
+  /**
+   * Get remote device details received at login
+   * @param session - Session (remote desktop) ID
+   * @returns Session info, or null if the session does not exist
+   */
+  getSessionInfo(session: string): NativeSessionInfo | null;
 }
 
 export default {} as HarmonyDeskNativeModule;